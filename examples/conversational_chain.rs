@@ -25,11 +25,11 @@ async fn main() {
         // The following is a friendly conversation between a human and an AI. The AI is talkative and provides lots of specific details from its context. If the AI does not know the answer to a question, it truthfully says it does not know.
         //
         // Current conversation:
-        // {history}
+        // {chat_history}
         // Human: {input}
         // AI:
         // ",
-        //             "input","history")))
+        //             "input","chat_history")))
         //
         //         ])
         .memory(memory.into())
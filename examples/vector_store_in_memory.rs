@@ -0,0 +1,60 @@
+// To run this example execute: cargo run --example vector_store_in_memory
+// The documents are saved to ./data/in_memory_store.json and loaded back on the next run.
+
+use langchain_rust::{
+    embedding::openai::openai_embedder::OpenAiEmbedder,
+    schemas::Document,
+    vectorstore::{memory::StoreBuilder, VecStoreOptions, VectorStore},
+};
+use std::{io::Write, path::Path};
+
+#[tokio::main]
+async fn main() {
+    // Initialize Embedder
+    let embedder = OpenAiEmbedder::default();
+
+    // Initialize the in-memory Vector Store
+    let store = StoreBuilder::new().embedder(embedder).build().unwrap();
+
+    let path = Path::new("./data/in_memory_store.json");
+    if path.exists() {
+        store.load(path).await.unwrap();
+    } else {
+        // Add documents to the store
+        let doc1 = Document::new(
+            "langchain-rust is a port of the langchain python library to rust and was written in 2024.",
+        );
+        let doc2 = Document::new(
+            "langchaingo is a port of the langchain python library to go language and was written in 2023."
+        );
+        let doc3 = Document::new(
+            "Capital of United States of America (USA) is Washington D.C. and the capital of France is Paris."
+        );
+        let doc4 = Document::new("Capital of France is Paris.");
+
+        store
+            .add_documents(&[doc1, doc2, doc3, doc4], &VecStoreOptions::default())
+            .await
+            .unwrap();
+        store.save(path).await.unwrap();
+    }
+
+    // Ask for user input
+    print!("Query> ");
+    std::io::stdout().flush().unwrap();
+    let mut query = String::new();
+    std::io::stdin().read_line(&mut query).unwrap();
+
+    let results = store
+        .similarity_search(&query, 2, &VecStoreOptions::default())
+        .await
+        .unwrap();
+
+    if results.is_empty() {
+        println!("No results found.");
+    } else {
+        results.iter().for_each(|r| {
+            println!("Document: {} (score: {:.3})", r.page_content, r.score);
+        });
+    }
+}
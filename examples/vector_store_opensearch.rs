@@ -119,6 +119,7 @@ async fn add_documents_to_index(store: &Store) -> Result<Vec<String>, Box<dyn Er
         name_space: None,
         score_threshold: None,
         filters: None,
+        metadata_filter: None,
         embedder: Some(store.embedder.clone()),
     };
 
@@ -1,14 +1,16 @@
 use std::{collections::HashMap, fmt::Display};
 
-use tracing::{info_span, Instrument, Span};
+use tracing::Instrument;
 
 use crate::{
     agent::{
         AgentError, AgentExecutor, AgentInput, AgentOutput, AgentStep, DefaultStrategy,
         ExecutionOutput, Strategy,
     },
+    callbacks::{CallbackHandler, CallbackManager},
     chain::{ChainError, ChainOutput, InputCtor, OutputCtor},
     schemas::{IntoWithUsage, TokenUsage, ToolCall, WithUsage},
+    telemetry,
     tools::ToolDyn,
     utils::helper::normalize_tool_name,
 };
@@ -85,14 +87,10 @@ where
     /// Entry point – iteratively plan / execute tool actions until the agent
     /// produces a valid final answer that can be transformed into `O`.
     pub async fn start(mut self) -> Result<ExecutionOutput<'input, O, S>, ChainError> {
-        let span = if let Some(id) = self.strategy.agent_id() {
-            info_span!("agent_execution", %id)
-        } else {
-            Span::none()
-        };
+        let span = telemetry::agent_span(self.strategy.agent_id());
 
         async move {
-            self.load_memory().await;
+            self.load_memory().await?;
             self.input = self.strategy.prepare_input::<I>(self.input).await?;
             self.log_initial_prompt()?;
 
@@ -131,10 +129,13 @@ where
         Ok(())
     }
 
-    async fn load_memory(&mut self) {
+    async fn load_memory(&mut self) -> Result<(), ChainError> {
         if let Some(memory) = &self.executor.memory {
-            self.input.set_chat_history(memory.read().await.messages());
+            let query = self.input.inner.to_string();
+            self.input
+                .set_chat_history(memory.relevant_messages(&query).await?);
         }
+        Ok(())
     }
 
     async fn plan_step(&mut self) -> Result<AgentOutput, ChainError> {
@@ -163,6 +164,7 @@ where
             return;
         }
 
+        let callbacks = CallbackManager::current();
         for call in tool_calls {
             log::debug!("\nTool call:\n{call}");
             let tool_name = normalize_tool_name(&call.name);
@@ -171,10 +173,24 @@ where
                 return;
             };
 
-            let Ok(result) = tool
-                .call(call.arguments.clone())
-                .await
-                .inspect_err(|e| failure!(self, "Tool '{tool_name}' error: {e}"))
+            let run = callbacks.child_run();
+            callbacks
+                .on_tool_start(&run, &tool_name, &call.arguments)
+                .await;
+            let result = tool.call(call.arguments.clone()).await;
+            match &result {
+                Ok(result) => {
+                    let output = result.data.to_string();
+                    callbacks.on_tool_end(&run, &tool_name, &output).await;
+                }
+                Err(e) => {
+                    let error = e.to_string();
+                    callbacks.on_tool_error(&run, &tool_name, &error).await;
+                }
+            }
+
+            let Ok(result) =
+                result.inspect_err(|e| failure!(self, "Tool '{tool_name}' error: {e}"))
             else {
                 return;
             };
@@ -190,6 +206,9 @@ where
                 return;
             };
             log::debug!("\nTool {} result:\n{}", &step.tool_call.name, step.result);
+            callbacks
+                .on_agent_step(&callbacks.current_run(), &step)
+                .await;
             self.steps.push(step);
             self.consecutive_fails = 0;
         }
@@ -220,9 +239,9 @@ where
 
         if let Some(memory) = &self.executor.memory {
             memory
-                .write()
+                .update(human_message, self.steps, final_answer)
                 .await
-                .update(human_message, self.steps, final_answer);
+                .map_err(|e| FinalizeFailure::Abort(e.into()))?;
         }
 
         let WithUsage { content, usage } = answer.with_usage(self.total_usage);
@@ -7,7 +7,7 @@ use tokio::sync::RwLock;
 use crate::{
     agent::{Agent, AgentInput, DefaultStrategy, ExecutionContext, Strategy},
     chain::{Chain, ChainError, ChainOutput, InputCtor, OutputCtor},
-    memory::Memory,
+    memory::{AsyncMemory, Memory, SyncMemoryAdapter},
     schemas::{GetPrompt, Prompt, WithUsage},
     template::TemplateError,
 };
@@ -29,7 +29,7 @@ where
     for<'any> O::Target<'any>: ChainOutput<I::Target<'any>>,
 {
     pub(super) agent: Box<dyn Agent<I, O> + 'agent>,
-    pub(super) memory: Option<Arc<RwLock<dyn Memory>>>,
+    pub(super) memory: Option<Arc<dyn AsyncMemory>>,
     pub(super) options: ExecutorOptions,
 }
 
@@ -49,6 +49,12 @@ where
 
     /// Sets the memory for the executor.
     pub fn with_memory(mut self, memory: Arc<RwLock<dyn Memory>>) -> Self {
+        self.memory = Some(SyncMemoryAdapter::new(memory).into());
+        self
+    }
+
+    /// Sets an asynchronous memory for the executor, such as one backed by a database.
+    pub fn with_async_memory(mut self, memory: Arc<dyn AsyncMemory>) -> Self {
         self.memory = Some(memory);
         self
     }
@@ -0,0 +1,141 @@
+/// Implements [`CallbackHandler`](crate::callbacks::CallbackHandler) for a type with an
+/// `async fn emit(&self, run: &RunInfo, event: &str, data: serde_json::Value)` method, turning
+/// every hook into a named event with a JSON payload.
+macro_rules! impl_event_handler {
+    ($handler:ty) => {
+        #[async_trait::async_trait]
+        impl $crate::callbacks::CallbackHandler for $handler {
+            async fn on_llm_start(
+                &self,
+                run: &$crate::callbacks::RunInfo,
+                messages: &[$crate::schemas::Message],
+            ) {
+                let messages = messages.iter().map(|m| m.to_string()).collect::<Vec<_>>();
+                self.emit(run, "llm_start", serde_json::json!({ "messages": messages }))
+                    .await;
+            }
+
+            async fn on_llm_end(
+                &self,
+                run: &$crate::callbacks::RunInfo,
+                output: &$crate::llm::LLMOutput,
+                usage: Option<&$crate::schemas::TokenUsage>,
+            ) {
+                let data = serde_json::json!({ "output": output.to_string(), "usage": usage });
+                self.emit(run, "llm_end", data).await;
+            }
+
+            async fn on_llm_error(
+                &self,
+                run: &$crate::callbacks::RunInfo,
+                error: &$crate::llm::LLMError,
+            ) {
+                let data = serde_json::json!({ "error": error.to_string() });
+                self.emit(run, "llm_error", data).await;
+            }
+
+            async fn on_llm_token(&self, run: &$crate::callbacks::RunInfo, token: &str) {
+                self.emit(run, "llm_token", serde_json::json!({ "token": token }))
+                    .await;
+            }
+
+            async fn on_chain_start(
+                &self,
+                run: &$crate::callbacks::RunInfo,
+                name: &str,
+                inputs: &$crate::chain::TextReplacements<'_>,
+            ) {
+                let data = serde_json::json!({ "name": name, "inputs": inputs });
+                self.emit(run, "chain_start", data).await;
+            }
+
+            async fn on_chain_end(
+                &self,
+                run: &$crate::callbacks::RunInfo,
+                usage: Option<&$crate::schemas::TokenUsage>,
+            ) {
+                self.emit(run, "chain_end", serde_json::json!({ "usage": usage }))
+                    .await;
+            }
+
+            async fn on_chain_error(
+                &self,
+                run: &$crate::callbacks::RunInfo,
+                error: &$crate::chain::ChainError,
+            ) {
+                let data = serde_json::json!({ "error": error.to_string() });
+                self.emit(run, "chain_error", data).await;
+            }
+
+            async fn on_tool_start(
+                &self,
+                run: &$crate::callbacks::RunInfo,
+                tool_name: &str,
+                input: &serde_json::Value,
+            ) {
+                let data = serde_json::json!({ "tool": tool_name, "input": input });
+                self.emit(run, "tool_start", data).await;
+            }
+
+            async fn on_tool_end(
+                &self,
+                run: &$crate::callbacks::RunInfo,
+                tool_name: &str,
+                output: &str,
+            ) {
+                let data = serde_json::json!({ "tool": tool_name, "output": output });
+                self.emit(run, "tool_end", data).await;
+            }
+
+            async fn on_tool_error(
+                &self,
+                run: &$crate::callbacks::RunInfo,
+                tool_name: &str,
+                error: &str,
+            ) {
+                let data = serde_json::json!({ "tool": tool_name, "error": error });
+                self.emit(run, "tool_error", data).await;
+            }
+
+            async fn on_retriever_start(&self, run: &$crate::callbacks::RunInfo, query: &str) {
+                self.emit(run, "retriever_start", serde_json::json!({ "query": query }))
+                    .await;
+            }
+
+            async fn on_retriever_end(
+                &self,
+                run: &$crate::callbacks::RunInfo,
+                query: &str,
+                documents: &[$crate::schemas::Document],
+            ) {
+                let data = serde_json::json!({ "query": query, "documents": documents });
+                self.emit(run, "retriever_end", data).await;
+            }
+
+            async fn on_retriever_error(
+                &self,
+                run: &$crate::callbacks::RunInfo,
+                query: &str,
+                error: &str,
+            ) {
+                let data = serde_json::json!({ "query": query, "error": error });
+                self.emit(run, "retriever_error", data).await;
+            }
+
+            async fn on_agent_step(
+                &self,
+                run: &$crate::callbacks::RunInfo,
+                step: &$crate::agent::AgentStep,
+            ) {
+                let data = serde_json::json!({
+                    "tool_call": step.tool_call,
+                    "result": step.result,
+                    "summary": step.summary,
+                });
+                self.emit(run, "agent_step", data).await;
+            }
+        }
+    };
+}
+
+pub(super) use impl_event_handler;
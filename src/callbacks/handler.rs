@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+    agent::AgentStep,
+    chain::{ChainError, TextReplacements},
+    llm::{LLMError, LLMOutput},
+    schemas::{Document, Message, TokenUsage},
+};
+
+/// Identifies the run an event belongs to. Runs started while another run is in progress have
+/// its id as `parent_run_id`.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunInfo {
+    pub run_id: Uuid,
+    pub parent_run_id: Option<Uuid>,
+    pub tags: Vec<String>,
+    pub metadata: HashMap<String, Value>,
+}
+
+/// Receives events from LLMs, chains, tools, retrievers and agents.
+///
+/// Handlers are registered either for a single run, with
+/// [`RunConfig::with_callback`](crate::chain::RunConfig::with_callback), or for every run, with
+/// [`add_global_handler`](crate::callbacks::add_global_handler). Every hook does nothing by
+/// default.
+///
+/// Chain events are only emitted for runs started with
+/// [`Chain::call_with_config`](crate::chain::Chain::call_with_config).
+#[async_trait]
+pub trait CallbackHandler: Send + Sync {
+    async fn on_llm_start(&self, _run: &RunInfo, _messages: &[Message]) {}
+
+    async fn on_llm_end(&self, _run: &RunInfo, _output: &LLMOutput, _usage: Option<&TokenUsage>) {}
+
+    async fn on_llm_error(&self, _run: &RunInfo, _error: &LLMError) {}
+
+    /// Called for every chunk of a streamed LLM response.
+    async fn on_llm_token(&self, _run: &RunInfo, _token: &str) {}
+
+    async fn on_chain_start(&self, _run: &RunInfo, _name: &str, _inputs: &TextReplacements<'_>) {}
+
+    async fn on_chain_end(&self, _run: &RunInfo, _usage: Option<&TokenUsage>) {}
+
+    async fn on_chain_error(&self, _run: &RunInfo, _error: &ChainError) {}
+
+    async fn on_tool_start(&self, _run: &RunInfo, _tool_name: &str, _input: &Value) {}
+
+    async fn on_tool_end(&self, _run: &RunInfo, _tool_name: &str, _output: &str) {}
+
+    async fn on_tool_error(&self, _run: &RunInfo, _tool_name: &str, _error: &str) {}
+
+    async fn on_retriever_start(&self, _run: &RunInfo, _query: &str) {}
+
+    async fn on_retriever_end(&self, _run: &RunInfo, _query: &str, _documents: &[Document]) {}
+
+    async fn on_retriever_error(&self, _run: &RunInfo, _query: &str, _error: &str) {}
+
+    async fn on_agent_step(&self, _run: &RunInfo, _step: &AgentStep) {}
+}
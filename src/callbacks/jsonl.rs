@@ -0,0 +1,57 @@
+use std::{
+    io,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{json, Value};
+use tokio::{
+    fs::{File, OpenOptions},
+    io::AsyncWriteExt,
+    sync::Mutex,
+};
+
+use super::{event::impl_event_handler, RunInfo};
+
+/// Appends every event to a file as a JSON object per line.
+pub struct JsonlFileHandler {
+    file: Mutex<File>,
+}
+
+impl JsonlFileHandler {
+    /// Opens the file in append mode, creating it if it does not exist.
+    pub async fn new(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await?;
+
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    async fn emit(&self, run: &RunInfo, event: &str, data: Value) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let line = json!({
+            "event": event,
+            "timestamp_ms": timestamp,
+            "run_id": run.run_id,
+            "parent_run_id": run.parent_run_id,
+            "tags": run.tags,
+            "metadata": run.metadata,
+            "data": data,
+        });
+
+        let mut file = self.file.lock().await;
+        if let Err(e) = file.write_all(format!("{line}\n").as_bytes()).await {
+            log::warn!("Failed to write callback event: {e}");
+        }
+    }
+}
+
+impl_event_handler!(JsonlFileHandler);
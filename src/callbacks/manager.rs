@@ -0,0 +1,588 @@
+use std::{
+    error::Error,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, RwLock},
+};
+
+use async_stream::stream;
+use async_trait::async_trait;
+use futures::{Stream, StreamExt};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+    agent::AgentStep,
+    chain::{ChainError, RunConfig, TextReplacements},
+    llm::{LLMError, LLMOutput},
+    schemas::{Document, Message, StreamData, TokenUsage, WithUsage},
+};
+
+use super::{CallbackHandler, RunInfo};
+
+static GLOBAL_HANDLERS: RwLock<Vec<Arc<dyn CallbackHandler>>> = RwLock::new(Vec::new());
+
+/// Registers a handler receiving the events of every run.
+pub fn add_global_handler(handler: Arc<dyn CallbackHandler>) {
+    GLOBAL_HANDLERS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(handler);
+}
+
+/// Removes every handler registered with [`add_global_handler`].
+pub fn clear_global_handlers() {
+    GLOBAL_HANDLERS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .clear();
+}
+
+/// Dispatches events to the global handlers and to the handlers of the current [`RunConfig`].
+#[derive(Clone)]
+pub struct CallbackManager {
+    handlers: Vec<Arc<dyn CallbackHandler>>,
+    run_id: Uuid,
+    parent_run_id: Option<Uuid>,
+    tags: Vec<String>,
+    metadata: std::collections::HashMap<String, Value>,
+}
+
+impl CallbackManager {
+    /// Returns the manager of the run the current task is part of. Outside of a run with an id,
+    /// the manager gets a new run id, shared by all its events.
+    pub fn current() -> Self {
+        let mut handlers = GLOBAL_HANDLERS
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+
+        match RunConfig::current() {
+            Some(config) => {
+                handlers.extend(config.callbacks);
+                Self {
+                    handlers,
+                    run_id: config.run_id.unwrap_or_else(Uuid::new_v4),
+                    parent_run_id: config.parent_run_id,
+                    tags: config.tags,
+                    metadata: config.metadata,
+                }
+            }
+            None => Self {
+                handlers,
+                run_id: Uuid::new_v4(),
+                parent_run_id: None,
+                tags: Vec::new(),
+                metadata: Default::default(),
+            },
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+
+    /// Returns the info of the current run, the one of the current [`RunConfig`].
+    pub fn current_run(&self) -> RunInfo {
+        RunInfo {
+            run_id: self.run_id,
+            parent_run_id: self.parent_run_id,
+            tags: self.tags.clone(),
+            metadata: self.metadata.clone(),
+        }
+    }
+
+    /// Returns the info of a new run, child of the current run.
+    pub fn child_run(&self) -> RunInfo {
+        RunInfo {
+            run_id: Uuid::new_v4(),
+            parent_run_id: Some(self.run_id),
+            tags: self.tags.clone(),
+            metadata: self.metadata.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl CallbackHandler for CallbackManager {
+    async fn on_llm_start(&self, run: &RunInfo, messages: &[Message]) {
+        for handler in &self.handlers {
+            handler.on_llm_start(run, messages).await;
+        }
+    }
+
+    async fn on_llm_end(&self, run: &RunInfo, output: &LLMOutput, usage: Option<&TokenUsage>) {
+        for handler in &self.handlers {
+            handler.on_llm_end(run, output, usage).await;
+        }
+    }
+
+    async fn on_llm_error(&self, run: &RunInfo, error: &LLMError) {
+        for handler in &self.handlers {
+            handler.on_llm_error(run, error).await;
+        }
+    }
+
+    async fn on_llm_token(&self, run: &RunInfo, token: &str) {
+        for handler in &self.handlers {
+            handler.on_llm_token(run, token).await;
+        }
+    }
+
+    async fn on_chain_start(&self, run: &RunInfo, name: &str, inputs: &TextReplacements<'_>) {
+        for handler in &self.handlers {
+            handler.on_chain_start(run, name, inputs).await;
+        }
+    }
+
+    async fn on_chain_end(&self, run: &RunInfo, usage: Option<&TokenUsage>) {
+        for handler in &self.handlers {
+            handler.on_chain_end(run, usage).await;
+        }
+    }
+
+    async fn on_chain_error(&self, run: &RunInfo, error: &ChainError) {
+        for handler in &self.handlers {
+            handler.on_chain_error(run, error).await;
+        }
+    }
+
+    async fn on_tool_start(&self, run: &RunInfo, tool_name: &str, input: &Value) {
+        for handler in &self.handlers {
+            handler.on_tool_start(run, tool_name, input).await;
+        }
+    }
+
+    async fn on_tool_end(&self, run: &RunInfo, tool_name: &str, output: &str) {
+        for handler in &self.handlers {
+            handler.on_tool_end(run, tool_name, output).await;
+        }
+    }
+
+    async fn on_tool_error(&self, run: &RunInfo, tool_name: &str, error: &str) {
+        for handler in &self.handlers {
+            handler.on_tool_error(run, tool_name, error).await;
+        }
+    }
+
+    async fn on_retriever_start(&self, run: &RunInfo, query: &str) {
+        for handler in &self.handlers {
+            handler.on_retriever_start(run, query).await;
+        }
+    }
+
+    async fn on_retriever_end(&self, run: &RunInfo, query: &str, documents: &[Document]) {
+        for handler in &self.handlers {
+            handler.on_retriever_end(run, query, documents).await;
+        }
+    }
+
+    async fn on_retriever_error(&self, run: &RunInfo, query: &str, error: &str) {
+        for handler in &self.handlers {
+            handler.on_retriever_error(run, query, error).await;
+        }
+    }
+
+    async fn on_agent_step(&self, run: &RunInfo, step: &AgentStep) {
+        for handler in &self.handlers {
+            handler.on_agent_step(run, step).await;
+        }
+    }
+}
+
+/// Runs the `generation` of the answer to `messages` as a child run of the current run, sending
+/// the LLM events to the handlers of the [`CallbackManager::current`] manager. The LLMs call it
+/// from [`LLM::generate`](crate::llm::LLM::generate), so that every call is reported.
+pub(crate) async fn trace_llm<G, F>(
+    messages: Vec<Message>,
+    generation: G,
+) -> Result<WithUsage<LLMOutput>, LLMError>
+where
+    G: FnOnce(Vec<Message>) -> F,
+    F: Future<Output = Result<WithUsage<LLMOutput>, LLMError>>,
+{
+    let callbacks = CallbackManager::current();
+    if callbacks.is_empty() {
+        return generation(messages).await;
+    }
+
+    let run = callbacks.child_run();
+    callbacks.on_llm_start(&run, &messages).await;
+    let result = generation(messages).await;
+    match &result {
+        Ok(output) => {
+            callbacks
+                .on_llm_end(&run, &output.content, output.usage.as_ref())
+                .await
+        }
+        Err(e) => callbacks.on_llm_error(&run, e).await,
+    }
+    result
+}
+
+/// Like [`trace_llm`], for the `stream` of the answer to `messages`: each streamed token is sent
+/// to the handlers, and the run ends with the concatenated text when the stream does.
+pub(crate) async fn trace_llm_stream<G, F, S>(
+    messages: Vec<Message>,
+    stream: G,
+) -> Result<Pin<Box<dyn Stream<Item = Result<StreamData, LLMError>> + Send>>, LLMError>
+where
+    G: FnOnce(Vec<Message>) -> F,
+    F: Future<Output = Result<S, LLMError>>,
+    S: Stream<Item = Result<StreamData, LLMError>> + Send + 'static,
+{
+    let callbacks = CallbackManager::current();
+    if callbacks.is_empty() {
+        return Ok(Box::pin(stream(messages).await?));
+    }
+
+    let run = callbacks.child_run();
+    callbacks.on_llm_start(&run, &messages).await;
+    let mut llm_stream = match stream(messages).await {
+        Ok(llm_stream) => Box::pin(llm_stream),
+        Err(e) => {
+            callbacks.on_llm_error(&run, &e).await;
+            return Err(e);
+        }
+    };
+
+    let traced_stream = stream! {
+        let mut text = String::new();
+        let mut usage = None;
+        while let Some(result) = llm_stream.next().await {
+            match result {
+                Ok(data) => {
+                    callbacks.on_llm_token(&run, &data.content).await;
+                    text.push_str(&data.content);
+                    if data.tokens.is_some() {
+                        usage = data.tokens.clone();
+                    }
+                    yield Ok(data);
+                }
+                Err(e) => {
+                    callbacks.on_llm_error(&run, &e).await;
+                    yield Err(e);
+                    return;
+                }
+            }
+        }
+        callbacks
+            .on_llm_end(&run, &LLMOutput::Text(text), usage.as_ref())
+            .await;
+    };
+
+    Ok(Box::pin(traced_stream))
+}
+
+/// Runs the `retrieval` of the documents relevant to `query` as a child run of the current run,
+/// sending the retriever events to the handlers of the [`CallbackManager::current`] manager.
+/// The retrievers and LLMs called by the `retrieval` report their runs as children of this one.
+pub(crate) async fn trace_retrieval<F>(
+    query: &str,
+    retrieval: F,
+) -> Result<Vec<Document>, Box<dyn Error>>
+where
+    F: Future<Output = Result<Vec<Document>, Box<dyn Error>>>,
+{
+    let callbacks = CallbackManager::current();
+    let run = callbacks.child_run();
+    callbacks.on_retriever_start(&run, query).await;
+
+    let retrieval = RunConfig::new()
+        .with_run_id(run.run_id)
+        .scope(async { Ok(retrieval.await) });
+    // The error is not `Send`, so it can't be kept while the handlers run: it is returned with
+    // its message only.
+    let documents = match retrieval
+        .await
+        .unwrap_or_else(|e| Err(e.into()))
+        .map_err(|e| e.to_string())
+    {
+        Ok(documents) => documents,
+        Err(message) => {
+            callbacks.on_retriever_error(&run, query, &message).await;
+            return Err(message.into());
+        }
+    };
+    callbacks.on_retriever_end(&run, query, &documents).await;
+    Ok(documents)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use futures::TryStreamExt;
+
+    use crate::{
+        chain::{Chain, DefaultChainInput, DefaultChainInputCtor, StringCtor},
+        llm::{fake::FakeLLM, LLM},
+        retrievers::{Bm25Retriever, EnsembleRetriever},
+        schemas::{IntoWithUsage, Retriever, WithUsage},
+    };
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Mutex<Vec<(String, RunInfo)>>,
+    }
+
+    #[async_trait]
+    impl CallbackHandler for Recorder {
+        async fn on_llm_start(&self, run: &RunInfo, _messages: &[Message]) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(("llm_start".into(), run.clone()));
+        }
+
+        async fn on_llm_end(
+            &self,
+            run: &RunInfo,
+            _output: &LLMOutput,
+            _usage: Option<&TokenUsage>,
+        ) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(("llm_end".into(), run.clone()));
+        }
+
+        async fn on_llm_token(&self, run: &RunInfo, _token: &str) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(("llm_token".into(), run.clone()));
+        }
+
+        async fn on_chain_start(&self, run: &RunInfo, _name: &str, _inputs: &TextReplacements<'_>) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(("start".into(), run.clone()));
+        }
+
+        async fn on_chain_end(&self, run: &RunInfo, _usage: Option<&TokenUsage>) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(("end".into(), run.clone()));
+        }
+
+        async fn on_retriever_start(&self, run: &RunInfo, _query: &str) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(("retriever_start".into(), run.clone()));
+        }
+
+        async fn on_retriever_end(&self, run: &RunInfo, _query: &str, _documents: &[Document]) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(("retriever_end".into(), run.clone()));
+        }
+
+        async fn on_retriever_error(&self, run: &RunInfo, _query: &str, _error: &str) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(("retriever_error".into(), run.clone()));
+        }
+    }
+
+    struct Inner;
+
+    #[async_trait]
+    impl Chain<DefaultChainInputCtor, StringCtor> for Inner {
+        async fn call<'a>(
+            &self,
+            _input: DefaultChainInput<'a>,
+        ) -> Result<WithUsage<String>, ChainError> {
+            Ok("inner".to_string().with_usage(None))
+        }
+    }
+
+    struct Outer;
+
+    #[async_trait]
+    impl Chain<DefaultChainInputCtor, StringCtor> for Outer {
+        async fn call<'a>(
+            &self,
+            input: DefaultChainInput<'a>,
+        ) -> Result<WithUsage<String>, ChainError> {
+            Inner.call_with_config(input, RunConfig::new()).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_nested_runs_are_reported_with_their_parent() {
+        let recorder = Arc::new(Recorder::default());
+        let config = RunConfig::new()
+            .with_tag("test")
+            .with_callback(recorder.clone());
+
+        Outer
+            .call_with_config(DefaultChainInput::new("hi"), config)
+            .await
+            .unwrap();
+
+        let events = recorder.events.lock().unwrap();
+        let kinds: Vec<&str> = events.iter().map(|(kind, _)| kind.as_str()).collect();
+        assert_eq!(kinds, ["start", "start", "end", "end"]);
+        let (outer, inner) = (&events[0].1, &events[1].1);
+        assert_eq!(outer.parent_run_id, None);
+        assert_eq!(inner.parent_run_id, Some(outer.run_id));
+        assert_eq!(events[2].1.run_id, inner.run_id);
+        assert_eq!(events[3].1.run_id, outer.run_id);
+        assert_eq!(inner.tags, vec!["test"]);
+    }
+
+    #[tokio::test]
+    async fn test_retrievers_report_a_child_run() {
+        let recorder = Arc::new(Recorder::default());
+        let run_id = Uuid::new_v4();
+        let config = RunConfig::new()
+            .with_run_id(run_id)
+            .with_callback(recorder.clone());
+        let retriever = Bm25Retriever::new(vec![Document::new("cats and dogs")], 1);
+
+        let docs = config
+            .scope(async {
+                retriever
+                    .get_relevant_documents("cats")
+                    .await
+                    .map_err(|e| ChainError::RetrieverError(e.to_string()))
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(docs.len(), 1);
+        let events = recorder.events.lock().unwrap();
+        let kinds: Vec<&str> = events.iter().map(|(kind, _)| kind.as_str()).collect();
+        assert_eq!(kinds, ["retriever_start", "retriever_end"]);
+        assert_eq!(events[0].1.parent_run_id, Some(run_id));
+        assert_eq!(events[1].1.run_id, events[0].1.run_id);
+    }
+
+    struct Failing;
+
+    #[async_trait]
+    impl Retriever for Failing {
+        async fn get_relevant_documents(
+            &self,
+            _query: &str,
+        ) -> Result<Vec<Document>, Box<dyn Error>> {
+            Err(Box::new(std::io::Error::other("disk full")))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_nested_retrievers_report_the_outer_run_as_parent() {
+        let recorder = Arc::new(Recorder::default());
+        let config = RunConfig::new()
+            .with_run_id(Uuid::new_v4())
+            .with_callback(recorder.clone());
+        let retriever = EnsembleRetriever::new(1)
+            .with_retriever(Bm25Retriever::new(vec![Document::new("cats")], 1), 1.0)
+            .with_retriever(Bm25Retriever::new(vec![Document::new("dogs")], 1), 1.0);
+
+        config
+            .scope(async {
+                retriever
+                    .get_relevant_documents("cats")
+                    .await
+                    .map_err(|e| ChainError::RetrieverError(e.to_string()))
+            })
+            .await
+            .unwrap();
+
+        let events = recorder.events.lock().unwrap();
+        let kinds: Vec<&str> = events.iter().map(|(kind, _)| kind.as_str()).collect();
+        assert_eq!(
+            kinds,
+            [
+                "retriever_start",
+                "retriever_start",
+                "retriever_end",
+                "retriever_start",
+                "retriever_end",
+                "retriever_end"
+            ]
+        );
+        let outer = &events[0].1;
+        assert_eq!(events[1].1.parent_run_id, Some(outer.run_id));
+        assert_eq!(events[3].1.parent_run_id, Some(outer.run_id));
+        assert_ne!(events[1].1.run_id, events[3].1.run_id);
+        assert_eq!(events[5].1.run_id, outer.run_id);
+    }
+
+    #[tokio::test]
+    async fn test_retrievers_report_their_errors() {
+        let recorder = Arc::new(Recorder::default());
+        let config = RunConfig::new().with_callback(recorder.clone());
+        let retriever = EnsembleRetriever::new(1).with_retriever(Failing, 1.0);
+
+        let error = config
+            .scope(async {
+                Ok(retriever
+                    .get_relevant_documents("cats")
+                    .await
+                    .map_err(|e| e.to_string()))
+            })
+            .await
+            .unwrap()
+            .unwrap_err();
+
+        assert_eq!(error, "disk full");
+        let events = recorder.events.lock().unwrap();
+        let kinds: Vec<&str> = events.iter().map(|(kind, _)| kind.as_str()).collect();
+        assert_eq!(kinds, ["retriever_start", "retriever_error"]);
+        assert_eq!(events[1].1.run_id, events[0].1.run_id);
+    }
+
+    #[tokio::test]
+    async fn test_llm_calls_report_a_child_run() {
+        let recorder = Arc::new(Recorder::default());
+        let run_id = Uuid::new_v4();
+        let config = RunConfig::new()
+            .with_run_id(run_id)
+            .with_callback(recorder.clone());
+        let llm = FakeLLM::fixed("meow");
+
+        config
+            .scope(async {
+                llm.generate(vec![Message::new_human_message("cat?")])
+                    .await?;
+                llm.stream(vec![Message::new_human_message("cat?")])
+                    .await?
+                    .try_collect::<Vec<_>>()
+                    .await?;
+                Ok(())
+            })
+            .await
+            .unwrap();
+
+        let events = recorder.events.lock().unwrap();
+        let kinds: Vec<&str> = events.iter().map(|(kind, _)| kind.as_str()).collect();
+        assert_eq!(
+            kinds,
+            ["llm_start", "llm_end", "llm_start", "llm_token", "llm_end"]
+        );
+        assert!(events
+            .iter()
+            .all(|(_, run)| run.parent_run_id == Some(run_id)));
+        assert_eq!(events[1].1.run_id, events[0].1.run_id);
+        assert_ne!(events[2].1.run_id, events[0].1.run_id);
+        assert_eq!(events[4].1.run_id, events[2].1.run_id);
+    }
+
+    #[test]
+    fn test_run_id_is_stable() {
+        let callbacks = CallbackManager::current();
+        let run = callbacks.current_run();
+        assert_eq!(callbacks.current_run().run_id, run.run_id);
+        assert_eq!(callbacks.child_run().parent_run_id, Some(run.run_id));
+    }
+}
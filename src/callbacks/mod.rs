@@ -0,0 +1,16 @@
+mod event;
+
+mod handler;
+pub use handler::*;
+
+mod manager;
+pub use manager::*;
+
+mod stdout;
+pub use stdout::*;
+
+mod jsonl;
+pub use jsonl::*;
+
+mod tracing_handler;
+pub use tracing_handler::*;
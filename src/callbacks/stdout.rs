@@ -0,0 +1,24 @@
+use serde_json::Value;
+
+use super::{event::impl_event_handler, RunInfo};
+
+/// Prints every event to stdout, one line per event.
+#[derive(Debug, Clone, Default)]
+pub struct StdOutHandler;
+
+impl StdOutHandler {
+    pub fn new() -> Self {
+        Self
+    }
+
+    async fn emit(&self, run: &RunInfo, event: &str, data: Value) {
+        match run.parent_run_id {
+            Some(parent_run_id) => {
+                println!("[{event}] run={} parent={parent_run_id} {data}", run.run_id)
+            }
+            None => println!("[{event}] run={} {data}", run.run_id),
+        }
+    }
+}
+
+impl_event_handler!(StdOutHandler);
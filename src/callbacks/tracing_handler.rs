@@ -0,0 +1,26 @@
+use serde_json::Value;
+
+use super::{event::impl_event_handler, RunInfo};
+
+/// Emits every event as a `tracing` event with target `langchain_rust::callbacks`.
+#[derive(Debug, Clone, Default)]
+pub struct TracingHandler;
+
+impl TracingHandler {
+    pub fn new() -> Self {
+        Self
+    }
+
+    async fn emit(&self, run: &RunInfo, event: &str, data: Value) {
+        tracing::info!(
+            target: "langchain_rust::callbacks",
+            event,
+            run_id = %run.run_id,
+            parent_run_id = ?run.parent_run_id,
+            tags = ?run.tags,
+            %data,
+        );
+    }
+}
+
+impl_event_handler!(TracingHandler);
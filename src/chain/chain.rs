@@ -3,8 +3,11 @@ use std::pin::Pin;
 use async_trait::async_trait;
 use futures::Stream;
 
+use uuid::Uuid;
+
 use crate::{
-    chain::{InputCtor, OutputCtor},
+    callbacks::{CallbackHandler, CallbackManager},
+    chain::{ChainInput, InputCtor, OutputCtor, RunConfig},
     schemas::{OutputTrace, StreamData, WithUsage},
 };
 
@@ -23,6 +26,38 @@ pub trait Chain<I: InputCtor, O: OutputCtor>: Sync + Send {
         Ok(OutputTrace::single(output))
     }
 
+    /// Calls the chain with a [`RunConfig`], which is visible to every nested chain, agent and
+    /// LLM call through [`RunConfig::current`]. The call fails with [`ChainError::Timeout`] or
+    /// [`ChainError::Cancelled`] if the timeout of the config elapses or its cancellation token
+    /// is cancelled first.
+    ///
+    /// The run gets an id if the config has none, and its chain events are sent to the callback
+    /// handlers.
+    async fn call_with_config<'a>(
+        &self,
+        input: I::Target<'a>,
+        mut config: RunConfig,
+    ) -> Result<WithUsage<O::Target<'a>>, ChainError> {
+        config.run_id.get_or_insert_with(Uuid::new_v4);
+        config
+            .scope(async move {
+                let callbacks = CallbackManager::current();
+                let run = callbacks.current_run();
+                let name = std::any::type_name::<Self>();
+                callbacks
+                    .on_chain_start(&run, name, &input.text_replacements())
+                    .await;
+
+                let result = self.call(input).await;
+                match &result {
+                    Ok(output) => callbacks.on_chain_end(&run, output.usage.as_ref()).await,
+                    Err(e) => callbacks.on_chain_error(&run, e).await,
+                }
+                result
+            })
+            .await
+    }
+
     /// Stream the `Chain` and get an asynchronous stream of chain generations.
     /// The input is a set of variables passed as a `PromptArgs` hashmap.
     /// If the chain have memroy, the tream method will not be able to automaticaly
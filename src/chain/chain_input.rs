@@ -19,6 +19,14 @@ impl ChainInput for () {
     }
 }
 
+/// A list of inputs has no replacements of its own, it is meant to be fanned out to an inner chain
+/// (see [`MapChain`](crate::chain::MapChain)).
+impl<T: ChainInput> ChainInput for Vec<T> {
+    fn text_replacements(&self) -> TextReplacements<'_> {
+        HashMap::new()
+    }
+}
+
 impl ChainInput for HashMap<String, String> {
     fn text_replacements(&self) -> TextReplacements<'_> {
         self.iter()
@@ -0,0 +1,73 @@
+use crate::{
+    chain::{LLMChain, StuffDocument},
+    llm::LLM,
+    schemas::MessageType,
+    template::MessageTemplate,
+};
+
+use super::{CitedAnswerCtor, CitedQACtor, DEFAULT_CITED_QA_TEMPLATE};
+
+/// A question answering chain whose answer cites the documents it is based on.
+pub type CitedQAChain = StuffDocument<CitedQACtor, CitedAnswerCtor>;
+
+impl StuffDocument<CitedQACtor, CitedAnswerCtor> {
+    /// load_cited_qa return an instance of StuffDocument
+    /// with a prompt designed for question answering with citations
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// let llm = OpenAI::default();
+    /// let chain = CitedQAChain::load_cited_qa(llm);
+    ///
+    /// let input = CitedQA::new()
+    ///     .documents(&documents)
+    ///     .question("How old is luis and whats his favorite text editor");
+    ///
+    /// let output = chain.call(input).await.unwrap().content;
+    /// for citation in output.citations {
+    ///     println!("[{}] {:?}", citation.index + 1, citation.source);
+    /// }
+    /// ```
+    pub fn load_cited_qa<L: Into<Box<dyn LLM>>>(llm: L) -> Self {
+        let prompt = MessageTemplate::from_jinja2(MessageType::System, DEFAULT_CITED_QA_TEMPLATE);
+
+        let llm_chain = LLMChain::builder().prompt(prompt).llm(llm).build().unwrap(); // Safe to unwrap, the prompt and the LLM are set.
+
+        StuffDocument::new(llm_chain)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::{
+        chain::{Chain, CitedQA},
+        llm::openai::OpenAI,
+        schemas::Document,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_cited_qa() {
+        let chain = CitedQAChain::load_cited_qa(OpenAI::default());
+        let documents = [
+            Document::new(indoc! {"
+                    Question: Which is the favorite text editor of luis
+                    Answer: Nvim"
+            }),
+            Document::new(indoc! {"
+                    Question: How old is Luis
+                    Answer: 24"
+            }),
+        ];
+        let input = CitedQA::new()
+            .documents(&documents)
+            .question("How old is luis and whats his favorite text editor");
+
+        let output = chain.call(input).await.unwrap().content;
+        println!("{output:?}");
+    }
+}
@@ -0,0 +1,62 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use crate::{
+    chain::{ChainInput, Ctor, TextReplacements},
+    schemas::Document,
+};
+
+/// Input of a question answering chain citing its sources. Unlike
+/// [`StuffQA`](crate::chain::StuffQA), the documents are numbered in the context, starting at 1,
+/// so that the answer can refer to them with `[n]` markers.
+#[derive(Clone, Ctor)]
+pub struct CitedQA<'a> {
+    pub(crate) input_documents: Cow<'a, [Document]>,
+    pub(crate) question: Cow<'a, str>,
+}
+
+impl<'a> CitedQA<'a> {
+    pub fn new() -> Self {
+        Self {
+            input_documents: Cow::Borrowed(&[]),
+            question: "".into(),
+        }
+    }
+
+    pub fn documents(mut self, documents: impl Into<Cow<'a, [Document]>>) -> Self {
+        self.input_documents = documents.into();
+        self
+    }
+
+    pub fn question(mut self, question: impl Into<Cow<'a, str>>) -> Self {
+        self.question = question.into();
+        self
+    }
+}
+
+impl ChainInput for CitedQA<'_> {
+    fn text_replacements(&self) -> TextReplacements<'_> {
+        let context = self
+            .input_documents
+            .iter()
+            .enumerate()
+            .map(
+                |(i, doc)| match doc.metadata.get("source").and_then(|s| s.as_str()) {
+                    Some(source) => format!("[{}] (source: {source})\n{}", i + 1, doc.page_content),
+                    None => format!("[{}]\n{}", i + 1, doc.page_content),
+                },
+            )
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        HashMap::from([
+            ("question", self.question.as_ref().into()),
+            ("context", context.into()),
+        ])
+    }
+}
+
+impl Default for CitedQA<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
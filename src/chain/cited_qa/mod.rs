@@ -0,0 +1,11 @@
+mod input;
+pub use input::*;
+
+mod output;
+pub use output::*;
+
+mod chain;
+pub use chain::*;
+
+mod prompt;
+pub use prompt::*;
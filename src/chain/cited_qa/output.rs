@@ -0,0 +1,282 @@
+use std::{collections::HashMap, ops::Range, sync::LazyLock};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    chain::{ChainOutput, Ctor},
+    output_parser::OutputParseError,
+    schemas::Document,
+};
+
+use super::CitedQA;
+
+const QUOTES_HEADER: &str = "QUOTES:";
+
+static MARKER_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[(\d+)\]").expect("Static regex is valid"));
+static QUOTE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*\[(\d+)\]\s*"?(.*?)"?\s*$"#).expect("Static regex is valid")
+});
+
+/// A document supporting a [`CitedAnswer`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Citation {
+    /// Index of the document in the input, the answer refers to it as `[index + 1]`.
+    pub index: usize,
+    /// The `source` metadata of the document, if any.
+    pub source: Option<String>,
+    pub metadata: HashMap<String, Value>,
+    /// Byte ranges of the sentences of the answer citing the document, in order.
+    pub spans: Vec<Range<usize>>,
+    /// A span of the document quoted by the model, kept only if it appears verbatim in the
+    /// document.
+    pub quote: Option<String>,
+}
+
+/// An answer with `[n]` markers and the documents they refer to, in order of first citation.
+#[derive(Debug, Clone, Serialize, Deserialize, Ctor)]
+pub struct CitedAnswer {
+    pub answer: String,
+    pub citations: Vec<Citation>,
+    /// The numbers of the `[n]` markers of the answer referring to no document, such as
+    /// citations made up by the model, in order of first appearance.
+    #[serde(default)]
+    pub invalid_markers: Vec<usize>,
+}
+
+impl CitedAnswer {
+    /// Parses the output of the model. Only `[n]` markers with `n` between 1 and the number of
+    /// `documents` are citations, other bracketed numbers are kept as answer text and reported
+    /// in [`CitedAnswer::invalid_markers`]. Quote lines that don't refer to a document or don't
+    /// appear in it are dropped.
+    pub fn parse(text: &str, documents: &[Document]) -> Result<Self, OutputParseError> {
+        let (answer, quotes) = match text.find(QUOTES_HEADER) {
+            Some(i) => (&text[..i], &text[i + QUOTES_HEADER.len()..]),
+            None => (text, ""),
+        };
+        let answer = answer.trim();
+        let sentences = Self::sentence_spans(answer);
+
+        let mut spans_by_index: Vec<(usize, Vec<Range<usize>>)> = Vec::new();
+        let mut invalid_markers = Vec::new();
+        for captures in MARKER_RE.captures_iter(answer) {
+            let Some(index) = Self::document_index(&captures[1], documents.len()) else {
+                if let Ok(n) = captures[1].parse::<usize>() {
+                    if !invalid_markers.contains(&n) {
+                        invalid_markers.push(n);
+                    }
+                }
+                continue;
+            };
+            let span = Self::citing_sentence(answer, &sentences, captures.get(0).unwrap().start()); // Safe to unwrap, group 0 is the whole match.
+            match spans_by_index.iter_mut().find(|(i, _)| *i == index) {
+                Some((_, spans)) => {
+                    if let Some(span) = span.filter(|span| !spans.contains(span)) {
+                        spans.push(span);
+                    }
+                }
+                None => spans_by_index.push((index, span.into_iter().collect())),
+            }
+        }
+
+        let mut quotes_by_index: HashMap<usize, String> = HashMap::new();
+        for line in quotes.lines() {
+            let Some(captures) = QUOTE_RE.captures(line) else {
+                continue;
+            };
+            let Some(index) = Self::document_index(&captures[1], documents.len()) else {
+                continue;
+            };
+            let quote = captures[2].trim();
+            if !quote.is_empty() && documents[index].page_content.contains(quote) {
+                quotes_by_index.entry(index).or_insert_with(|| quote.into());
+            }
+        }
+
+        let citations = spans_by_index
+            .into_iter()
+            .map(|(index, spans)| {
+                let document = &documents[index];
+                Citation {
+                    index,
+                    source: document
+                        .metadata
+                        .get("source")
+                        .and_then(|s| s.as_str())
+                        .map(Into::into),
+                    metadata: document.metadata.clone(),
+                    spans,
+                    quote: quotes_by_index.remove(&index),
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            answer: answer.into(),
+            citations,
+            invalid_markers,
+        })
+    }
+
+    fn document_index(marker: &str, documents: usize) -> Option<usize> {
+        marker
+            .parse::<usize>()
+            .ok()
+            .filter(|n| (1..=documents).contains(n))
+            .map(|n| n - 1)
+    }
+
+    /// Splits the answer into sentences ending with `.`, `!`, `?` or a line break followed by
+    /// whitespace, without their surrounding whitespace.
+    fn sentence_spans(answer: &str) -> Vec<Range<usize>> {
+        let mut spans = Vec::new();
+        let mut start = 0;
+        let mut chars = answer.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            let at_boundary = matches!(c, '.' | '!' | '?' | '\n')
+                && chars.peek().is_none_or(|(_, next)| next.is_whitespace());
+            if at_boundary || chars.peek().is_none() {
+                let end = i + c.len_utf8();
+                let sentence = &answer[start..end];
+                let trimmed_start = start + (sentence.len() - sentence.trim_start().len());
+                let trimmed_end = start + sentence.trim_end().len();
+                if trimmed_start < trimmed_end {
+                    spans.push(trimmed_start..trimmed_end);
+                }
+                start = end;
+            }
+        }
+        spans
+    }
+
+    /// Returns the sentence cited by the marker at `position`. A marker opening a sentence, as in
+    /// `He is 24. [1]`, cites the previous one.
+    fn citing_sentence(
+        answer: &str,
+        sentences: &[Range<usize>],
+        position: usize,
+    ) -> Option<Range<usize>> {
+        let current = sentences
+            .iter()
+            .position(|sentence| sentence.contains(&position))?;
+        let before_marker = &answer[sentences[current].start..position];
+        if current > 0 && MARKER_RE.replace_all(before_marker, "").trim().is_empty() {
+            Some(sentences[current - 1].clone())
+        } else {
+            Some(sentences[current].clone())
+        }
+    }
+}
+
+impl<'a> ChainOutput<CitedQA<'a>> for CitedAnswer {
+    fn from_text_and_input(
+        input: CitedQA<'a>,
+        text: impl Into<String>,
+    ) -> Result<Self, (CitedQA<'a>, OutputParseError)> {
+        match Self::parse(&text.into(), &input.input_documents) {
+            Ok(answer) => Ok(answer),
+            Err(e) => Err((input, e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    fn documents() -> Vec<Document> {
+        vec![
+            Document::new("Luis is 24 years old.").with_metadata(HashMap::from([(
+                "source".to_string(),
+                Value::from("about.md"),
+            )])),
+            Document::new("Luis' favorite text editor is Nvim."),
+        ]
+    }
+
+    #[test]
+    fn test_parse_cited_answer() {
+        let text = indoc! {r#"
+            Luis uses Nvim [2]. He is 24 [1][2].
+            QUOTES:
+            [1] "Luis is 24 years old."
+            [2] "Luis likes Emacs.""#};
+
+        let answer = CitedAnswer::parse(text, &documents()).unwrap();
+
+        assert_eq!(answer.answer, "Luis uses Nvim [2]. He is 24 [1][2].");
+        assert_eq!(answer.citations.len(), 2);
+        assert_eq!(answer.citations[0].index, 1);
+        assert_eq!(answer.citations[0].quote, None);
+        assert_eq!(answer.citations[1].index, 0);
+        assert_eq!(answer.citations[1].source.as_deref(), Some("about.md"));
+        assert_eq!(
+            answer.citations[1].quote.as_deref(),
+            Some("Luis is 24 years old.")
+        );
+    }
+
+    #[test]
+    fn test_parse_citation_spans() {
+        let text = "Luis uses Nvim [2]. He is 24. [1][2]";
+
+        let answer = CitedAnswer::parse(text, &documents()).unwrap();
+
+        let sentences: Vec<Vec<&str>> = answer
+            .citations
+            .iter()
+            .map(|citation| {
+                citation
+                    .spans
+                    .iter()
+                    .map(|span| &answer.answer[span.clone()])
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            sentences,
+            vec![vec!["Luis uses Nvim [2].", "He is 24."], vec!["He is 24."],]
+        );
+    }
+
+    #[test]
+    fn test_parse_keeps_other_bracketed_numbers() {
+        let text = indoc! {r#"
+            Luis was born before [2024] and is 24 [1]. [0]
+            QUOTES:
+            [3] "Luis is 24 years old."
+            [1] "Luis is 24 years old.""#};
+
+        let answer = CitedAnswer::parse(text, &documents()).unwrap();
+
+        assert_eq!(
+            answer.answer,
+            "Luis was born before [2024] and is 24 [1]. [0]"
+        );
+        assert_eq!(answer.citations.len(), 1);
+        assert_eq!(answer.citations[0].index, 0);
+        assert_eq!(
+            answer.citations[0].quote.as_deref(),
+            Some("Luis is 24 years old.")
+        );
+        assert_eq!(answer.invalid_markers, vec![2024, 0]);
+    }
+
+    #[test]
+    fn test_parse_reports_made_up_citations() {
+        let text = "Luis uses Nvim [2] and Emacs [3][3].";
+
+        let answer = CitedAnswer::parse(text, &documents()).unwrap();
+
+        assert_eq!(answer.citations.len(), 1);
+        assert_eq!(answer.invalid_markers, vec![3]);
+        assert!(CitedAnswer::parse("Luis uses Nvim [2].", &documents())
+            .unwrap()
+            .invalid_markers
+            .is_empty());
+    }
+}
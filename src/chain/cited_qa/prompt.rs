@@ -0,0 +1,9 @@
+pub const DEFAULT_CITED_QA_TEMPLATE: &str = r#"Answer the question at the end using only the numbered sources below. After every sentence that uses information from a source, cite it with the number of the source in square brackets, like [1] or [1][3]. Only cite the numbers of the sources below. If you don't know the answer, just say that you don't know, don't try to make up an answer and don't cite anything.
+
+After the answer, write a line containing only "QUOTES:", followed by one line per cited source in the format [n] "exact quote from source n supporting the answer".
+
+Sources:
+{{context}}
+
+Question:{{question}}
+Helpful Answer:"#;
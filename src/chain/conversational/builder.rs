@@ -5,7 +5,7 @@ use tokio::sync::RwLock;
 use crate::{
     chain::{ChainOutput, ConversationalChainInputCtor, InputCtor, LLMChain, OutputCtor},
     llm::{LLMOutputCtor, LLM},
-    memory::{Memory, SimpleMemory},
+    memory::{AsyncMemory, Memory, SimpleMemory, SyncMemoryAdapter},
     output_parser::OutputParser,
     schemas::{BuilderError, MessageType},
     template::{MessageTemplate, PromptTemplate},
@@ -19,7 +19,7 @@ where
     for<'any> O::Target<'any>: ChainOutput<I::Target<'any>>,
 {
     llm: Option<Box<dyn LLM>>,
-    memory: Option<Arc<RwLock<dyn Memory>>>,
+    memory: Option<Arc<dyn AsyncMemory>>,
     output_parser: Option<Box<dyn OutputParser<ConversationalChainInputCtor<I>, LLMOutputCtor>>>,
     prompt: Option<PromptTemplate>,
     _phantom: std::marker::PhantomData<(I, O)>,
@@ -46,6 +46,12 @@ where
     }
 
     pub fn memory(mut self, memory: Arc<RwLock<dyn Memory>>) -> Self {
+        self.memory = Some(SyncMemoryAdapter::new(memory).into());
+        self
+    }
+
+    /// Sets an asynchronous memory, such as one backed by a database.
+    pub fn async_memory(mut self, memory: Arc<dyn AsyncMemory>) -> Self {
         self.memory = Some(memory);
         self
     }
@@ -84,7 +90,7 @@ where
 
         let memory = self
             .memory
-            .unwrap_or_else(|| Arc::new(RwLock::new(SimpleMemory::new())));
+            .unwrap_or_else(|| SyncMemoryAdapter::new(SimpleMemory::new().into()).into());
 
         Ok(ConversationalChain {
             llm_chain,
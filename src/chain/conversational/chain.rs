@@ -4,7 +4,7 @@ use async_stream::stream;
 use async_trait::async_trait;
 use futures::Stream;
 use futures_util::{pin_mut, StreamExt};
-use tokio::sync::{Mutex, RwLock};
+use tokio::sync::Mutex;
 
 use crate::{
     chain::{
@@ -12,7 +12,7 @@ use crate::{
         StringCtor,
     },
     llm::{LLMOutput, LLMOutputCtor},
-    memory::Memory,
+    memory::AsyncMemory,
     schemas::{messages::Message, GetPrompt, IntoWithUsage, Prompt, StreamData, WithUsage},
     template::TemplateError,
 };
@@ -25,7 +25,7 @@ where
     for<'any> O::Target<'any>: ChainOutput<I::Target<'any>>,
 {
     pub(super) llm_chain: LLMChain<ConversationalChainInputCtor<I>, LLMOutputCtor>,
-    pub memory: Arc<RwLock<dyn Memory>>,
+    pub memory: Arc<dyn AsyncMemory>,
     pub(super) _phantom: std::marker::PhantomData<O>,
 }
 
@@ -47,25 +47,30 @@ where
     for<'any> O::Target<'any>: ChainOutput<I::Target<'any>>,
 {
     async fn call<'a>(&self, input: I::Target<'a>) -> Result<WithUsage<O::Target<'a>>, ChainError> {
-        let human_message = Message::new_human_message(input.to_string());
+        let input_text = input.to_string();
+        let history = self.memory.relevant_messages(&input_text).await?;
+        let human_message = Message::new_human_message(input_text);
 
-        let history = {
-            let memory = self.memory.read().await;
-            memory.to_string()
-        };
-        let input = ConversationalChainInput::new(input).with_history(history);
+        let input = ConversationalChainInput::new(input)
+            .with_history(Message::messages_to_string(&history));
         let result = self.llm_chain.call_with_reference(&input).await?;
 
-        let mut memory = self.memory.write().await;
-        memory.add_message(human_message);
-
+        self.memory.add_message(human_message).await?;
         match &result.content {
-            LLMOutput::Text(text) => memory.add_ai_message(text.clone()),
-            LLMOutput::ToolCall(tool_calls) => memory.add_tool_call_message(tool_calls.clone()),
+            LLMOutput::Text(text) => self.memory.add_ai_message(text.clone()).await?,
+            LLMOutput::Audio(audio) => self.memory.add_ai_message(audio.transcript.clone()).await?,
+            LLMOutput::ToolCall(tool_calls) => {
+                self.memory
+                    .add_tool_call_message(tool_calls.clone())
+                    .await?
+            }
         }
 
         let content = match result.content {
             LLMOutput::Text(text) => O::Target::from_text_and_input(input.inner, text)?,
+            LLMOutput::Audio(audio) => {
+                O::Target::from_text_and_input(input.inner, audio.transcript)?
+            }
             LLMOutput::ToolCall(tool_calls) => {
                 O::Target::from_tool_call_and_input(input.inner, tool_calls)?
             }
@@ -79,13 +84,12 @@ where
         input: I::Target<'_>,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamData, ChainError>> + Send>>, ChainError>
     {
-        let human_message = Message::new_human_message(input.to_string());
+        let input_text = input.to_string();
+        let history = self.memory.relevant_messages(&input_text).await?;
+        let human_message = Message::new_human_message(input_text);
 
-        let history = {
-            let memory = self.memory.read().await;
-            memory.to_string()
-        };
-        let input = ConversationalChainInput::new(input).with_history(history);
+        let input = ConversationalChainInput::new(input)
+            .with_history(Message::messages_to_string(&history));
 
         let complete_ai_message = Arc::new(Mutex::new(String::new()));
         let complete_ai_message_clone = complete_ai_message.clone();
@@ -111,9 +115,14 @@ where
                 }
             }
 
-            let mut memory = memory.write().await;
-            memory.add_message(human_message);
-            memory.add_ai_message(complete_ai_message.lock().await.to_string());
+            let ai_message = complete_ai_message.lock().await.to_string();
+            let saved = async {
+                memory.add_message(human_message).await?;
+                memory.add_ai_message(ai_message).await
+            };
+            if let Err(e) = saved.await {
+                yield Err(e.into());
+            }
         };
 
         Ok(Box::pin(output_stream))
@@ -133,15 +142,54 @@ where
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Mutex as StdMutex;
+
     use async_openai::config::OpenAIConfig;
 
     use crate::{
         chain::DefaultChainInput,
-        llm::openai::{OpenAI, OpenAIModel},
+        llm::{
+            fake::FakeLLM,
+            openai::{OpenAI, OpenAIModel},
+        },
+        memory::VectorStoreRetrieverMemory,
+        vectorstore::memory::{tests::WordCountEmbedder, StoreBuilder},
     };
 
     use super::*;
 
+    #[tokio::test]
+    async fn test_conversational_recalls_relevant_turns() {
+        let prompts = Arc::new(StdMutex::new(Vec::new()));
+        let recorded = prompts.clone();
+        let llm = FakeLLM::new(move |prompt| {
+            recorded.lock().unwrap().push(prompt.to_string());
+            "Noted!".to_string()
+        });
+        let store = StoreBuilder::new()
+            .embedder(WordCountEmbedder)
+            .build()
+            .unwrap();
+        let memory = VectorStoreRetrieverMemory::new(store, 1);
+        let chain: ConversationalChain = ConversationalChain::builder()
+            .llm(llm)
+            .async_memory(Arc::new(memory))
+            .build()
+            .unwrap();
+
+        for input in ["My cat is called Tom", "My dog is called Rex"] {
+            chain.call(DefaultChainInput::new(input)).await.unwrap();
+        }
+        chain
+            .call(DefaultChainInput::new("What is my cat called?"))
+            .await
+            .unwrap();
+
+        let prompts = prompts.lock().unwrap();
+        assert!(prompts[2].contains("My cat is called Tom"));
+        assert!(!prompts[2].contains("Rex"));
+    }
+
     #[tokio::test]
     #[ignore]
     async fn test_invoke_conversational() {
@@ -1,7 +1,7 @@
 pub const DEFAULT_TEMPLATE: &str = r#"The following is a friendly conversation between a human and an AI. The AI is talkative and provides lots of specific details from its context. If the AI does not know the answer to a question, it truthfully says it does not know.
 
 Current conversation:
-{history}
+{chat_history}
 Human: {input}
 AI:
 "#;
@@ -1,29 +1,29 @@
-use std::sync::Arc;
+use std::{fmt::Display, sync::Arc};
+
 use tokio::sync::RwLock;
 
 use crate::{
     chain::{
-        Chain, ChainError, CondenseQuestionGeneratorChain, StuffDocumentBuilder, DEFAULT_OUTPUT_KEY,
+        Chain, CondenseQuestionGeneratorChain, CondenseQuestionPromptCtor, InputCtor, StringCtor,
+        StuffDocument, StuffQACtor,
     },
-    language_models::llm::LLM,
-    memory::SimpleMemory,
-    schemas::{BaseMemory, Retriever},
+    llm::LLM,
+    memory::{AsyncMemory, Memory, SimpleMemory, SyncMemoryAdapter},
+    schemas::{BuilderError, Retriever},
     template::PromptTemplate,
 };
 
 use super::ConversationalRetrieverChain;
 
-const CONVERSATIONAL_RETRIEVAL_QA_DEFAULT_INPUT_KEY: &str = "question";
-
 ///Conversation Retriever Chain Builder
 /// # Usage
 /// ## Convensional way
 /// ```rust,ignore
-/// let chain = ConversationalRetrieverChainBuilder::new()
+/// let chain: ConversationalRetrieverChain = ConversationalRetrieverChain::builder()
 ///     .llm(llm)
 ///     .rephrase_question(true)
 ///     .retriever(RetrieverMock {})
-///     .memory(SimpleMemory::new().into())
+///     .memory(Arc::new(RwLock::new(SimpleMemory::new())))
 ///     .build()
 ///     .expect("Error building ConversationalChain");
 ///
@@ -31,35 +31,44 @@ const CONVERSATIONAL_RETRIEVAL_QA_DEFAULT_INPUT_KEY: &str = "question";
 /// ## Custom way
 /// ```rust,ignore
 ///
-/// let llm = Box::new(OpenAI::default().with_model(OpenAIModel::Gpt35.to_string()));
-/// let combine_documents_chain = StuffDocument::load_stuff_qa(llm.clone_box());
-//  let condense_question_chain = CondenseQuestionGeneratorChain::new(llm.clone_box());
-/// let chain = ConversationalRetrieverChainBuilder::new()
+/// let llm: OpenAI<OpenAIConfig> = OpenAI::builder()
+///     .with_model(OpenAIModel::Gpt35.to_string())
+///     .build();
+/// let combine_documents_chain = StuffDocument::load_stuff_qa(llm.clone());
+/// let condense_question_chain = CondenseQuestionGeneratorChain::new(llm);
+/// let chain: ConversationalRetrieverChain = ConversationalRetrieverChain::builder()
 ///     .rephrase_question(true)
-///     .combine_documents_chain(Box::new(combine_documents_chain))
-///     .condense_question_chain(Box::new(condense_question_chain))
+///     .combine_documents_chain(combine_documents_chain)
+///     .condense_question_chain(condense_question_chain)
 ///     .retriever(RetrieverMock {})
-///     .memory(SimpleMemory::new().into())
 ///     .build()
 ///     .expect("Error building ConversationalChain");
 /// ```
 ///
-pub struct ConversationalRetrieverChainBuilder {
-    llm: Option<Box<dyn LLM>>,
+pub struct ConversationalRetrieverChainBuilder<I: InputCtor>
+where
+    for<'any> I::Target<'any>: Display,
+{
+    combine_llm: Option<Box<dyn LLM>>,
+    condense_llm: Option<Box<dyn LLM>>,
     retriever: Option<Box<dyn Retriever>>,
-    memory: Option<Arc<RwLock<dyn BaseMemory>>>,
-    combine_documents_chain: Option<Box<dyn Chain>>,
-    condense_question_chain: Option<Box<dyn Chain>>,
+    memory: Option<Arc<dyn AsyncMemory>>,
+    combine_documents_chain: Option<Box<dyn Chain<StuffQACtor, StringCtor>>>,
+    condense_question_chain: Option<Box<dyn Chain<CondenseQuestionPromptCtor, StringCtor>>>,
     prompt: Option<PromptTemplate>,
     rephrase_question: bool,
     return_source_documents: bool,
-    input_key: String,
-    output_key: String,
+    _phantom: std::marker::PhantomData<I>,
 }
-impl ConversationalRetrieverChainBuilder {
-    pub fn new() -> Self {
-        ConversationalRetrieverChainBuilder {
-            llm: None,
+
+impl<I: InputCtor> ConversationalRetrieverChainBuilder<I>
+where
+    for<'any> I::Target<'any>: Display,
+{
+    pub(super) fn new() -> Self {
+        Self {
+            combine_llm: None,
+            condense_llm: None,
             retriever: None,
             memory: None,
             combine_documents_chain: None,
@@ -67,52 +76,56 @@ impl ConversationalRetrieverChainBuilder {
             prompt: None,
             rephrase_question: true,
             return_source_documents: true,
-            input_key: CONVERSATIONAL_RETRIEVAL_QA_DEFAULT_INPUT_KEY.to_string(),
-            output_key: DEFAULT_OUTPUT_KEY.to_string(),
+            _phantom: std::marker::PhantomData,
         }
     }
 
-    pub fn retriever<R: Into<Box<dyn Retriever>>>(mut self, retriever: R) -> Self {
+    pub fn retriever(mut self, retriever: impl Into<Box<dyn Retriever>>) -> Self {
         self.retriever = Some(retriever.into());
         self
     }
 
     ///If you want to add a custom prompt,keep in mind which variables are obligatory.
-    pub fn prompt<P: Into<PromptTemplate>>(mut self, prompt: P) -> Self {
+    ///Only used when the combine documents chain is built from `llm`.
+    pub fn prompt(mut self, prompt: impl Into<PromptTemplate>) -> Self {
         self.prompt = Some(prompt.into());
         self
     }
 
-    pub fn input_key<S: Into<String>>(mut self, input_key: S) -> Self {
-        self.input_key = input_key.into();
+    pub fn memory(mut self, memory: Arc<RwLock<dyn Memory>>) -> Self {
+        self.memory = Some(SyncMemoryAdapter::new(memory).into());
         self
     }
 
-    pub fn memory(mut self, memory: Arc<RwLock<dyn BaseMemory>>) -> Self {
+    /// Sets an asynchronous memory, such as one backed by a database.
+    pub fn async_memory(mut self, memory: Arc<dyn AsyncMemory>) -> Self {
         self.memory = Some(memory);
         self
     }
 
-    pub fn llm<L: Into<Box<dyn LLM>>>(mut self, llm: L) -> Self {
-        self.llm = Some(llm.into());
+    /// Builds both the combine documents chain and the condense question chain from this LLM,
+    /// unless they are given explicitly.
+    pub fn llm<L: Into<Box<dyn LLM>> + Clone>(mut self, llm: L) -> Self {
+        self.combine_llm = Some(llm.clone().into());
+        self.condense_llm = Some(llm.into());
         self
     }
 
     ///Chain designed to take the documents and the question and generate an output
-    pub fn combine_documents_chain<C: Into<Box<dyn Chain>>>(
+    pub fn combine_documents_chain(
         mut self,
-        combine_documents_chain: C,
+        combine_documents_chain: impl Chain<StuffQACtor, StringCtor> + 'static,
     ) -> Self {
-        self.combine_documents_chain = Some(combine_documents_chain.into());
+        self.combine_documents_chain = Some(Box::new(combine_documents_chain));
         self
     }
 
-    ///Chain designed to reformulate the question based on the cat history
-    pub fn condense_question_chain<C: Into<Box<dyn Chain>>>(
+    ///Chain designed to reformulate the question based on the chat history
+    pub fn condense_question_chain(
         mut self,
-        condense_question_chain: C,
+        condense_question_chain: impl Chain<CondenseQuestionPromptCtor, StringCtor> + 'static,
     ) -> Self {
-        self.condense_question_chain = Some(condense_question_chain.into());
+        self.condense_question_chain = Some(Box::new(condense_question_chain));
         self
     }
 
@@ -126,34 +139,36 @@ impl ConversationalRetrieverChainBuilder {
         self
     }
 
-    pub fn build(mut self) -> Result<ConversationalRetrieverChain, BuilerError> {
-        if let Some(llm) = self.llm {
-            let combine_documents_chain = {
-                let mut builder = StuffDocumentBuilder::new().llm(llm.clone_box());
-                if let Some(prompt) = self.prompt {
-                    builder = builder.prompt(prompt);
-                }
-                builder.build()?
-            };
-            let condense_question_chain = CondenseQuestionGeneratorChain::new(llm.clone_box());
-            self.combine_documents_chain = Some(Box::new(combine_documents_chain));
-            self.condense_question_chain = Some(Box::new(condense_question_chain));
-        }
-
+    pub fn build(self) -> Result<ConversationalRetrieverChain<I>, BuilderError> {
         let retriever = self
             .retriever
             .ok_or(BuilderError::MissingField("retriever"))?;
 
+        let combine_documents_chain = match (self.combine_documents_chain, self.combine_llm) {
+            (Some(chain), _) => chain,
+            (None, Some(llm)) => {
+                let mut builder = StuffDocument::builder().llm(llm);
+                if let Some(prompt) = self.prompt {
+                    builder = builder.prompt(prompt);
+                }
+                let chain: StuffDocument = builder
+                    .build()
+                    .map_err(|e| BuilderError::Inner("combine_documents_chain", Box::new(e)))?;
+                Box::new(chain)
+            }
+            (None, None) => return Err(BuilderError::MissingField("combine_documents_chain")),
+        };
+
+        let condense_question_chain = match (self.condense_question_chain, self.condense_llm) {
+            (Some(chain), _) => chain,
+            (None, Some(llm)) => Box::new(CondenseQuestionGeneratorChain::new(llm)),
+            (None, None) => return Err(BuilderError::MissingField("condense_question_chain")),
+        };
+
         let memory = self
             .memory
-            .unwrap_or_else(|| Arc::new(RwLock::new(SimpleMemory::new())));
-
-        let combine_documents_chain = self
-            .combine_documents_chain
-            .ok_or(BuilderError::MissingField("combine_documents_chain"))?;
-        let condense_question_chain = self
-            .condense_question_chain
-            .ok_or(BuilderError::MissingField("condense_question_chain"))?;
+            .unwrap_or_else(|| SyncMemoryAdapter::new(SimpleMemory::new().into()).into());
+
         Ok(ConversationalRetrieverChain {
             retriever,
             memory,
@@ -161,14 +176,7 @@ impl ConversationalRetrieverChainBuilder {
             condense_question_chain,
             rephrase_question: self.rephrase_question,
             return_source_documents: self.return_source_documents,
-            input_key: self.input_key,
-            output_key: self.output_key,
+            _phantom: std::marker::PhantomData,
         })
     }
 }
-
-impl Default for ConversationalRetrieverChainBuilder {
-    fn default() -> Self {
-        Self::new()
-    }
-}
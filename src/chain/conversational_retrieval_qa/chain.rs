@@ -0,0 +1,416 @@
+use std::{fmt::Display, pin::Pin, sync::Arc};
+
+use async_stream::stream;
+use async_trait::async_trait;
+use futures::Stream;
+use futures_util::{pin_mut, StreamExt};
+use serde_json::{json, Value};
+
+use crate::{
+    chain::{
+        Chain, ChainError, CondenseQuestionPrompt, CondenseQuestionPromptCtor,
+        DefaultChainInputCtor, InputCtor, StringCtor, StuffQA, StuffQACtor,
+    },
+    memory::AsyncMemory,
+    schemas::{Document, IntoWithUsage, Message, OutputTrace, Retriever, StreamData, WithUsage},
+};
+
+use super::{
+    ConversationalRetrieverChainBuilder, ConversationalRetrieverOutput,
+    ConversationalRetrieverOutputCtor,
+};
+
+/// Answers questions about the documents of a retriever, keeping the conversation in memory.
+///
+/// When the conversation already has messages, the question is first rephrased into a
+/// standalone question by `condense_question_chain`, which is then used to retrieve documents.
+pub struct ConversationalRetrieverChain<I: InputCtor = DefaultChainInputCtor>
+where
+    for<'any> I::Target<'any>: Display,
+{
+    pub(super) retriever: Box<dyn Retriever>,
+    pub memory: Arc<dyn AsyncMemory>,
+    pub(super) combine_documents_chain: Box<dyn Chain<StuffQACtor, StringCtor>>,
+    pub(super) condense_question_chain: Box<dyn Chain<CondenseQuestionPromptCtor, StringCtor>>,
+    pub(super) rephrase_question: bool,
+    pub(super) return_source_documents: bool,
+    pub(super) _phantom: std::marker::PhantomData<I>,
+}
+
+impl<I: InputCtor> ConversationalRetrieverChain<I>
+where
+    for<'any> I::Target<'any>: Display,
+{
+    pub fn builder() -> ConversationalRetrieverChainBuilder<I> {
+        ConversationalRetrieverChainBuilder::new()
+    }
+
+    /// Returns the question to give to the retriever, rephrased with the chat history if
+    /// needed, and the usage of the rephrasing.
+    async fn get_question(
+        &self,
+        history: &[Message],
+        input: &str,
+    ) -> Result<Option<WithUsage<String>>, ChainError> {
+        if history.is_empty() || !self.rephrase_question {
+            return Ok(None);
+        }
+
+        let condense_input = CondenseQuestionPrompt::new()
+            .question(input)
+            .chat_history(history);
+        let question = self.condense_question_chain.call(condense_input).await?;
+
+        Ok(Some(question))
+    }
+
+    async fn get_documents(&self, question: &str) -> Result<Vec<Document>, ChainError> {
+        self.retriever
+            .get_relevant_documents(question)
+            .await
+            .map_err(|e| ChainError::RetrieverError(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl<I: InputCtor> Chain<I, ConversationalRetrieverOutputCtor> for ConversationalRetrieverChain<I>
+where
+    for<'any> I::Target<'any>: Display,
+{
+    async fn call<'a>(
+        &self,
+        input: I::Target<'a>,
+    ) -> Result<WithUsage<ConversationalRetrieverOutput>, ChainError> {
+        let trace = self.call_with_trace(input).await?;
+        Ok(trace.final_step.content.with_usage(trace.total_usage))
+    }
+
+    async fn call_with_trace<'a>(
+        &self,
+        input: I::Target<'a>,
+    ) -> Result<OutputTrace<ConversationalRetrieverOutput>, ChainError> {
+        let input = input.to_string();
+        let history = self.memory.relevant_messages(&input).await?;
+
+        let generated_question = self.get_question(&history, &input).await?;
+        let question = generated_question
+            .as_ref()
+            .map_or(input.as_str(), |q| q.content.as_str());
+
+        let documents = self.get_documents(question).await?;
+        let answer = self
+            .combine_documents_chain
+            .call(StuffQA::new().documents(&documents).question(question))
+            .await?;
+
+        self.memory.add_human_message(input.clone()).await?;
+        self.memory.add_ai_message(answer.content.clone()).await?;
+
+        let mut previous_steps = Vec::new();
+        let mut generated = None;
+        if let Some(question) = generated_question {
+            previous_steps.push(Value::String(question.content.clone()).with_usage(question.usage));
+            generated = Some(question.content);
+        }
+        let output = ConversationalRetrieverOutput {
+            answer: answer.content,
+            source_documents: if self.return_source_documents {
+                documents
+            } else {
+                Vec::new()
+            },
+            generated_question: generated,
+        };
+
+        Ok(OutputTrace::new(
+            previous_steps,
+            output.with_usage(answer.usage),
+        ))
+    }
+
+    /// Streams the answer, preceded by a [`StreamData`] with empty content whose value holds the
+    /// `source_documents` and the `generated_question`, as returned by `call`. The conversation
+    /// is only saved to memory once the answer is complete.
+    async fn stream(
+        &self,
+        input: I::Target<'_>,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamData, ChainError>> + Send>>, ChainError>
+    {
+        let input = input.to_string();
+        let history = self.memory.relevant_messages(&input).await?;
+
+        let generated_question = self.get_question(&history, &input).await?;
+        let question = generated_question
+            .as_ref()
+            .map_or(input.as_str(), |q| q.content.as_str());
+
+        let documents = self.get_documents(question).await?;
+        let stream = self
+            .combine_documents_chain
+            .stream(StuffQA::new().documents(&documents).question(question))
+            .await?;
+
+        let sources = StreamData::new(
+            json!({
+                "source_documents": if self.return_source_documents {
+                    documents.as_slice()
+                } else {
+                    &[]
+                },
+                "generated_question": generated_question.as_ref().map(|q| &q.content),
+            }),
+            generated_question.and_then(|q| q.usage),
+            "",
+        );
+
+        let memory = self.memory.clone();
+        let human_message = input.clone();
+        let output_stream = stream! {
+            yield Ok(sources);
+
+            let mut ai_message = String::new();
+            pin_mut!(stream);
+            while let Some(result) = stream.next().await {
+                match result {
+                    Ok(data) => {
+                        ai_message.push_str(&data.content);
+                        yield Ok(data);
+                    },
+                    Err(e) => {
+                        yield Err(e);
+                        return;
+                    }
+                }
+            }
+
+            let saved = async {
+                memory.add_human_message(human_message).await?;
+                memory.add_ai_message(ai_message).await
+            };
+            if let Err(e) = saved.await {
+                yield Err(e.into());
+            }
+        };
+
+        Ok(Box::pin(output_stream))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use async_openai::config::OpenAIConfig;
+    use indoc::indoc;
+
+    use crate::{
+        chain::{ChainInput, DefaultChainInput},
+        llm::openai::{OpenAI, OpenAIModel},
+        memory::SimpleMemory,
+        schemas::{Document, TokenUsage},
+    };
+
+    use super::*;
+
+    struct RetrieverTest {}
+
+    #[async_trait]
+    impl Retriever for RetrieverTest {
+        async fn get_relevant_documents(
+            &self,
+            _question: &str,
+        ) -> Result<Vec<Document>, Box<dyn Error>> {
+            Ok(vec![
+                Document::new(indoc! {"
+                    Question: Which is the favorite text editor of luis
+                    Answer: Nvim"
+                }),
+                Document::new(indoc! {"
+                    Question: How old is luis
+                    Answer: 24"
+                }),
+                Document::new(indoc! {"
+                    Question: Where do luis live
+                    Answer: Peru"
+                }),
+                Document::new(indoc! {"
+                    Question: What's his favorite food
+                    Answer: Pan con chicharron"
+                }),
+            ])
+        }
+    }
+
+    /// Answers with the question it was given.
+    struct Echo;
+
+    #[async_trait]
+    impl Chain<StuffQACtor, StringCtor> for Echo {
+        async fn call<'a>(&self, input: StuffQA<'a>) -> Result<WithUsage<String>, ChainError> {
+            Ok(input
+                .question
+                .into_owned()
+                .with_usage(Some(TokenUsage::new(1, 1))))
+        }
+
+        async fn stream(
+            &self,
+            input: StuffQA<'_>,
+        ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamData, ChainError>> + Send>>, ChainError>
+        {
+            let chunks = input
+                .question
+                .split_inclusive(' ')
+                .map(|chunk| Ok(StreamData::new(Value::Null, None, chunk)))
+                .collect::<Vec<_>>();
+            Ok(Box::pin(futures::stream::iter(chunks)))
+        }
+    }
+
+    /// Streams part of an answer, then fails.
+    struct Interrupted;
+
+    #[async_trait]
+    impl Chain<StuffQACtor, StringCtor> for Interrupted {
+        async fn call<'a>(&self, _input: StuffQA<'a>) -> Result<WithUsage<String>, ChainError> {
+            Err(ChainError::OtherError("interrupted".into()))
+        }
+
+        async fn stream(
+            &self,
+            _input: StuffQA<'_>,
+        ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamData, ChainError>> + Send>>, ChainError>
+        {
+            Ok(Box::pin(futures::stream::iter(vec![
+                Ok(StreamData::new(Value::Null, None, "Luis is")),
+                Err(ChainError::OtherError("interrupted".into())),
+                Ok(StreamData::new(Value::Null, None, " 24")),
+            ])))
+        }
+    }
+
+    /// Rephrases every question to the same standalone question.
+    struct Condense;
+
+    #[async_trait]
+    impl Chain<CondenseQuestionPromptCtor, StringCtor> for Condense {
+        async fn call<'a>(
+            &self,
+            input: CondenseQuestionPrompt<'a>,
+        ) -> Result<WithUsage<String>, ChainError> {
+            let question = input.text_replacements()["question"].to_string();
+            Ok(format!("standalone {question}").with_usage(Some(TokenUsage::new(1, 1))))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_conversational_retriever_rephrases_with_history() {
+        let chain: ConversationalRetrieverChain = ConversationalRetrieverChain::builder()
+            .retriever(RetrieverTest {})
+            .combine_documents_chain(Echo)
+            .condense_question_chain(Condense)
+            .build()
+            .unwrap();
+
+        let first = chain.call(DefaultChainInput::new("Hola")).await.unwrap();
+        assert_eq!(first.content.answer, "Hola");
+        assert_eq!(first.content.generated_question, None);
+        assert_eq!(first.content.source_documents.len(), 4);
+
+        let second = chain
+            .call_with_trace(DefaultChainInput::new("y su comida?"))
+            .await
+            .unwrap();
+        assert_eq!(second.final_step.content.answer, "standalone y su comida?");
+        assert_eq!(second.previous_steps.len(), 1);
+        assert_eq!(second.total_usage.unwrap().total_tokens, 4);
+
+        assert_eq!(chain.memory.messages().await.unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_conversational_retriever_stream_leads_with_sources() {
+        let chain: ConversationalRetrieverChain = ConversationalRetrieverChain::builder()
+            .retriever(RetrieverTest {})
+            .combine_documents_chain(Echo)
+            .condense_question_chain(Condense)
+            .build()
+            .unwrap();
+        chain.call(DefaultChainInput::new("Hola")).await.unwrap();
+
+        let stream = chain
+            .stream(DefaultChainInput::new("y su comida?"))
+            .await
+            .unwrap();
+        let data: Vec<StreamData> = stream.map(Result::unwrap).collect().await;
+
+        assert_eq!(data[0].content, "");
+        assert_eq!(
+            data[0].value["source_documents"].as_array().unwrap().len(),
+            4
+        );
+        assert_eq!(
+            data[0].value["generated_question"],
+            "standalone y su comida?"
+        );
+        let answer: String = data[1..].iter().map(|d| d.content.as_str()).collect();
+        assert_eq!(answer, "standalone y su comida?");
+
+        let messages = chain.memory.messages().await.unwrap();
+        assert_eq!(messages.len(), 4);
+        assert_eq!(messages[3].content, "standalone y su comida?");
+    }
+
+    #[tokio::test]
+    async fn test_conversational_retriever_stream_stops_on_error() {
+        let chain: ConversationalRetrieverChain = ConversationalRetrieverChain::builder()
+            .retriever(RetrieverTest {})
+            .combine_documents_chain(Interrupted)
+            .condense_question_chain(Condense)
+            .build()
+            .unwrap();
+
+        let stream = chain.stream(DefaultChainInput::new("Hola")).await.unwrap();
+        let results: Vec<_> = stream.collect().await;
+
+        assert_eq!(results.len(), 3);
+        assert!(results[2].is_err());
+        assert!(chain.memory.messages().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_invoke_retriever_conversational() {
+        let llm: OpenAI<OpenAIConfig> = OpenAI::builder()
+            .with_model(OpenAIModel::Gpt35.to_string())
+            .build();
+        let chain: ConversationalRetrieverChain = ConversationalRetrieverChain::builder()
+            .llm(llm)
+            .retriever(RetrieverTest {})
+            .memory(SimpleMemory::new().into())
+            .build()
+            .expect("Error building ConversationalChain");
+
+        let result_first = chain.call(DefaultChainInput::new("Hola")).await;
+        assert!(
+            result_first.is_ok(),
+            "Error invoking ConversationalRetrieverChain: {:?}",
+            result_first.err()
+        );
+        if let Ok(result) = result_first {
+            println!("Result: {:?}", result.content);
+        }
+
+        let result_second = chain
+            .call(DefaultChainInput::new("Cual es la comida favorita de luis"))
+            .await;
+        assert!(
+            result_second.is_ok(),
+            "Error invoking ConversationalRetrieverChain: {:?}",
+            result_second.err()
+        );
+        if let Ok(result) = result_second {
+            println!("Result: {:?}", result.content);
+        }
+    }
+}
@@ -1,6 +1,8 @@
 mod builder;
 pub use builder::*;
 
-#[allow(clippy::module_inception)]
-mod conversational_retrieval_qa;
-pub use conversational_retrieval_qa::*;
+mod chain;
+pub use chain::*;
+
+mod output;
+pub use output::*;
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{chain::Ctor, schemas::Document};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Ctor)]
+pub struct ConversationalRetrieverOutput {
+    pub answer: String,
+    /// The documents used to answer, empty if `return_source_documents` is disabled.
+    pub source_documents: Vec<Document>,
+    /// The standalone question sent to the retriever, if the question was rephrased.
+    pub generated_question: Option<String>,
+}
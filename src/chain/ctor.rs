@@ -44,3 +44,21 @@ impl Ctor for StringCtor {
 impl Ctor for () {
     type Target<'a> = ();
 }
+
+pub struct VecCtor<T>(std::marker::PhantomData<T>);
+impl<T: Ctor> Ctor for VecCtor<T> {
+    type Target<'a> = Vec<T::Target<'a>>;
+}
+
+macro_rules! impl_tuple_ctor {
+    ($($T:ident),+) => {
+        impl<$($T: Ctor),+> Ctor for ($($T,)+) {
+            type Target<'a> = ($($T::Target<'a>,)+);
+        }
+    };
+}
+
+impl_tuple_ctor!(A, B);
+impl_tuple_ctor!(A, B, C);
+impl_tuple_ctor!(A, B, C, D);
+impl_tuple_ctor!(A, B, C, D, E);
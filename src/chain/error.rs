@@ -1,7 +1,10 @@
+use std::time::Duration;
+
 use thiserror::Error;
 
 use crate::{
-    agent::AgentError, llm::LLMError, output_parser::OutputParseError, template::TemplateError,
+    agent::AgentError, document_loaders::LoaderError, llm::LLMError, memory::MemoryError,
+    output_parser::OutputParseError, template::TemplateError,
 };
 
 #[derive(Error, Debug)]
@@ -15,12 +18,27 @@ pub enum ChainError {
     #[error("Retriever error: {0}")]
     RetrieverError(String),
 
+    #[error("Router error: {0}")]
+    RouterError(String),
+
+    #[error("Loader error: {0}")]
+    LoaderError(#[from] LoaderError),
+
+    #[error("Memory error: {0}")]
+    MemoryError(#[from] MemoryError),
+
     #[error("Output parse error: {0}")]
     OutputParseError(#[from] OutputParseError),
 
     #[error("Prompt error: {0}")]
     PromptError(#[from] TemplateError),
 
+    #[error("Run timed out after {0:?}")]
+    Timeout(Duration),
+
+    #[error("Run was cancelled")]
+    Cancelled,
+
     #[error("Error: {0}")]
     OtherError(String),
 }
@@ -46,6 +46,7 @@ where
         let content = match content {
             LLMOutput::Text(text) => self.output_parser.parse_from_text(text),
             LLMOutput::ToolCall(tool_calls) => O::Target::from_tool_call(tool_calls),
+            LLMOutput::Audio(audio) => self.output_parser.parse_from_text(audio.transcript),
         }?;
 
         Ok(content.with_usage(usage))
@@ -87,6 +88,9 @@ where
         let content = match content {
             LLMOutput::Text(text) => self.output_parser.parse_from_text_and_input(input, text)?,
             LLMOutput::ToolCall(tool_calls) => O::Target::from_tool_call(tool_calls)?,
+            LLMOutput::Audio(audio) => self
+                .output_parser
+                .parse_from_text_and_input(input, audio.transcript)?,
         };
 
         Ok(content.with_usage(usage))
@@ -0,0 +1,130 @@
+use std::future::Future;
+
+use async_trait::async_trait;
+use futures::{StreamExt, TryStreamExt};
+
+use crate::{
+    chain::{Chain, ChainError, ChainInput, Ctor, VecCtor},
+    schemas::{IntoWithUsage, OutputTrace, TokenUsage, WithUsage},
+};
+
+const DEFAULT_MAX_CONCURRENCY: usize = 4;
+
+/// Awaits the calls with at most `max_concurrency` running at a time, returning their outputs
+/// in order or the first error.
+///
+/// The calls are futures created upfront (they are lazy) so that the stream holds no closure.
+pub(crate) async fn try_join_buffered<T, E, F>(
+    calls: Vec<F>,
+    max_concurrency: usize,
+) -> Result<Vec<T>, E>
+where
+    F: Future<Output = Result<T, E>>,
+{
+    futures::stream::iter(calls)
+        .buffered(max_concurrency.max(1))
+        .try_collect()
+        .await
+}
+
+/// Applies a chain to every element of a `Vec` of inputs, running at most `max_concurrency`
+/// calls at a time. The outputs are returned in the same order as the inputs.
+pub struct MapChain<'a, I: Ctor, O: Ctor>
+where
+    for<'any> I::Target<'any>: ChainInput,
+{
+    pub chain: Box<dyn Chain<I, O> + 'a>,
+    pub max_concurrency: usize,
+}
+
+impl<'a, I: Ctor, O: Ctor> MapChain<'a, I, O>
+where
+    for<'any> I::Target<'any>: ChainInput,
+{
+    pub fn new(chain: impl Chain<I, O> + 'a) -> Self {
+        Self {
+            chain: Box::new(chain),
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+        }
+    }
+
+    /// Sets the maximum number of inner chain calls running at the same time.
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency.max(1);
+        self
+    }
+}
+
+#[async_trait]
+impl<I: Ctor, O: Ctor> Chain<VecCtor<I>, VecCtor<O>> for MapChain<'_, I, O>
+where
+    for<'any> I::Target<'any>: ChainInput,
+{
+    async fn call<'a>(
+        &self,
+        input: Vec<I::Target<'a>>,
+    ) -> Result<WithUsage<Vec<O::Target<'a>>>, ChainError> {
+        let calls = input
+            .into_iter()
+            .map(|input| self.chain.call(input))
+            .collect();
+        let results = try_join_buffered(calls, self.max_concurrency).await?;
+
+        let usage = TokenUsage::merge_options(results.iter().map(|r| &r.usage));
+        let content = results.into_iter().map(|r| r.content).collect::<Vec<_>>();
+        Ok(content.with_usage(usage))
+    }
+
+    async fn call_with_trace<'a>(
+        &self,
+        input: Vec<I::Target<'a>>,
+    ) -> Result<OutputTrace<Vec<O::Target<'a>>>, ChainError> {
+        let calls = input
+            .into_iter()
+            .map(|input| self.chain.call_with_trace(input))
+            .collect();
+        let traces = try_join_buffered(calls, self.max_concurrency).await?;
+
+        let usage = TokenUsage::merge_options(traces.iter().map(|t| &t.final_step.usage));
+        let (previous_steps, content): (Vec<_>, Vec<_>) = traces
+            .into_iter()
+            .map(|t| (t.previous_steps, t.final_step.content))
+            .unzip();
+        let previous_steps = previous_steps.into_iter().flatten().collect();
+
+        Ok(OutputTrace::new(previous_steps, content.with_usage(usage)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chain::{DefaultChainInput, DefaultChainInputCtor, StringCtor};
+
+    use super::*;
+
+    struct Upper;
+
+    #[async_trait]
+    impl Chain<DefaultChainInputCtor, StringCtor> for Upper {
+        async fn call<'a>(
+            &self,
+            input: DefaultChainInput<'a>,
+        ) -> Result<WithUsage<String>, ChainError> {
+            Ok(input
+                .to_string()
+                .to_uppercase()
+                .with_usage(Some(TokenUsage::new(1, 1))))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_map_chain() {
+        let chain = MapChain::new(Upper).with_max_concurrency(2);
+        let inputs = ["a", "b", "c"].map(DefaultChainInput::new).to_vec();
+
+        let result = chain.call(inputs).await.unwrap();
+
+        assert_eq!(result.content, vec!["A", "B", "C"]);
+        assert_eq!(result.usage.unwrap().total_tokens, 6);
+    }
+}
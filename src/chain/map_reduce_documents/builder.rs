@@ -0,0 +1,85 @@
+use crate::{
+    chain::{Chain, StringCtor, StuffQACtor},
+    schemas::BuilderError,
+};
+
+use super::{
+    MapReduceDocumentsChain, DEFAULT_BATCH_SIZE, DEFAULT_MAX_CONCURRENCY, DEFAULT_TOKEN_MAX,
+};
+
+pub struct MapReduceDocumentsChainBuilder {
+    map_chain: Option<Box<dyn Chain<StuffQACtor, StringCtor>>>,
+    collapse_chain: Option<Box<dyn Chain<StuffQACtor, StringCtor>>>,
+    reduce_chain: Option<Box<dyn Chain<StuffQACtor, StringCtor>>>,
+    batch_size: usize,
+    token_max: usize,
+    max_concurrency: usize,
+}
+
+impl MapReduceDocumentsChainBuilder {
+    pub(super) fn new() -> Self {
+        Self {
+            map_chain: None,
+            collapse_chain: None,
+            reduce_chain: None,
+            batch_size: DEFAULT_BATCH_SIZE,
+            token_max: DEFAULT_TOKEN_MAX,
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+        }
+    }
+
+    /// The chain applied to every batch of documents.
+    pub fn map_chain(mut self, chain: impl Chain<StuffQACtor, StringCtor> + 'static) -> Self {
+        self.map_chain = Some(Box::new(chain));
+        self
+    }
+
+    /// The chain used to shrink the mapped outputs while they don't fit in `token_max`.
+    /// Defaults to the reduce chain.
+    pub fn collapse_chain(mut self, chain: impl Chain<StuffQACtor, StringCtor> + 'static) -> Self {
+        self.collapse_chain = Some(Box::new(chain));
+        self
+    }
+
+    /// The chain combining the mapped outputs into the final answer.
+    pub fn reduce_chain(mut self, chain: impl Chain<StuffQACtor, StringCtor> + 'static) -> Self {
+        self.reduce_chain = Some(Box::new(chain));
+        self
+    }
+
+    /// Number of documents given to each call of the map chain.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Maximum number of tokens (cl100k_base) of the documents given to the reduce chain.
+    pub fn token_max(mut self, token_max: usize) -> Self {
+        self.token_max = token_max;
+        self
+    }
+
+    /// Maximum number of map or collapse calls running at the same time.
+    pub fn max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency.max(1);
+        self
+    }
+
+    pub fn build(self) -> Result<MapReduceDocumentsChain, BuilderError> {
+        let map_chain = self
+            .map_chain
+            .ok_or(BuilderError::MissingField("map_chain"))?;
+        let reduce_chain = self
+            .reduce_chain
+            .ok_or(BuilderError::MissingField("reduce_chain"))?;
+
+        Ok(MapReduceDocumentsChain {
+            map_chain,
+            collapse_chain: self.collapse_chain,
+            reduce_chain,
+            batch_size: self.batch_size,
+            token_max: self.token_max,
+            max_concurrency: self.max_concurrency,
+        })
+    }
+}
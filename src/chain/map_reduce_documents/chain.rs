@@ -0,0 +1,351 @@
+use std::pin::Pin;
+
+use async_trait::async_trait;
+use futures::{Stream, TryStreamExt};
+use serde_json::Value;
+
+use crate::{
+    chain::{try_join_buffered, Chain, ChainError, LLMChain, StringCtor, StuffQA, StuffQACtor},
+    document_loaders::LoaderError,
+    llm::LLM,
+    schemas::{
+        Document, IntoWithUsage, MessageType, OutputTrace, StreamData, TokenUsage, WithUsage,
+    },
+    template::MessageTemplate,
+};
+
+use super::{
+    MapReduceDocumentsChainBuilder, DEFAULT_MAP_QA_TEMPLATE, DEFAULT_MAP_SUMMARIZE_TEMPLATE,
+    DEFAULT_REDUCE_QA_TEMPLATE, DEFAULT_REDUCE_SUMMARIZE_TEMPLATE,
+};
+
+pub(super) const DEFAULT_BATCH_SIZE: usize = 1;
+pub(super) const DEFAULT_TOKEN_MAX: usize = 3000;
+pub(super) const DEFAULT_MAX_CONCURRENCY: usize = 4;
+
+/// Combines documents by first applying `map_chain` to every batch of documents, then
+/// collapsing the outputs until they fit in `token_max`, and finally asking `reduce_chain` for
+/// the answer.
+///
+/// Unlike [`StuffDocument`](crate::chain::StuffDocument), the documents never have to fit in a
+/// single prompt.
+pub struct MapReduceDocumentsChain {
+    pub(super) map_chain: Box<dyn Chain<StuffQACtor, StringCtor>>,
+    pub(super) collapse_chain: Option<Box<dyn Chain<StuffQACtor, StringCtor>>>,
+    pub(super) reduce_chain: Box<dyn Chain<StuffQACtor, StringCtor>>,
+    pub(super) batch_size: usize,
+    pub(super) token_max: usize,
+    pub(super) max_concurrency: usize,
+}
+
+impl MapReduceDocumentsChain {
+    pub fn builder() -> MapReduceDocumentsChainBuilder {
+        MapReduceDocumentsChainBuilder::new()
+    }
+
+    /// Returns a map-reduce chain with prompts designed for question answering.
+    pub fn load_map_reduce_qa<L: Into<Box<dyn LLM>> + Clone>(llm: L) -> Self {
+        Self::from_templates(llm, DEFAULT_MAP_QA_TEMPLATE, DEFAULT_REDUCE_QA_TEMPLATE)
+    }
+
+    /// Returns a map-reduce chain with prompts designed for summarization. The question of the
+    /// input is ignored.
+    pub fn load_map_reduce_summarize<L: Into<Box<dyn LLM>> + Clone>(llm: L) -> Self {
+        Self::from_templates(
+            llm,
+            DEFAULT_MAP_SUMMARIZE_TEMPLATE,
+            DEFAULT_REDUCE_SUMMARIZE_TEMPLATE,
+        )
+    }
+
+    fn from_templates<L: Into<Box<dyn LLM>> + Clone>(
+        llm: L,
+        map_template: &str,
+        reduce_template: &str,
+    ) -> Self {
+        let map_chain = LLMChain::builder()
+            .prompt(MessageTemplate::from_jinja2(
+                MessageType::System,
+                map_template,
+            ))
+            .llm(llm.clone())
+            .build()
+            .unwrap(); // Safe to unwrap, the prompt and the LLM are set.
+        let reduce_chain = LLMChain::builder()
+            .prompt(MessageTemplate::from_jinja2(
+                MessageType::System,
+                reduce_template,
+            ))
+            .llm(llm)
+            .build()
+            .unwrap(); // Safe to unwrap, the prompt and the LLM are set.
+
+        Self::builder()
+            .map_chain(map_chain)
+            .reduce_chain(reduce_chain)
+            .build()
+            .unwrap() // Safe to unwrap, the map and reduce chains are set.
+    }
+
+    /// Collects the documents of a stream, such as the one returned by
+    /// [`Loader::load`](crate::document_loaders::Loader::load), and combines them.
+    pub async fn call_with_documents_stream<S>(
+        &self,
+        documents: S,
+        question: &str,
+    ) -> Result<WithUsage<String>, ChainError>
+    where
+        S: Stream<Item = Result<Document, LoaderError>> + Send,
+    {
+        let documents: Vec<Document> = documents.try_collect().await?;
+        let input = StuffQA::new().documents(documents).question(question);
+        self.call(input).await
+    }
+
+    /// Runs the map step and the collapse steps, returning the documents to give to the reduce
+    /// chain along with one trace step per round.
+    async fn map_and_collapse(
+        &self,
+        input: &StuffQA<'_>,
+    ) -> Result<(Vec<Document>, Vec<WithUsage<Value>>), ChainError> {
+        let mut steps = Vec::new();
+
+        let batches = input
+            .input_documents
+            .chunks(self.batch_size)
+            .map(|batch| {
+                StuffQA::new()
+                    .documents(batch)
+                    .question(input.question.as_ref())
+            })
+            .collect();
+        let mapped = self.combine_all(self.map_chain.as_ref(), batches).await?;
+        steps.push(Self::step(&mapped));
+        let mut documents = mapped.content;
+
+        let collapse_chain = self.collapse_chain.as_ref().unwrap_or(&self.reduce_chain);
+        let mut tokens = count_documents_tokens(&documents);
+        while documents.len() > 1 && tokens > self.token_max {
+            let groups = group_by_token_max(&documents, self.token_max)
+                .into_iter()
+                .map(|group| {
+                    StuffQA::new()
+                        .documents(group)
+                        .question(input.question.as_ref())
+                })
+                .collect();
+            let collapsed = self.combine_all(collapse_chain.as_ref(), groups).await?;
+            steps.push(Self::step(&collapsed));
+            let collapsed = collapsed.content;
+
+            let collapsed_tokens = count_documents_tokens(&collapsed);
+            if collapsed_tokens >= tokens {
+                return Err(ChainError::OtherError(format!(
+                    "Collapsing documents did not reduce their size below token_max ({})",
+                    self.token_max
+                )));
+            }
+            documents = collapsed;
+            tokens = collapsed_tokens;
+        }
+
+        Ok((documents, steps))
+    }
+
+    async fn combine_all(
+        &self,
+        chain: &dyn Chain<StuffQACtor, StringCtor>,
+        inputs: Vec<StuffQA<'_>>,
+    ) -> Result<WithUsage<Vec<Document>>, ChainError> {
+        let calls = inputs.into_iter().map(|input| chain.call(input)).collect();
+        let results = try_join_buffered(calls, self.max_concurrency).await?;
+
+        let usage = TokenUsage::merge_options(results.iter().map(|r| &r.usage));
+        let documents = results
+            .into_iter()
+            .map(|r| Document::new(r.content))
+            .collect::<Vec<_>>();
+        Ok(documents.with_usage(usage))
+    }
+
+    fn step(documents: &WithUsage<Vec<Document>>) -> WithUsage<Value> {
+        let outputs = documents
+            .content
+            .iter()
+            .map(|d| Value::String(d.page_content.clone()))
+            .collect();
+        Value::Array(outputs).with_usage(documents.usage.clone())
+    }
+}
+
+#[async_trait]
+impl Chain<StuffQACtor, StringCtor> for MapReduceDocumentsChain {
+    async fn call<'a>(&self, input: StuffQA<'a>) -> Result<WithUsage<String>, ChainError> {
+        let trace = self.call_with_trace(input).await?;
+        Ok(trace.final_step.content.with_usage(trace.total_usage))
+    }
+
+    async fn call_with_trace<'a>(
+        &self,
+        input: StuffQA<'a>,
+    ) -> Result<OutputTrace<String>, ChainError> {
+        let (documents, steps) = self.map_and_collapse(&input).await?;
+
+        let reduce_input = StuffQA::new()
+            .documents(documents)
+            .question(input.question.as_ref());
+        let result = self.reduce_chain.call(reduce_input).await?;
+
+        Ok(OutputTrace::new(steps, result))
+    }
+
+    async fn stream(
+        &self,
+        input: StuffQA<'_>,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamData, ChainError>> + Send>>, ChainError>
+    {
+        let (documents, _) = self.map_and_collapse(&input).await?;
+
+        let reduce_input = StuffQA::new()
+            .documents(documents)
+            .question(input.question.as_ref());
+        self.reduce_chain.stream(reduce_input).await
+    }
+}
+
+fn count_tokens(text: &str) -> usize {
+    tiktoken_rs::cl100k_base_singleton()
+        .encode_ordinary(text)
+        .len()
+}
+
+fn count_documents_tokens(documents: &[Document]) -> usize {
+    documents
+        .iter()
+        .map(|d| count_tokens(&d.page_content))
+        .sum()
+}
+
+/// Splits the documents into consecutive groups whose total number of tokens does not exceed
+/// `token_max`. A document larger than `token_max` gets a group of its own.
+fn group_by_token_max(documents: &[Document], token_max: usize) -> Vec<&[Document]> {
+    let mut groups = Vec::new();
+    let mut start = 0;
+    let mut group_tokens = 0;
+
+    for (i, document) in documents.iter().enumerate() {
+        let tokens = count_tokens(&document.page_content);
+        if i > start && group_tokens + tokens > token_max {
+            groups.push(&documents[start..i]);
+            start = i;
+            group_tokens = 0;
+        }
+        group_tokens += tokens;
+    }
+    if start < documents.len() {
+        groups.push(&documents[start..]);
+    }
+
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chain::ChainInput;
+
+    use super::*;
+
+    /// Answers with `"{prefix}({context})"`, the context being the documents joined by "\n".
+    struct Wrap(&'static str);
+
+    #[async_trait]
+    impl Chain<StuffQACtor, StringCtor> for Wrap {
+        async fn call<'a>(&self, input: StuffQA<'a>) -> Result<WithUsage<String>, ChainError> {
+            let context = input.text_replacements()["context"].replace('\n', ",");
+            Ok(format!("{}({})", self.0, context).with_usage(Some(TokenUsage::new(1, 1))))
+        }
+    }
+
+    /// Always answers with the same short text.
+    struct Shorten;
+
+    #[async_trait]
+    impl Chain<StuffQACtor, StringCtor> for Shorten {
+        async fn call<'a>(&self, _input: StuffQA<'a>) -> Result<WithUsage<String>, ChainError> {
+            Ok("short".to_string().with_usage(None))
+        }
+    }
+
+    fn documents() -> Vec<Document> {
+        ["a", "b", "c"].map(Document::new).to_vec()
+    }
+
+    #[tokio::test]
+    async fn test_map_reduce_batches() {
+        let chain = MapReduceDocumentsChain::builder()
+            .map_chain(Wrap("map"))
+            .reduce_chain(Wrap("reduce"))
+            .batch_size(2)
+            .build()
+            .unwrap();
+        let documents = documents();
+
+        let result = chain
+            .call_with_trace(StuffQA::new().documents(&documents))
+            .await
+            .unwrap();
+
+        assert_eq!(result.final_step.content, "reduce(map(a,b),map(c))");
+        assert_eq!(result.previous_steps.len(), 1);
+        assert_eq!(result.total_usage.unwrap().total_tokens, 6);
+    }
+
+    #[tokio::test]
+    async fn test_map_reduce_collapses_over_token_max() {
+        let chain = MapReduceDocumentsChain::builder()
+            .map_chain(Wrap("map"))
+            .collapse_chain(Shorten)
+            .reduce_chain(Wrap("reduce"))
+            .token_max(4)
+            .build()
+            .unwrap();
+        let documents = documents();
+
+        let result = chain
+            .call_with_trace(StuffQA::new().documents(&documents))
+            .await
+            .unwrap();
+
+        assert_eq!(result.previous_steps.len(), 2);
+        assert!(result.final_step.content.contains("short"));
+        assert!(!result.final_step.content.contains("map"));
+    }
+
+    #[test]
+    fn test_group_by_token_max() {
+        let documents = documents();
+
+        assert_eq!(group_by_token_max(&documents, 2).len(), 2);
+        assert_eq!(group_by_token_max(&documents, 0).len(), 3);
+        assert_eq!(group_by_token_max(&documents, 100).len(), 1);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_map_reduce_summarize() {
+        let chain = MapReduceDocumentsChain::load_map_reduce_summarize(
+            crate::llm::openai::OpenAI::default(),
+        );
+        let documents = [
+            Document::new("Luis is 24 years old and lives in Madrid."),
+            Document::new("Luis' favorite text editor is Nvim."),
+        ];
+
+        let output = chain
+            .call(StuffQA::new().documents(&documents))
+            .await
+            .unwrap()
+            .content;
+        println!("{output}");
+    }
+}
@@ -0,0 +1,8 @@
+mod chain;
+pub use chain::*;
+
+mod builder;
+pub use builder::*;
+
+mod prompt;
+pub use prompt::*;
@@ -0,0 +1,26 @@
+pub const DEFAULT_MAP_QA_TEMPLATE: &str = r#"Use the following portion of a long document to see if any of the text is relevant to answer the question. Return any relevant text verbatim. If nothing is relevant, return an empty answer.
+
+{{context}}
+
+Question:{{question}}
+Relevant text, if any:"#;
+
+pub const DEFAULT_REDUCE_QA_TEMPLATE: &str = r#"Given the following extracted parts of a long document and a question, create a final answer. If you don't know the answer, just say that you don't know, don't try to make up an answer.
+
+{{context}}
+
+Question:{{question}}
+Helpful Answer:"#;
+
+pub const DEFAULT_MAP_SUMMARIZE_TEMPLATE: &str = r#"Write a concise summary of the following:
+
+{{context}}
+
+CONCISE SUMMARY:"#;
+
+pub const DEFAULT_REDUCE_SUMMARIZE_TEMPLATE: &str = r#"The following is a set of summaries:
+
+{{context}}
+
+Take these and distill them into a final, consolidated summary of the main themes.
+CONCISE SUMMARY:"#;
@@ -5,6 +5,9 @@ pub use chain::*;
 mod pure_chain;
 pub use pure_chain::*;
 
+mod run_config;
+pub use run_config::*;
+
 mod ctor;
 pub use ctor::*;
 
@@ -23,20 +26,38 @@ pub mod llm;
 mod sequential;
 pub use sequential::*;
 
+mod parallel;
+pub use parallel::*;
+
+mod map;
+pub use map::*;
+
+mod router;
+pub use router::*;
+
 pub mod sql_database;
 pub use sql_database::*;
 
 mod stuff_documents;
 pub use stuff_documents::*;
 
+mod map_reduce_documents;
+pub use map_reduce_documents::*;
+
+mod refine_documents;
+pub use refine_documents::*;
+
 mod question_answering;
 pub use question_answering::*;
 
+mod cited_qa;
+pub use cited_qa::*;
+
 mod empty;
 pub use empty::*;
 
-// mod conversational_retrieval_qa;
-// pub use conversational_retrieval_qa::*;
+mod conversational_retrieval_qa;
+pub use conversational_retrieval_qa::*;
 
 mod error;
 pub use error::*;
@@ -0,0 +1,131 @@
+use async_trait::async_trait;
+
+use crate::{
+    chain::{Chain, ChainError, Ctor, InputCtor},
+    schemas::{IntoWithUsage, OutputTrace, TokenUsage, WithUsage},
+};
+
+/// Runs several chains on the same input concurrently and collects their outputs into a tuple.
+///
+/// The inner chains are stored as a tuple, `ParallelChain((chain1, chain2))` implements
+/// `Chain<I, (O1, O2)>`. Tuples of up to five chains are supported. If a struct is preferred over
+/// a tuple, implement `From<(O1, O2)>` for it and append a chain with [`sequential_chain!`].
+///
+/// [`sequential_chain!`]: crate::sequential_chain
+pub struct ParallelChain<C>(pub C);
+
+impl<C> ParallelChain<C> {
+    pub fn new(chains: C) -> Self {
+        Self(chains)
+    }
+}
+
+macro_rules! impl_parallel_chain {
+    ($(($C:ident, $O:ident, $chain:ident, $result:ident)),+) => {
+        #[async_trait]
+        impl<I, $($C, $O),+> Chain<I, ($($O,)+)> for ParallelChain<($($C,)+)>
+        where
+            I: InputCtor,
+            for<'any> I::Target<'any>: Clone,
+            $(
+                $O: Ctor,
+                $C: Chain<I, $O>,
+            )+
+        {
+            async fn call<'a>(
+                &self,
+                input: I::Target<'a>,
+            ) -> Result<WithUsage<($($O::Target<'a>,)+)>, ChainError> {
+                let ($($chain,)+) = &self.0;
+                let ($($result,)+) = futures::try_join!($($chain.call(input.clone())),+)?;
+
+                let usage = TokenUsage::merge_options([$(&$result.usage),+]);
+                Ok(($($result.content,)+).with_usage(usage))
+            }
+
+            async fn call_with_trace<'a>(
+                &self,
+                input: I::Target<'a>,
+            ) -> Result<OutputTrace<($($O::Target<'a>,)+)>, ChainError> {
+                let ($($chain,)+) = &self.0;
+                let ($($result,)+) =
+                    futures::try_join!($($chain.call_with_trace(input.clone())),+)?;
+
+                let previous_steps = std::iter::empty()
+                    $(.chain($result.previous_steps))+
+                    .collect();
+                let usage = TokenUsage::merge_options([$(&$result.final_step.usage),+]);
+                let final_step = ($($result.final_step.content,)+).with_usage(usage);
+
+                Ok(OutputTrace::new(previous_steps, final_step))
+            }
+        }
+    };
+}
+
+impl_parallel_chain!((C1, O1, chain1, result1), (C2, O2, chain2, result2));
+impl_parallel_chain!(
+    (C1, O1, chain1, result1),
+    (C2, O2, chain2, result2),
+    (C3, O3, chain3, result3)
+);
+impl_parallel_chain!(
+    (C1, O1, chain1, result1),
+    (C2, O2, chain2, result2),
+    (C3, O3, chain3, result3),
+    (C4, O4, chain4, result4)
+);
+impl_parallel_chain!(
+    (C1, O1, chain1, result1),
+    (C2, O2, chain2, result2),
+    (C3, O3, chain3, result3),
+    (C4, O4, chain4, result4),
+    (C5, O5, chain5, result5)
+);
+
+#[macro_export]
+macro_rules! parallel_chain {
+    ( $( $chain:expr ),+ $(,)? ) => {
+        $crate::chain::ParallelChain(( $( $chain, )+ ))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chain::{DefaultChainInput, DefaultChainInputCtor, StringCtor};
+
+    use super::*;
+
+    struct Prefix(&'static str);
+
+    #[async_trait]
+    impl Chain<DefaultChainInputCtor, StringCtor> for Prefix {
+        async fn call<'a>(
+            &self,
+            input: DefaultChainInput<'a>,
+        ) -> Result<WithUsage<String>, ChainError> {
+            Ok(format!("{}{input}", self.0).with_usage(Some(TokenUsage::new(1, 2))))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parallel_chain() {
+        let chain = parallel_chain!(Prefix("a: "), Prefix("b: "));
+        let result = chain.call(DefaultChainInput::new("input")).await.unwrap();
+
+        assert_eq!(result.content, ("a: input".into(), "b: input".into()));
+        assert_eq!(result.usage.unwrap().total_tokens, 6);
+    }
+
+    #[tokio::test]
+    async fn test_parallel_chain_trace() {
+        let chain = ParallelChain::new((Prefix("a: "), Prefix("b: "), Prefix("c: ")));
+        let trace = chain
+            .call_with_trace(DefaultChainInput::new("input"))
+            .await
+            .unwrap();
+
+        assert_eq!(trace.final_step.content.2, "c: input");
+        assert_eq!(trace.total_usage.unwrap().total_tokens, 9);
+    }
+}
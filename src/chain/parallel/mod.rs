@@ -0,0 +1,2 @@
+mod chain;
+pub use chain::*;
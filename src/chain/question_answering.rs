@@ -93,8 +93,8 @@ impl<I: InputCtor> Chain<I, StringCtor> for CondenseQuestionGeneratorChain<I> {
 
 #[derive(Clone, Ctor)]
 pub struct StuffQA<'a> {
-    input_documents: Cow<'a, [Document]>,
-    question: Cow<'a, str>,
+    pub(crate) input_documents: Cow<'a, [Document]>,
+    pub(crate) question: Cow<'a, str>,
 }
 
 impl<'a> StuffQA<'a> {
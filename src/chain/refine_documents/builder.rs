@@ -0,0 +1,46 @@
+use crate::{
+    chain::{Chain, StringCtor, StuffQACtor},
+    schemas::BuilderError,
+};
+
+use super::{RefineDocumentsChain, RefineQACtor};
+
+pub struct RefineDocumentsChainBuilder {
+    initial_chain: Option<Box<dyn Chain<StuffQACtor, StringCtor>>>,
+    refine_chain: Option<Box<dyn Chain<RefineQACtor, StringCtor>>>,
+}
+
+impl RefineDocumentsChainBuilder {
+    pub(super) fn new() -> Self {
+        Self {
+            initial_chain: None,
+            refine_chain: None,
+        }
+    }
+
+    /// The chain producing the first answer from the first document.
+    pub fn initial_chain(mut self, chain: impl Chain<StuffQACtor, StringCtor> + 'static) -> Self {
+        self.initial_chain = Some(Box::new(chain));
+        self
+    }
+
+    /// The chain refining the existing answer with each of the following documents.
+    pub fn refine_chain(mut self, chain: impl Chain<RefineQACtor, StringCtor> + 'static) -> Self {
+        self.refine_chain = Some(Box::new(chain));
+        self
+    }
+
+    pub fn build(self) -> Result<RefineDocumentsChain, BuilderError> {
+        let initial_chain = self
+            .initial_chain
+            .ok_or(BuilderError::MissingField("initial_chain"))?;
+        let refine_chain = self
+            .refine_chain
+            .ok_or(BuilderError::MissingField("refine_chain"))?;
+
+        Ok(RefineDocumentsChain {
+            initial_chain,
+            refine_chain,
+        })
+    }
+}
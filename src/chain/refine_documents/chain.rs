@@ -0,0 +1,251 @@
+use std::{borrow::Cow, pin::Pin};
+
+use async_trait::async_trait;
+use futures::{Stream, TryStreamExt};
+use serde_json::Value;
+
+use crate::{
+    chain::{Chain, ChainError, ChainInput, Ctor, LLMChain, StringCtor, StuffQA, StuffQACtor},
+    document_loaders::LoaderError,
+    llm::LLM,
+    schemas::{Document, IntoWithUsage, MessageType, OutputTrace, StreamData, WithUsage},
+    template::MessageTemplate,
+};
+
+use super::{
+    RefineDocumentsChainBuilder, DEFAULT_INITIAL_QA_TEMPLATE, DEFAULT_INITIAL_SUMMARIZE_TEMPLATE,
+    DEFAULT_REFINE_QA_TEMPLATE, DEFAULT_REFINE_SUMMARIZE_TEMPLATE,
+};
+
+/// Input of the refine step of a [`RefineDocumentsChain`].
+#[derive(Clone, ChainInput, Ctor)]
+pub struct RefineQA<'a> {
+    #[langchain(into = "text")]
+    context: Cow<'a, str>,
+    #[langchain(into = "text")]
+    question: Cow<'a, str>,
+    #[langchain(into = "text")]
+    existing_answer: Cow<'a, str>,
+}
+
+impl<'a> RefineQA<'a> {
+    pub fn new() -> Self {
+        Self {
+            context: "".into(),
+            question: "".into(),
+            existing_answer: "".into(),
+        }
+    }
+
+    pub fn context(mut self, context: impl Into<Cow<'a, str>>) -> Self {
+        self.context = context.into();
+        self
+    }
+
+    pub fn question(mut self, question: impl Into<Cow<'a, str>>) -> Self {
+        self.question = question.into();
+        self
+    }
+
+    pub fn existing_answer(mut self, existing_answer: impl Into<Cow<'a, str>>) -> Self {
+        self.existing_answer = existing_answer.into();
+        self
+    }
+}
+
+impl Default for RefineQA<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Combines documents by answering with the first document, then asking `refine_chain` to
+/// refine the existing answer with every following document, one at a time.
+pub struct RefineDocumentsChain {
+    pub(super) initial_chain: Box<dyn Chain<StuffQACtor, StringCtor>>,
+    pub(super) refine_chain: Box<dyn Chain<RefineQACtor, StringCtor>>,
+}
+
+impl RefineDocumentsChain {
+    pub fn builder() -> RefineDocumentsChainBuilder {
+        RefineDocumentsChainBuilder::new()
+    }
+
+    /// Returns a refine chain with prompts designed for question answering.
+    pub fn load_refine_qa<L: Into<Box<dyn LLM>> + Clone>(llm: L) -> Self {
+        Self::from_templates(llm, DEFAULT_INITIAL_QA_TEMPLATE, DEFAULT_REFINE_QA_TEMPLATE)
+    }
+
+    /// Returns a refine chain with prompts designed for summarization. The question of the
+    /// input is ignored.
+    pub fn load_refine_summarize<L: Into<Box<dyn LLM>> + Clone>(llm: L) -> Self {
+        Self::from_templates(
+            llm,
+            DEFAULT_INITIAL_SUMMARIZE_TEMPLATE,
+            DEFAULT_REFINE_SUMMARIZE_TEMPLATE,
+        )
+    }
+
+    fn from_templates<L: Into<Box<dyn LLM>> + Clone>(
+        llm: L,
+        initial_template: &str,
+        refine_template: &str,
+    ) -> Self {
+        let initial_chain = LLMChain::builder()
+            .prompt(MessageTemplate::from_jinja2(
+                MessageType::System,
+                initial_template,
+            ))
+            .llm(llm.clone())
+            .build()
+            .unwrap(); // Safe to unwrap, the prompt and the LLM are set.
+        let refine_chain = LLMChain::builder()
+            .prompt(MessageTemplate::from_jinja2(
+                MessageType::System,
+                refine_template,
+            ))
+            .llm(llm)
+            .build()
+            .unwrap(); // Safe to unwrap, the prompt and the LLM are set.
+
+        Self::builder()
+            .initial_chain(initial_chain)
+            .refine_chain(refine_chain)
+            .build()
+            .unwrap() // Safe to unwrap, the initial and refine chains are set.
+    }
+
+    /// Collects the documents of a stream, such as the one returned by
+    /// [`Loader::load`](crate::document_loaders::Loader::load), and combines them.
+    pub async fn call_with_documents_stream<S>(
+        &self,
+        documents: S,
+        question: &str,
+    ) -> Result<WithUsage<String>, ChainError>
+    where
+        S: Stream<Item = Result<Document, LoaderError>> + Send,
+    {
+        let documents: Vec<Document> = documents.try_collect().await?;
+        let input = StuffQA::new().documents(documents).question(question);
+        self.call(input).await
+    }
+
+    /// Answers with the first document and refines the answer with every following one.
+    async fn refine(
+        &self,
+        documents: &[Document],
+        question: &str,
+    ) -> Result<OutputTrace<String>, ChainError> {
+        let (first, rest) = documents.split_first().unzip();
+        let initial_input = StuffQA::new()
+            .documents(first.map(std::slice::from_ref).unwrap_or_default())
+            .question(question);
+        let mut answer = self.initial_chain.call(initial_input).await?;
+
+        let mut steps = Vec::new();
+        for document in rest.unwrap_or_default() {
+            let refine_input = RefineQA::new()
+                .context(document.page_content.as_str())
+                .question(question)
+                .existing_answer(answer.content.as_str());
+            let refined = self.refine_chain.call(refine_input).await?;
+            steps.push(Value::String(answer.content).with_usage(answer.usage));
+            answer = refined;
+        }
+
+        Ok(OutputTrace::new(steps, answer))
+    }
+}
+
+#[async_trait]
+impl Chain<StuffQACtor, StringCtor> for RefineDocumentsChain {
+    async fn call<'a>(&self, input: StuffQA<'a>) -> Result<WithUsage<String>, ChainError> {
+        let trace = self.call_with_trace(input).await?;
+        Ok(trace.final_step.content.with_usage(trace.total_usage))
+    }
+
+    async fn call_with_trace<'a>(
+        &self,
+        input: StuffQA<'a>,
+    ) -> Result<OutputTrace<String>, ChainError> {
+        self.refine(&input.input_documents, &input.question).await
+    }
+
+    async fn stream(
+        &self,
+        input: StuffQA<'_>,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamData, ChainError>> + Send>>, ChainError>
+    {
+        // Only the last step is streamed, the previous ones are needed to build its input.
+        match input.input_documents.split_last() {
+            Some((last, previous)) if !previous.is_empty() => {
+                let answer = self.refine(previous, &input.question).await?;
+                let refine_input = RefineQA::new()
+                    .context(last.page_content.as_str())
+                    .question(input.question.as_ref())
+                    .existing_answer(answer.final_step.content);
+                self.refine_chain.stream(refine_input).await
+            }
+            _ => self.initial_chain.stream(input).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schemas::TokenUsage;
+
+    use super::*;
+
+    struct Initial;
+
+    #[async_trait]
+    impl Chain<StuffQACtor, StringCtor> for Initial {
+        async fn call<'a>(&self, input: StuffQA<'a>) -> Result<WithUsage<String>, ChainError> {
+            let context = &input.text_replacements()["context"];
+            Ok(format!("init:{context}").with_usage(Some(TokenUsage::new(1, 1))))
+        }
+    }
+
+    struct Refine;
+
+    #[async_trait]
+    impl Chain<RefineQACtor, StringCtor> for Refine {
+        async fn call<'a>(&self, input: RefineQA<'a>) -> Result<WithUsage<String>, ChainError> {
+            let answer = format!("{}+{}", input.existing_answer, input.context);
+            Ok(answer.with_usage(Some(TokenUsage::new(1, 1))))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refine_documents() {
+        let chain = RefineDocumentsChain::builder()
+            .initial_chain(Initial)
+            .refine_chain(Refine)
+            .build()
+            .unwrap();
+        let documents = ["a", "b", "c"].map(Document::new);
+
+        let result = chain
+            .call_with_trace(StuffQA::new().documents(&documents))
+            .await
+            .unwrap();
+
+        assert_eq!(result.final_step.content, "init:a+b+c");
+        assert_eq!(result.previous_steps.len(), 2);
+        assert_eq!(result.total_usage.unwrap().total_tokens, 6);
+    }
+
+    #[tokio::test]
+    async fn test_refine_no_documents() {
+        let chain = RefineDocumentsChain::builder()
+            .initial_chain(Initial)
+            .refine_chain(Refine)
+            .build()
+            .unwrap();
+
+        let result = chain.call(StuffQA::new()).await.unwrap();
+
+        assert_eq!(result.content, "init:");
+    }
+}
@@ -0,0 +1,27 @@
+pub const DEFAULT_INITIAL_QA_TEMPLATE: &str = r#"Context information is below.
+
+{{context}}
+
+Given the context information and not prior knowledge, answer the question: {{question}}"#;
+
+pub const DEFAULT_REFINE_QA_TEMPLATE: &str = r#"The original question is as follows: {{question}}
+We have provided an existing answer: {{existing_answer}}
+We have the opportunity to refine the existing answer (only if needed) with some more context below.
+
+{{context}}
+
+Given the new context, refine the original answer to better answer the question. If the context isn't useful, return the original answer."#;
+
+pub const DEFAULT_INITIAL_SUMMARIZE_TEMPLATE: &str = r#"Write a concise summary of the following:
+
+{{context}}
+
+CONCISE SUMMARY:"#;
+
+pub const DEFAULT_REFINE_SUMMARIZE_TEMPLATE: &str = r#"Your job is to produce a final summary.
+We have provided an existing summary up to a certain point: {{existing_answer}}
+We have the opportunity to refine the existing summary (only if needed) with some more context below.
+
+{{context}}
+
+Given the new context, refine the original summary. If the context isn't useful, return the original summary."#;
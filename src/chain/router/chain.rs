@@ -0,0 +1,132 @@
+use std::{collections::HashMap, pin::Pin};
+
+use async_trait::async_trait;
+use futures::Stream;
+
+use crate::{
+    chain::{Chain, ChainError, InputCtor, OutputCtor},
+    schemas::{OutputTrace, StreamData, WithUsage},
+};
+
+use super::RouteSelector;
+
+/// Dispatches each input to one of several chains, as decided by a [`RouteSelector`].
+///
+/// If the selector returns no route, or a route that has no chain registered, the default chain
+/// is used. Without a default chain, a [`ChainError::RouterError`] is returned instead.
+///
+/// # Example
+/// ```rust,ignore
+/// let selector = PredicateSelector::<DefaultChainInputCtor>::new()
+///     .with_predicate("math", |input| input.to_string().contains("calculate"));
+///
+/// let chain = RouterChain::new(selector)
+///     .with_route("math", math_chain)
+///     .with_default(general_chain);
+/// ```
+pub struct RouterChain<'a, I: InputCtor, O: OutputCtor> {
+    selector: Box<dyn RouteSelector<I> + 'a>,
+    routes: HashMap<String, Box<dyn Chain<I, O> + 'a>>,
+    default_chain: Option<Box<dyn Chain<I, O> + 'a>>,
+}
+
+impl<'a, I: InputCtor, O: OutputCtor> RouterChain<'a, I, O> {
+    pub fn new(selector: impl RouteSelector<I> + 'a) -> Self {
+        Self {
+            selector: Box::new(selector),
+            routes: HashMap::new(),
+            default_chain: None,
+        }
+    }
+
+    /// Registers the chain handling the route named `route`.
+    pub fn with_route(mut self, route: impl Into<String>, chain: impl Chain<I, O> + 'a) -> Self {
+        self.routes.insert(route.into(), Box::new(chain));
+        self
+    }
+
+    /// Sets the chain used when no registered route is selected.
+    pub fn with_default(mut self, chain: impl Chain<I, O> + 'a) -> Self {
+        self.default_chain = Some(Box::new(chain));
+        self
+    }
+
+    async fn route(&self, input: &I::Target<'_>) -> Result<&(dyn Chain<I, O> + 'a), ChainError> {
+        let route = self.selector.select(input).await?;
+        log::debug!("Router selected route: {route:?}");
+
+        route
+            .and_then(|route| self.routes.get(&route))
+            .or(self.default_chain.as_ref())
+            .map(|chain| chain.as_ref())
+            .ok_or_else(|| ChainError::RouterError("No route matched the input".into()))
+    }
+}
+
+#[async_trait]
+impl<I: InputCtor, O: OutputCtor> Chain<I, O> for RouterChain<'_, I, O> {
+    async fn call<'a>(&self, input: I::Target<'a>) -> Result<WithUsage<O::Target<'a>>, ChainError> {
+        self.route(&input).await?.call(input).await
+    }
+
+    async fn call_with_trace<'a>(
+        &self,
+        input: I::Target<'a>,
+    ) -> Result<OutputTrace<O::Target<'a>>, ChainError> {
+        self.route(&input).await?.call_with_trace(input).await
+    }
+
+    async fn stream(
+        &self,
+        input: I::Target<'_>,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamData, ChainError>> + Send>>, ChainError>
+    {
+        self.route(&input).await?.stream(input).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        chain::{DefaultChainInput, DefaultChainInputCtor, PredicateSelector, StringCtor},
+        schemas::IntoWithUsage,
+    };
+
+    use super::*;
+
+    struct Constant(&'static str);
+
+    #[async_trait]
+    impl Chain<DefaultChainInputCtor, StringCtor> for Constant {
+        async fn call<'a>(
+            &self,
+            _input: DefaultChainInput<'a>,
+        ) -> Result<WithUsage<String>, ChainError> {
+            Ok(self.0.to_string().with_usage(None))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_router_chain() {
+        let selector = PredicateSelector::<DefaultChainInputCtor>::new()
+            .with_predicate("math", |input| input.to_string().contains('+'));
+        let chain = RouterChain::new(selector)
+            .with_route("math", Constant("math"))
+            .with_default(Constant("default"));
+
+        let math = chain.call(DefaultChainInput::new("1 + 1")).await.unwrap();
+        assert_eq!(math.content, "math");
+
+        let other = chain.call(DefaultChainInput::new("hello")).await.unwrap();
+        assert_eq!(other.content, "default");
+    }
+
+    #[tokio::test]
+    async fn test_router_chain_without_default() {
+        let selector = PredicateSelector::<DefaultChainInputCtor>::new();
+        let chain: RouterChain<_, StringCtor> = RouterChain::new(selector);
+
+        let result = chain.call(DefaultChainInput::new("hello")).await;
+        assert!(matches!(result, Err(ChainError::RouterError(_))));
+    }
+}
@@ -0,0 +1,5 @@
+mod chain;
+pub use chain::*;
+
+mod selector;
+pub use selector::*;
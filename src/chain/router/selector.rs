@@ -0,0 +1,80 @@
+use std::fmt::Display;
+
+use async_trait::async_trait;
+
+use crate::{
+    chain::{ChainError, InputCtor},
+    semantic_router::RouteLayer,
+};
+
+/// Decides which route of a [`RouterChain`](crate::chain::RouterChain) handles an input.
+#[async_trait]
+pub trait RouteSelector<I: InputCtor>: Send + Sync {
+    /// Returns the name of the selected route, or `None` if no route matches.
+    async fn select(&self, input: &I::Target<'_>) -> Result<Option<String>, ChainError>;
+}
+
+type Predicate<I> = Box<dyn for<'any> Fn(&<I as InputCtor>::Target<'any>) -> bool + Send + Sync>;
+
+/// Selects the first route whose predicate returns `true`, in insertion order.
+pub struct PredicateSelector<I: InputCtor> {
+    predicates: Vec<(String, Predicate<I>)>,
+}
+
+impl<I: InputCtor> PredicateSelector<I> {
+    pub fn new() -> Self {
+        Self {
+            predicates: Vec::new(),
+        }
+    }
+
+    pub fn with_predicate<F>(mut self, route: impl Into<String>, predicate: F) -> Self
+    where
+        F: for<'any> Fn(&I::Target<'any>) -> bool + Send + Sync + 'static,
+    {
+        self.predicates.push((route.into(), Box::new(predicate)));
+        self
+    }
+}
+
+impl<I: InputCtor> Default for PredicateSelector<I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl<I: InputCtor> RouteSelector<I> for PredicateSelector<I> {
+    async fn select(&self, input: &I::Target<'_>) -> Result<Option<String>, ChainError> {
+        let route = self
+            .predicates
+            .iter()
+            .find(|(_, predicate)| predicate(input))
+            .map(|(route, _)| route.clone());
+        Ok(route)
+    }
+}
+
+/// Selects the route chosen by the [`RouteLayer`] for the input's string representation.
+///
+/// Only the embedding lookup is performed, tool inputs are never generated.
+#[async_trait]
+impl<I: InputCtor> RouteSelector<I> for RouteLayer
+where
+    for<'any> I::Target<'any>: Display,
+{
+    async fn select(&self, input: &I::Target<'_>) -> Result<Option<String>, ChainError> {
+        let query = input.to_string();
+        let embedding = self
+            .embedder
+            .embed_query(&query)
+            .await
+            .map_err(|e| ChainError::RouterError(e.to_string()))?;
+        let choice = self
+            .call_embedding(&embedding)
+            .await
+            .map_err(|e| ChainError::RouterError(e.to_string()))?;
+
+        Ok(choice.map(|choice| choice.route))
+    }
+}
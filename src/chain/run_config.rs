@@ -0,0 +1,227 @@
+use std::{collections::HashMap, fmt, future::Future, sync::Arc, time::Duration};
+
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{callbacks::CallbackHandler, chain::ChainError, llm::options::CallOptions};
+
+pub use tokio_util::sync::CancellationToken;
+
+tokio::task_local! {
+    static RUN_CONFIG: RunConfig;
+}
+
+/// Configuration of a single run, given to [`Chain::call_with_config`](crate::chain::Chain::call_with_config).
+///
+/// The config is visible to every chain, agent and LLM called during the run through
+/// [`RunConfig::current`], so that the same chain can be called with different options per
+/// request without mutating it. Configs of nested runs are merged into the config of the
+/// enclosing run.
+#[derive(Clone, Default)]
+pub struct RunConfig {
+    /// Overrides of the call options of every LLM called during the run.
+    pub call_options: Option<CallOptions>,
+    pub tags: Vec<String>,
+    pub metadata: HashMap<String, Value>,
+    pub run_id: Option<Uuid>,
+    /// The id of the enclosing run, set when the config is merged into it.
+    pub parent_run_id: Option<Uuid>,
+    /// Handlers receiving the events of the run, in addition to the global handlers.
+    pub callbacks: Vec<Arc<dyn CallbackHandler>>,
+    pub timeout: Option<Duration>,
+    pub cancellation_token: Option<CancellationToken>,
+}
+
+impl RunConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_call_options(mut self, call_options: CallOptions) -> Self {
+        self.call_options = Some(call_options);
+        self
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    pub fn with_tags<S: Into<String>>(mut self, tags: impl IntoIterator<Item = S>) -> Self {
+        self.tags.extend(tags.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn with_run_id(mut self, run_id: Uuid) -> Self {
+        self.run_id = Some(run_id);
+        self
+    }
+
+    pub fn with_callback(mut self, handler: Arc<dyn CallbackHandler>) -> Self {
+        self.callbacks.push(handler);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_cancellation_token(mut self, cancellation_token: CancellationToken) -> Self {
+        self.cancellation_token = Some(cancellation_token);
+        self
+    }
+
+    /// Returns the config of the run the current task is part of, if any.
+    pub fn current() -> Option<RunConfig> {
+        RUN_CONFIG.try_with(Clone::clone).ok()
+    }
+
+    /// Merges the config of a nested run into this one. Call options and metadata of the nested
+    /// run take precedence, tags are accumulated.
+    fn merge(mut self, nested: RunConfig) -> Self {
+        self.call_options = match (self.call_options, nested.call_options) {
+            (Some(mut options), Some(nested)) => {
+                options.override_options(nested);
+                Some(options)
+            }
+            (options, nested) => nested.or(options),
+        };
+        for tag in nested.tags {
+            if !self.tags.contains(&tag) {
+                self.tags.push(tag);
+            }
+        }
+        self.metadata.extend(nested.metadata);
+        if nested.run_id.is_some() {
+            self.parent_run_id = self.run_id;
+            self.run_id = nested.run_id;
+        }
+        self.callbacks.extend(nested.callbacks);
+        // The timeout of the enclosing run is already enforced around the nested one.
+        self.timeout = nested.timeout;
+        self.cancellation_token = nested.cancellation_token.or(self.cancellation_token);
+        self
+    }
+
+    /// Runs `future` with this config, merged into the config of the enclosing run if any,
+    /// enforcing its timeout and cancellation token.
+    pub async fn scope<T, F>(self, future: F) -> Result<T, ChainError>
+    where
+        F: Future<Output = Result<T, ChainError>>,
+    {
+        let config = match Self::current() {
+            Some(parent) => parent.merge(self),
+            None => self,
+        };
+        let timeout = config.timeout;
+        let cancellation_token = config.cancellation_token.clone();
+
+        let run = async move {
+            match timeout {
+                Some(timeout) => tokio::time::timeout(timeout, future)
+                    .await
+                    .map_err(|_| ChainError::Timeout(timeout))?,
+                None => future.await,
+            }
+        };
+        let run = async move {
+            match cancellation_token {
+                Some(token) => tokio::select! {
+                    _ = token.cancelled() => Err(ChainError::Cancelled),
+                    result = run => result,
+                },
+                None => run.await,
+            }
+        };
+
+        RUN_CONFIG.scope(config, run).await
+    }
+}
+
+impl fmt::Debug for RunConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RunConfig")
+            .field("call_options", &self.call_options)
+            .field("tags", &self.tags)
+            .field("metadata", &self.metadata)
+            .field("run_id", &self.run_id)
+            .field("parent_run_id", &self.parent_run_id)
+            .field("callbacks", &self.callbacks.len())
+            .field("timeout", &self.timeout)
+            .field("cancellation_token", &self.cancellation_token)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_nested_configs_are_merged() {
+        let outer = RunConfig::new()
+            .with_tag("outer")
+            .with_metadata("user", "luis")
+            .with_call_options(
+                CallOptions::new()
+                    .with_temperature(0.1)
+                    .with_max_tokens(10)
+                    .with_stop_words(vec!["END".into()]),
+            );
+        let inner = RunConfig::new().with_tag("inner").with_call_options(
+            CallOptions::new()
+                .with_temperature(0.9)
+                .with_stop_words(vec!["STOP".into()]),
+        );
+
+        let current = outer
+            .scope(async { inner.scope(async { Ok(RunConfig::current()) }).await })
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(current.tags, vec!["outer", "inner"]);
+        assert_eq!(current.metadata["user"], "luis");
+        let options = current.call_options.unwrap();
+        assert_eq!(options.temperature, Some(0.9));
+        assert_eq!(options.max_tokens, Some(10));
+        assert_eq!(options.stop_words, Some(vec!["STOP".to_string()]));
+        assert!(RunConfig::current().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        let config = RunConfig::new().with_timeout(Duration::from_millis(10));
+
+        let result = config
+            .scope(async {
+                tokio::time::sleep(Duration::from_secs(10)).await;
+                Ok(())
+            })
+            .await;
+
+        assert!(matches!(result, Err(ChainError::Timeout(_))));
+    }
+
+    #[tokio::test]
+    async fn test_cancellation() {
+        let token = CancellationToken::new();
+        let config = RunConfig::new().with_cancellation_token(token.clone());
+        token.cancel();
+
+        let result = config
+            .scope(async {
+                tokio::time::sleep(Duration::from_secs(10)).await;
+                Ok(())
+            })
+            .await;
+
+        assert!(matches!(result, Err(ChainError::Cancelled)));
+    }
+}
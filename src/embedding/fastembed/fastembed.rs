@@ -1,22 +1,44 @@
+use std::sync::{Arc, Mutex};
+
 use async_trait::async_trait;
 
-use crate::embedding::{Embedder, EmbedderError};
+use crate::{
+    embedding::{Embedder, EmbedderError},
+    telemetry,
+};
 use fastembed::TextEmbedding;
 
 pub struct FastEmbed {
-    model: TextEmbedding,
+    model: Arc<Mutex<TextEmbedding>>,
     batch_size: Option<usize>,
 }
 
 impl FastEmbed {
     pub fn try_new() -> Result<Self, EmbedderError> {
         Ok(Self {
-            model: TextEmbedding::try_new(Default::default())
-                .map_err(|e| EmbedderError::FastEmbedError(e.to_string()))?,
+            model: Arc::new(Mutex::new(
+                TextEmbedding::try_new(Default::default())
+                    .map_err(|e| EmbedderError::FastEmbedError(e.to_string()))?,
+            )),
             batch_size: None,
         })
     }
 
+    /// Embeds `texts` outside of the async executor, the inference being blocking.
+    async fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, EmbedderError> {
+        let model = self.model.clone();
+        let batch_size = self.batch_size;
+        tokio::task::spawn_blocking(move || {
+            model
+                .lock()
+                .map_err(|e| EmbedderError::FastEmbedError(e.to_string()))?
+                .embed(texts, batch_size)
+                .map_err(|e| EmbedderError::FastEmbedError(e.to_string()))
+        })
+        .await
+        .map_err(|e| EmbedderError::FastEmbedError(e.to_string()))?
+    }
+
     pub fn with_batch_size(mut self, batch_size: usize) -> Self {
         self.batch_size = Some(batch_size);
         self
@@ -26,7 +48,7 @@ impl FastEmbed {
 impl From<TextEmbedding> for FastEmbed {
     fn from(model: TextEmbedding) -> Self {
         Self {
-            model,
+            model: Arc::new(Mutex::new(model)),
             batch_size: None,
         }
     }
@@ -35,29 +57,31 @@ impl From<TextEmbedding> for FastEmbed {
 #[async_trait]
 impl Embedder for FastEmbed {
     async fn embed_documents(&self, documents: &[String]) -> Result<Vec<Vec<f64>>, EmbedderError> {
-        let embeddings = self
-            .model
-            .embed(documents.to_vec(), self.batch_size)
-            .map_err(|e| EmbedderError::FastEmbedError(e.to_string()))?;
+        let span = telemetry::embeddings_span("fastembed", "fastembed", documents.len());
+        telemetry::in_span(span, async move {
+            let embeddings = self.embed(documents.to_vec()).await?;
 
-        Ok(embeddings
-            .into_iter()
-            .map(|inner_vec| {
-                inner_vec
-                    .into_iter()
-                    .map(|x| x as f64)
-                    .collect::<Vec<f64>>()
-            })
-            .collect::<Vec<Vec<f64>>>())
+            Ok(embeddings
+                .into_iter()
+                .map(|inner_vec| {
+                    inner_vec
+                        .into_iter()
+                        .map(|x| x as f64)
+                        .collect::<Vec<f64>>()
+                })
+                .collect::<Vec<Vec<f64>>>())
+        })
+        .await
     }
 
     async fn embed_query(&self, text: &str) -> Result<Vec<f64>, EmbedderError> {
-        let embedding = self
-            .model
-            .embed(vec![text], self.batch_size)
-            .map_err(|e| EmbedderError::FastEmbedError(e.to_string()))?;
+        let span = telemetry::embeddings_span("fastembed", "fastembed", 1);
+        telemetry::in_span(span, async move {
+            let embedding = self.embed(vec![text.to_string()]).await?;
 
-        Ok(embedding[0].iter().map(|x| *x as f64).collect())
+            Ok(embedding[0].iter().map(|x| *x as f64).collect())
+        })
+        .await
     }
 }
 
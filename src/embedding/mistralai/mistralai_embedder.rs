@@ -1,6 +1,9 @@
 use std::sync::Arc;
 
-use crate::embedding::{embedder_trait::Embedder, EmbedderError};
+use crate::{
+    embedding::{embedder_trait::Embedder, EmbedderError},
+    telemetry,
+};
 use async_trait::async_trait;
 use mistralai_client::v1::{client::Client, constants::EmbedModel};
 
@@ -23,31 +26,40 @@ impl MistralAIEmbedder {
 #[async_trait]
 impl Embedder for MistralAIEmbedder {
     async fn embed_documents(&self, documents: &[String]) -> Result<Vec<Vec<f64>>, EmbedderError> {
-        let response = self
-            .client
-            .embeddings_async(self.model.clone(), documents.into(), None)
-            .await
-            .map_err(EmbedderError::MistralAIApiError)?;
+        let span =
+            telemetry::embeddings_span("mistral_ai", &format!("{:?}", self.model), documents.len());
+        telemetry::in_span(span, async move {
+            let response = self
+                .client
+                .embeddings_async(self.model.clone(), documents.into(), None)
+                .await
+                .map_err(EmbedderError::MistralAIApiError)?;
 
-        Ok(response
-            .data
-            .into_iter()
-            .map(|item| item.embedding.into_iter().map(|x| x as f64).collect())
-            .collect::<Vec<Vec<f64>>>())
+            Ok(response
+                .data
+                .into_iter()
+                .map(|item| item.embedding.into_iter().map(|x| x as f64).collect())
+                .collect::<Vec<Vec<f64>>>())
+        })
+        .await
     }
 
     async fn embed_query(&self, text: &str) -> Result<Vec<f64>, EmbedderError> {
-        let response = self
-            .client
-            .embeddings_async(self.model.clone(), vec![text.to_string()], None)
-            .await
-            .map_err(EmbedderError::MistralAIApiError)?;
+        let span = telemetry::embeddings_span("mistral_ai", &format!("{:?}", self.model), 1);
+        telemetry::in_span(span, async move {
+            let response = self
+                .client
+                .embeddings_async(self.model.clone(), vec![text.to_string()], None)
+                .await
+                .map_err(EmbedderError::MistralAIApiError)?;
 
-        Ok(response.data[0]
-            .embedding
-            .iter()
-            .map(|x| *x as f64)
-            .collect())
+            Ok(response.data[0]
+                .embedding
+                .iter()
+                .map(|x| *x as f64)
+                .collect())
+        })
+        .await
     }
 }
 
@@ -1,6 +1,9 @@
 #![allow(dead_code)]
 
-use crate::embedding::{embedder_trait::Embedder, EmbedderError};
+use crate::{
+    embedding::{embedder_trait::Embedder, EmbedderError},
+    telemetry,
+};
 pub use async_openai::config::{AzureConfig, Config, OpenAIConfig};
 use async_openai::{
     types::{CreateEmbeddingRequestArgs, EmbeddingInput},
@@ -49,42 +52,50 @@ impl Default for OpenAiEmbedder<OpenAIConfig> {
 #[async_trait]
 impl<C: Config + Send + Sync> Embedder for OpenAiEmbedder<C> {
     async fn embed_documents(&self, documents: &[String]) -> Result<Vec<Vec<f64>>, EmbedderError> {
-        let request = CreateEmbeddingRequestArgs::default()
-            .model(&self.model)
-            .input(EmbeddingInput::StringArray(documents.into()))
-            .build()?;
+        let span = telemetry::embeddings_span("openai", &self.model, documents.len());
+        telemetry::in_span(span, async move {
+            let request = CreateEmbeddingRequestArgs::default()
+                .model(&self.model)
+                .input(EmbeddingInput::StringArray(documents.into()))
+                .build()?;
 
-        let response = self.client.embeddings().create(request).await?;
+            let response = self.client.embeddings().create(request).await?;
 
-        let embeddings = response
-            .data
-            .into_iter()
-            .map(|item| item.embedding)
-            .map(|embedding| {
-                embedding
-                    .into_iter()
-                    .map(|x| x as f64)
-                    .collect::<Vec<f64>>()
-            })
-            .collect();
+            let embeddings = response
+                .data
+                .into_iter()
+                .map(|item| item.embedding)
+                .map(|embedding| {
+                    embedding
+                        .into_iter()
+                        .map(|x| x as f64)
+                        .collect::<Vec<f64>>()
+                })
+                .collect();
 
-        Ok(embeddings)
+            Ok(embeddings)
+        })
+        .await
     }
 
     async fn embed_query(&self, text: &str) -> Result<Vec<f64>, EmbedderError> {
-        let request = CreateEmbeddingRequestArgs::default()
-            .model(&self.model)
-            .input(text)
-            .build()?;
+        let span = telemetry::embeddings_span("openai", &self.model, 1);
+        telemetry::in_span(span, async move {
+            let request = CreateEmbeddingRequestArgs::default()
+                .model(&self.model)
+                .input(text)
+                .build()?;
 
-        let mut response = self.client.embeddings().create(request).await?;
+            let mut response = self.client.embeddings().create(request).await?;
 
-        let item = response.data.swap_remove(0);
+            let item = response.data.swap_remove(0);
 
-        Ok(item
-            .embedding
-            .into_iter()
-            .map(|x| x as f64)
-            .collect::<Vec<f64>>())
+            Ok(item
+                .embedding
+                .into_iter()
+                .map(|x| x as f64)
+                .collect::<Vec<f64>>())
+        })
+        .await
     }
 }
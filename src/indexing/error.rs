@@ -0,0 +1,25 @@
+use thiserror::Error;
+
+use crate::document_loaders::LoaderError;
+
+#[derive(Error, Debug)]
+pub enum IndexingError {
+    #[error("Loader error: {0}")]
+    LoaderError(#[from] LoaderError),
+
+    #[error("Vector store error: {0}")]
+    VectorStoreError(String),
+
+    #[error("Serialization error: {0}")]
+    SerdeError(#[from] serde_json::Error),
+
+    #[cfg(any(feature = "postgres", feature = "sqlite"))]
+    #[error("Database error: {0}")]
+    SqlxError(#[from] sqlx::Error),
+
+    #[error("Document without the source id key `{0}`, required by the incremental cleanup")]
+    MissingSourceId(String),
+
+    #[error("Indexing error: {0}")]
+    OtherError(String),
+}
@@ -0,0 +1,385 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use futures::Stream;
+use futures_util::{pin_mut, StreamExt};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::{document_loaders::LoaderError, schemas::Document, vectorstore::VectorStore};
+
+use super::{IndexingError, Record, RecordManager};
+
+/// What to delete from the vector store after indexing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CleanupMode {
+    /// Nothing is deleted.
+    #[default]
+    None,
+    /// The documents of the indexed sources which were not seen are deleted. Every document must
+    /// have a source id.
+    Incremental,
+    /// All the documents which were not seen are deleted, the stream must contain the whole
+    /// dataset.
+    Full,
+}
+
+pub struct IndexOptions<O> {
+    pub cleanup: CleanupMode,
+    /// The metadata key of the source of the documents, `source` by default.
+    pub source_id_key: String,
+    pub batch_size: usize,
+    pub vector_store_options: O,
+}
+
+impl<O> IndexOptions<O> {
+    pub fn new(vector_store_options: O) -> Self {
+        Self {
+            cleanup: CleanupMode::default(),
+            source_id_key: "source".into(),
+            batch_size: 100,
+            vector_store_options,
+        }
+    }
+
+    pub fn with_cleanup(mut self, cleanup: CleanupMode) -> Self {
+        self.cleanup = cleanup;
+        self
+    }
+
+    pub fn with_source_id_key<S: Into<String>>(mut self, source_id_key: S) -> Self {
+        self.source_id_key = source_id_key.into();
+        self
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+}
+
+impl<O: Default> Default for IndexOptions<O> {
+    fn default() -> Self {
+        Self::new(O::default())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IndexingResult {
+    pub num_added: usize,
+    pub num_skipped: usize,
+    pub num_deleted: usize,
+}
+
+/// Returns the hash of the content and the metadata of a document, used as the key of its
+/// [`Record`].
+pub fn hash_document(doc: &Document) -> Result<String, IndexingError> {
+    let metadata: BTreeMap<&String, &Value> = doc.metadata.iter().collect();
+    let mut hasher = Sha256::new();
+    hasher.update(doc.page_content.as_bytes());
+    hasher.update([0]);
+    hasher.update(serde_json::to_vec(&metadata)?);
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as i64)
+}
+
+fn source_id(doc: &Document, source_id_key: &str) -> Option<String> {
+    doc.metadata.get(source_id_key).map(|source| match source {
+        Value::String(source) => source.clone(),
+        source => source.to_string(),
+    })
+}
+
+fn vector_store_error(err: Box<dyn std::error::Error>) -> IndexingError {
+    IndexingError::VectorStoreError(err.to_string())
+}
+
+/// Indexes the documents of a stream, such as the one of a
+/// [`Loader`](crate::document_loaders::Loader), in a vector store.
+///
+/// The documents are identified by the hash of their content and metadata, kept in the
+/// `record_manager` with their id in the vector store. Documents already indexed are skipped,
+/// and the documents no longer in the stream are deleted according to the [`CleanupMode`].
+///
+/// # Usage
+/// ```rust,ignore
+/// let docs = TextLoader::new(text).load().await?;
+/// let options = IndexOptions::default().with_cleanup(CleanupMode::Incremental);
+/// let result = index(docs, &record_manager, &store, &options).await?;
+/// ```
+pub async fn index<S, VS>(
+    docs: S,
+    record_manager: &dyn RecordManager,
+    vector_store: &VS,
+    options: &IndexOptions<VS::Options>,
+) -> Result<IndexingResult, IndexingError>
+where
+    S: Stream<Item = Result<Document, LoaderError>>,
+    VS: VectorStore + ?Sized,
+{
+    let index_start = now();
+    let mut result = IndexingResult::default();
+    let mut sources = HashSet::new();
+    let mut seen_doc_ids = HashSet::new();
+
+    let batches = docs.chunks(options.batch_size.max(1));
+    pin_mut!(batches);
+    while let Some(batch) = batches.next().await {
+        let batch = batch.into_iter().collect::<Result<Vec<_>, _>>()?;
+
+        let mut keys = Vec::with_capacity(batch.len());
+        let mut docs = Vec::with_capacity(batch.len());
+        for doc in batch {
+            let key = hash_document(&doc)?;
+            if keys.contains(&key) {
+                result.num_skipped += 1;
+                continue;
+            }
+            let source = source_id(&doc, &options.source_id_key);
+            if options.cleanup == CleanupMode::Incremental && source.is_none() {
+                return Err(IndexingError::MissingSourceId(
+                    options.source_id_key.clone(),
+                ));
+            }
+            keys.push(key);
+            docs.push((doc, source));
+        }
+
+        let records = record_manager.get(&keys).await?;
+        let mut seen = Vec::new();
+        let mut new_keys = Vec::new();
+        let mut new_docs = Vec::new();
+        for ((key, (doc, source)), record) in keys.into_iter().zip(docs).zip(records) {
+            sources.extend(source.clone());
+            match record {
+                Some(record) => seen.push(Record {
+                    updated_at: index_start,
+                    ..record
+                }),
+                None => {
+                    new_keys.push((key, source));
+                    new_docs.push(doc);
+                }
+            }
+        }
+
+        let doc_ids = if new_docs.is_empty() {
+            Vec::new()
+        } else {
+            vector_store
+                .add_documents(&new_docs, &options.vector_store_options)
+                .await
+                .map_err(vector_store_error)?
+        };
+        if doc_ids.len() != new_docs.len() {
+            return Err(IndexingError::OtherError(
+                "Number of ids and documents do not match".into(),
+            ));
+        }
+
+        result.num_skipped += seen.len();
+        result.num_added += new_docs.len();
+        seen.extend(
+            new_keys
+                .into_iter()
+                .zip(doc_ids)
+                .map(|((key, group_id), doc_id)| Record {
+                    key,
+                    doc_id,
+                    group_id,
+                    updated_at: index_start,
+                }),
+        );
+        seen_doc_ids.extend(seen.iter().map(|r| r.doc_id.clone()));
+        record_manager.update(&seen).await?;
+    }
+
+    let stale = match options.cleanup {
+        CleanupMode::None => return Ok(result),
+        CleanupMode::Incremental => {
+            let sources = sources.into_iter().collect::<Vec<_>>();
+            record_manager
+                .list(Some(index_start), Some(&sources))
+                .await?
+        }
+        CleanupMode::Full => record_manager.list(Some(index_start), None).await?,
+    };
+    if stale.is_empty() {
+        return Ok(result);
+    }
+
+    // A document with a caller-provided id replaces the stored one instead of being added, do
+    // not delete it with the stale record.
+    let doc_ids = stale
+        .iter()
+        .map(|r| r.doc_id.clone())
+        .filter(|id| !seen_doc_ids.contains(id))
+        .collect::<Vec<_>>();
+    if !doc_ids.is_empty() {
+        vector_store
+            .delete(&doc_ids, &options.vector_store_options)
+            .await
+            .map_err(vector_store_error)?;
+    }
+    let keys = stale.into_iter().map(|r| r.key).collect::<Vec<_>>();
+    record_manager.delete(&keys).await?;
+    result.num_deleted = doc_ids.len();
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use futures::stream;
+    use serde_json::json;
+
+    use crate::{
+        indexing::InMemoryRecordManager,
+        vectorstore::{
+            memory::{tests::WordCountEmbedder, InMemoryStore},
+            DistanceMetric,
+        },
+    };
+
+    use super::*;
+
+    fn doc(text: &str, source: &str) -> Document {
+        Document::new(text).with_metadata([("source".to_string(), json!(source))].into())
+    }
+
+    fn docs(docs: Vec<Document>) -> impl Stream<Item = Result<Document, LoaderError>> {
+        stream::iter(docs.into_iter().map(Ok))
+    }
+
+    #[test]
+    fn test_hash_document_ignores_metadata_order() {
+        let a = Document::new("text")
+            .with_metadata([("a".to_string(), json!(1)), ("b".to_string(), json!(2))].into());
+        let b = Document::new("text")
+            .with_metadata([("b".to_string(), json!(2)), ("a".to_string(), json!(1))].into());
+        assert_eq!(hash_document(&a).unwrap(), hash_document(&b).unwrap());
+        assert_ne!(
+            hash_document(&a).unwrap(),
+            hash_document(&Document::new("text")).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_index_incremental() {
+        let store = InMemoryStore::new(Arc::new(WordCountEmbedder), DistanceMetric::Cosine);
+        let record_manager = InMemoryRecordManager::new();
+        let options = IndexOptions::default()
+            .with_cleanup(CleanupMode::Incremental)
+            .with_batch_size(2);
+
+        let first = vec![
+            doc("The cat sleeps", "a.txt"),
+            doc("The dog barks", "a.txt"),
+            doc("The fish swims", "b.txt"),
+        ];
+        let result = index(docs(first.clone()), &record_manager, &store, &options)
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            IndexingResult {
+                num_added: 3,
+                num_skipped: 0,
+                num_deleted: 0
+            }
+        );
+
+        let result = index(docs(first), &record_manager, &store, &options)
+            .await
+            .unwrap();
+        assert_eq!(result.num_skipped, 3);
+        assert_eq!(store.len(None).await, 3);
+
+        // a.txt changed, b.txt is not in the stream and is kept.
+        let second = vec![
+            doc("The cat sleeps", "a.txt"),
+            doc("The bird sings", "a.txt"),
+        ];
+        let result = index(docs(second), &record_manager, &store, &options)
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            IndexingResult {
+                num_added: 1,
+                num_skipped: 1,
+                num_deleted: 1
+            }
+        );
+        assert_eq!(store.len(None).await, 3);
+
+        let result = index(
+            docs(vec![Document::new("The cat sleeps")]),
+            &record_manager,
+            &store,
+            &options,
+        )
+        .await;
+        assert!(matches!(result, Err(IndexingError::MissingSourceId(_))));
+    }
+
+    #[tokio::test]
+    async fn test_index_full() {
+        let store = InMemoryStore::new(Arc::new(WordCountEmbedder), DistanceMetric::Cosine);
+        let record_manager = InMemoryRecordManager::new();
+        let options = IndexOptions::default().with_cleanup(CleanupMode::Full);
+
+        let first = vec![
+            doc("The cat sleeps", "a.txt"),
+            doc("The fish swims", "b.txt"),
+            doc("The fish swims", "b.txt"),
+        ];
+        let result = index(docs(first), &record_manager, &store, &options)
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            IndexingResult {
+                num_added: 2,
+                num_skipped: 1,
+                num_deleted: 0
+            }
+        );
+
+        let result = index(
+            docs(vec![doc("The dog barks", "c.txt")]),
+            &record_manager,
+            &store,
+            &options,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            result,
+            IndexingResult {
+                num_added: 1,
+                num_skipped: 0,
+                num_deleted: 2
+            }
+        );
+        let stored = store
+            .similarity_search("dog", 10, &Default::default())
+            .await
+            .unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].page_content, "The dog barks");
+    }
+}
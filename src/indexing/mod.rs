@@ -0,0 +1,18 @@
+mod error;
+pub use error::*;
+
+mod index;
+pub use index::*;
+
+mod record_manager;
+pub use record_manager::*;
+
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(feature = "sqlite")]
+pub use sqlite::*;
+
+#[cfg(feature = "postgres")]
+mod postgres;
+#[cfg(feature = "postgres")]
+pub use postgres::*;
@@ -0,0 +1,149 @@
+use async_trait::async_trait;
+use indoc::formatdoc;
+use sqlx::{Pool, Postgres};
+
+use super::{IndexingError, Record, RecordManager};
+
+type RecordRow = (String, String, Option<String>, i64);
+
+/// Stores the records of a name space in a postgres table.
+pub struct PostgresRecordManager {
+    pool: Pool<Postgres>,
+    table: String,
+    namespace: String,
+}
+
+impl PostgresRecordManager {
+    /// Creates a record manager for `namespace`, e.g. the vector store and the collection of the
+    /// documents.
+    pub fn new(pool: Pool<Postgres>, namespace: impl Into<String>) -> Self {
+        Self {
+            pool,
+            table: "upsertion_record".into(),
+            namespace: namespace.into(),
+        }
+    }
+
+    pub fn with_table(mut self, table: &str) -> Self {
+        self.table = table.into();
+        self
+    }
+
+    /// Creates the table of the records if it does not exist.
+    pub async fn initialize(&self) -> Result<(), IndexingError> {
+        let table = &self.table;
+        sqlx::query(&formatdoc! {"
+            CREATE TABLE IF NOT EXISTS {table}
+            (
+                namespace TEXT NOT NULL,
+                key TEXT NOT NULL,
+                doc_id TEXT NOT NULL,
+                group_id TEXT,
+                updated_at BIGINT NOT NULL,
+                PRIMARY KEY (namespace, key)
+            )"
+        })
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(&format!(
+            "CREATE INDEX IF NOT EXISTS {table}_updated_at ON {table} (namespace, updated_at)"
+        ))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RecordManager for PostgresRecordManager {
+    async fn get(&self, keys: &[String]) -> Result<Vec<Option<Record>>, IndexingError> {
+        let table = &self.table;
+        let records: Vec<Record> = sqlx::query_as::<_, RecordRow>(&format!(
+            "SELECT key, doc_id, group_id, updated_at FROM {table} WHERE namespace = $1 AND key = ANY($2)"
+        ))
+        .bind(&self.namespace)
+        .bind(keys)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(into_record)
+        .collect();
+
+        Ok(keys
+            .iter()
+            .map(|key| records.iter().find(|r| &r.key == key).cloned())
+            .collect())
+    }
+
+    async fn update(&self, records: &[Record]) -> Result<(), IndexingError> {
+        let table = &self.table;
+        let mut tx = self.pool.begin().await?;
+        for record in records {
+            sqlx::query(&formatdoc! {"
+                INSERT INTO {table} (namespace, key, doc_id, group_id, updated_at)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (namespace, key) DO UPDATE SET
+                    doc_id = EXCLUDED.doc_id,
+                    group_id = EXCLUDED.group_id,
+                    updated_at = EXCLUDED.updated_at"
+            })
+            .bind(&self.namespace)
+            .bind(&record.key)
+            .bind(&record.doc_id)
+            .bind(&record.group_id)
+            .bind(record.updated_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn list(
+        &self,
+        before: Option<i64>,
+        group_ids: Option<&[String]>,
+    ) -> Result<Vec<Record>, IndexingError> {
+        let table = &self.table;
+        let group_filter = match group_ids {
+            Some(_) => "AND group_id = ANY($3)",
+            None => "",
+        };
+        let sql = format!(
+            "SELECT key, doc_id, group_id, updated_at FROM {table} WHERE namespace = $1 AND updated_at < $2 {group_filter}"
+        );
+        let mut query = sqlx::query_as::<_, RecordRow>(&sql)
+            .bind(&self.namespace)
+            .bind(before.unwrap_or(i64::MAX));
+        if let Some(group_ids) = group_ids {
+            query = query.bind(group_ids);
+        }
+        Ok(query
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(into_record)
+            .collect())
+    }
+
+    async fn delete(&self, keys: &[String]) -> Result<(), IndexingError> {
+        let table = &self.table;
+        sqlx::query(&format!(
+            "DELETE FROM {table} WHERE namespace = $1 AND key = ANY($2)"
+        ))
+        .bind(&self.namespace)
+        .bind(keys)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+fn into_record((key, doc_id, group_id, updated_at): RecordRow) -> Record {
+    Record {
+        key,
+        doc_id,
+        group_id,
+        updated_at,
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+
+use super::IndexingError;
+
+/// The record of an indexed document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    /// The hash of the content and the metadata of the document.
+    pub key: String,
+    /// The id of the document in the vector store.
+    pub doc_id: String,
+    /// The source of the document, used by the incremental cleanup.
+    pub group_id: Option<String>,
+    /// The time of the indexing run that last saw the document, in nanoseconds since the epoch.
+    pub updated_at: i64,
+}
+
+/// Keeps track of the documents written to a vector store by [`index`](super::index), so that
+/// unchanged documents are skipped and stale ones deleted.
+///
+/// A record manager should be used with a single vector store (and name space).
+#[async_trait]
+pub trait RecordManager: Send + Sync {
+    /// Returns the records of the given keys, in the order of `keys`.
+    async fn get(&self, keys: &[String]) -> Result<Vec<Option<Record>>, IndexingError>;
+
+    /// Inserts the records, replacing the ones with the same key.
+    async fn update(&self, records: &[Record]) -> Result<(), IndexingError>;
+
+    /// Returns the records updated before `before`, of the given groups only if `group_ids` is
+    /// set.
+    async fn list(
+        &self,
+        before: Option<i64>,
+        group_ids: Option<&[String]>,
+    ) -> Result<Vec<Record>, IndexingError>;
+
+    /// Deletes the records of the given keys. Unknown keys are ignored.
+    async fn delete(&self, keys: &[String]) -> Result<(), IndexingError>;
+}
+
+#[derive(Default)]
+pub struct InMemoryRecordManager {
+    records: RwLock<HashMap<String, Record>>,
+}
+
+impl InMemoryRecordManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RecordManager for InMemoryRecordManager {
+    async fn get(&self, keys: &[String]) -> Result<Vec<Option<Record>>, IndexingError> {
+        let records = self.records.read().await;
+        Ok(keys.iter().map(|key| records.get(key).cloned()).collect())
+    }
+
+    async fn update(&self, records: &[Record]) -> Result<(), IndexingError> {
+        let mut stored = self.records.write().await;
+        for record in records {
+            stored.insert(record.key.clone(), record.clone());
+        }
+        Ok(())
+    }
+
+    async fn list(
+        &self,
+        before: Option<i64>,
+        group_ids: Option<&[String]>,
+    ) -> Result<Vec<Record>, IndexingError> {
+        let records = self.records.read().await;
+        Ok(records
+            .values()
+            .filter(|r| before.is_none_or(|before| r.updated_at < before))
+            .filter(|r| {
+                group_ids.is_none_or(|group_ids| {
+                    r.group_id
+                        .as_ref()
+                        .is_some_and(|group_id| group_ids.contains(group_id))
+                })
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn delete(&self, keys: &[String]) -> Result<(), IndexingError> {
+        let mut records = self.records.write().await;
+        for key in keys {
+            records.remove(key);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,222 @@
+use async_trait::async_trait;
+use indoc::formatdoc;
+use sqlx::{Pool, Sqlite};
+
+use crate::vectorstore::sqlite_rows::placeholders;
+
+use super::{IndexingError, Record, RecordManager};
+
+type RecordRow = (String, String, Option<String>, i64);
+
+/// Stores the records of a name space in a sqlite table.
+pub struct SqliteRecordManager {
+    pool: Pool<Sqlite>,
+    table: String,
+    namespace: String,
+}
+
+impl SqliteRecordManager {
+    /// Creates a record manager for `namespace`, e.g. the vector store and the collection of the
+    /// documents.
+    pub fn new(pool: Pool<Sqlite>, namespace: impl Into<String>) -> Self {
+        Self {
+            pool,
+            table: "upsertion_record".into(),
+            namespace: namespace.into(),
+        }
+    }
+
+    pub fn with_table(mut self, table: &str) -> Self {
+        self.table = table.into();
+        self
+    }
+
+    /// Creates the table of the records if it does not exist.
+    pub async fn initialize(&self) -> Result<(), IndexingError> {
+        let table = &self.table;
+        sqlx::query(&formatdoc! {"
+            CREATE TABLE IF NOT EXISTS {table}
+            (
+                namespace TEXT NOT NULL,
+                key TEXT NOT NULL,
+                doc_id TEXT NOT NULL,
+                group_id TEXT,
+                updated_at INTEGER NOT NULL,
+                PRIMARY KEY (namespace, key)
+            );
+            CREATE INDEX IF NOT EXISTS {table}_updated_at ON {table} (namespace, updated_at);"
+        })
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RecordManager for SqliteRecordManager {
+    async fn get(&self, keys: &[String]) -> Result<Vec<Option<Record>>, IndexingError> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let table = &self.table;
+        let sql = format!(
+            "SELECT key, doc_id, group_id, updated_at FROM {table} WHERE namespace = ? AND key IN ({})",
+            placeholders(keys.len())
+        );
+        let mut query = sqlx::query_as::<_, RecordRow>(&sql).bind(&self.namespace);
+        for key in keys {
+            query = query.bind(key);
+        }
+        let records: Vec<Record> = query
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(into_record)
+            .collect();
+
+        Ok(keys
+            .iter()
+            .map(|key| records.iter().find(|r| &r.key == key).cloned())
+            .collect())
+    }
+
+    async fn update(&self, records: &[Record]) -> Result<(), IndexingError> {
+        let table = &self.table;
+        let mut tx = self.pool.begin().await?;
+        for record in records {
+            sqlx::query(&formatdoc! {"
+                INSERT INTO {table} (namespace, key, doc_id, group_id, updated_at)
+                VALUES (?, ?, ?, ?, ?)
+                ON CONFLICT (namespace, key) DO UPDATE SET
+                    doc_id = excluded.doc_id,
+                    group_id = excluded.group_id,
+                    updated_at = excluded.updated_at"
+            })
+            .bind(&self.namespace)
+            .bind(&record.key)
+            .bind(&record.doc_id)
+            .bind(&record.group_id)
+            .bind(record.updated_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn list(
+        &self,
+        before: Option<i64>,
+        group_ids: Option<&[String]>,
+    ) -> Result<Vec<Record>, IndexingError> {
+        let table = &self.table;
+        let mut sql = format!(
+            "SELECT key, doc_id, group_id, updated_at FROM {table} WHERE namespace = ? AND updated_at < ?"
+        );
+        if let Some(group_ids) = group_ids {
+            if group_ids.is_empty() {
+                return Ok(Vec::new());
+            }
+            sql.push_str(&format!(
+                " AND group_id IN ({})",
+                placeholders(group_ids.len())
+            ));
+        }
+
+        let mut query = sqlx::query_as::<_, RecordRow>(&sql)
+            .bind(&self.namespace)
+            .bind(before.unwrap_or(i64::MAX));
+        for group_id in group_ids.unwrap_or_default() {
+            query = query.bind(group_id);
+        }
+        Ok(query
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(into_record)
+            .collect())
+    }
+
+    async fn delete(&self, keys: &[String]) -> Result<(), IndexingError> {
+        if keys.is_empty() {
+            return Ok(());
+        }
+        let table = &self.table;
+        let sql = format!(
+            "DELETE FROM {table} WHERE namespace = ? AND key IN ({})",
+            placeholders(keys.len())
+        );
+        let mut query = sqlx::query(&sql).bind(&self.namespace);
+        for key in keys {
+            query = query.bind(key);
+        }
+        query.execute(&self.pool).await?;
+        Ok(())
+    }
+}
+
+fn into_record((key, doc_id, group_id, updated_at): RecordRow) -> Record {
+    Record {
+        key,
+        doc_id,
+        group_id,
+        updated_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+
+    fn record(key: &str, group_id: &str, updated_at: i64) -> Record {
+        Record {
+            key: key.into(),
+            doc_id: format!("doc-{key}"),
+            group_id: Some(group_id.into()),
+            updated_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_record_manager() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let manager = SqliteRecordManager::new(pool.clone(), "a");
+        manager.initialize().await.unwrap();
+
+        manager
+            .update(&[
+                record("1", "x", 1),
+                record("2", "y", 1),
+                record("3", "y", 2),
+            ])
+            .await
+            .unwrap();
+        manager.update(&[record("1", "x", 3)]).await.unwrap();
+
+        let records = manager
+            .get(&["3".into(), "4".into(), "1".into()])
+            .await
+            .unwrap();
+        assert_eq!(
+            records,
+            vec![Some(record("3", "y", 2)), None, Some(record("1", "x", 3))]
+        );
+
+        let stale = manager.list(Some(2), None).await.unwrap();
+        assert_eq!(stale, vec![record("2", "y", 1)]);
+        let stale = manager.list(Some(3), Some(&["x".into()])).await.unwrap();
+        assert!(stale.is_empty());
+
+        let other_namespace = SqliteRecordManager::new(pool, "b");
+        assert!(other_namespace.list(None, None).await.unwrap().is_empty());
+
+        manager.delete(&["2".into()]).await.unwrap();
+        assert_eq!(manager.list(None, None).await.unwrap().len(), 2);
+    }
+}
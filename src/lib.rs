@@ -1,11 +1,15 @@
 pub mod agent;
+pub mod callbacks;
 pub mod chain;
 pub mod document_loaders;
 pub mod embedding;
+pub mod indexing;
 pub mod instructor;
 pub mod llm;
 pub mod memory;
 pub mod output_parser;
+pub mod reranker;
+pub mod retrievers;
 pub mod schemas;
 pub mod semantic_router;
 pub mod template;
@@ -13,5 +17,9 @@ pub mod text_splitter;
 pub mod tools;
 pub mod vectorstore;
 pub extern crate url;
+// Re-exported so that the `gen_ai` spans can be exported with matching versions.
+#[cfg(feature = "otel")]
+pub use {opentelemetry, tracing_opentelemetry};
 
+pub(crate) mod telemetry;
 pub(crate) mod utils;
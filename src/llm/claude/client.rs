@@ -1,7 +1,9 @@
 use crate::{
+    callbacks::{trace_llm, trace_llm_stream},
     llm::AnthropicError,
     llm::{options::CallOptions, LLMError, LLMOutput, LLM},
     schemas::{IntoWithUsage, Message, MessageType, StreamData, TokenUsage, WithUsage},
+    telemetry,
 };
 use async_trait::async_trait;
 use futures::{Stream, StreamExt};
@@ -118,6 +120,11 @@ impl Claude {
             completion_tokens: res.usage.output_tokens,
             total_tokens: res.usage.input_tokens + res.usage.output_tokens,
         });
+        telemetry::record_response(
+            &tracing::Span::current(),
+            res.stop_reason.as_deref(),
+            usage.as_ref(),
+        );
 
         Ok(LLMOutput::Text(generation).with_usage(usage))
     }
@@ -126,6 +133,7 @@ impl Claude {
         let (system_message, other_messages): (Vec<_>, Vec<_>) = messages
             .iter()
             .partition(|m| m.message_type == MessageType::System);
+        let options = self.options.with_run_config();
         let mut payload = Payload {
             model: self.model.clone(),
             system: system_message.first().map(|m| m.content.clone()),
@@ -133,46 +141,21 @@ impl Claude {
                 .into_iter()
                 .map(ClaudeMessage::from_message)
                 .collect::<Vec<_>>(),
-            max_tokens: self.options.max_tokens.unwrap_or(1024),
+            max_tokens: options.max_tokens.unwrap_or(1024),
             stream: None,
-            stop_sequences: self.options.stop_words.clone(),
-            temperature: self.options.temperature,
-            top_p: self.options.top_p,
-            top_k: self.options.top_k,
+            stop_sequences: options.stop_words,
+            temperature: options.temperature,
+            top_p: options.top_p,
+            top_k: options.top_k,
         };
         if stream {
             payload.stream = Some(true);
         }
         payload
     }
-}
-
-#[async_trait]
-impl LLM for Claude {
-    async fn generate(&self, messages: Vec<Message>) -> Result<WithUsage<LLMOutput>, LLMError> {
-        match &self.options.stream_option {
-            Some(stream_option) => {
-                let mut complete_response = String::new();
-                let mut usage = None;
-                let mut stream = self.stream(messages).await?;
-                while let Some(data) = stream.next().await {
-                    let data = data?;
-                    usage = TokenUsage::merge_options([&usage, &data.tokens]);
-                    complete_response.push_str(&data.content);
-
-                    if let Some(streaming_func) = &stream_option.streaming_func {
-                        let mut func = streaming_func.lock().await;
-                        let _ = func(&data.content).await;
-                    }
-                }
-
-                Ok(LLMOutput::Text(complete_response).with_usage(usage))
-            }
-            None => self.generate(messages).await,
-        }
-    }
 
-    async fn stream(
+    /// Streams the response to `messages`, without a span of its own.
+    async fn stream_response(
         &self,
         messages: Vec<Message>,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamData, LLMError>> + Send>>, LLMError> {
@@ -211,6 +194,67 @@ impl LLM for Claude {
 
         Ok(Box::pin(processed_stream))
     }
+}
+
+#[async_trait]
+impl LLM for Claude {
+    async fn generate(&self, messages: Vec<Message>) -> Result<WithUsage<LLMOutput>, LLMError> {
+        trace_llm(messages, |messages| async move {
+            let options = self.options.with_run_config();
+            let span = telemetry::chat_span("anthropic", &self.model, &options);
+            telemetry::in_span(span.clone(), async move {
+                match &options.stream_option {
+                    Some(stream_option) => {
+                        let mut complete_response = String::new();
+                        let mut usage = None;
+                        let mut stream = self.stream_response(messages).await?;
+                        while let Some(data) = stream.next().await {
+                            let data = data?;
+                            usage = TokenUsage::merge_options([&usage, &data.tokens]);
+                            complete_response.push_str(&data.content);
+
+                            if let Some(streaming_func) = &stream_option.streaming_func {
+                                let mut func = streaming_func.lock().await;
+                                let _ = func(&data.content).await;
+                            }
+                        }
+                        telemetry::record_response(&span, None, usage.as_ref());
+
+                        Ok(LLMOutput::Text(complete_response).with_usage(usage))
+                    }
+                    None => self.generate(messages).await,
+                }
+            })
+            .await
+        })
+        .await
+    }
+
+    async fn stream(
+        &self,
+        messages: Vec<Message>,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamData, LLMError>> + Send>>, LLMError> {
+        trace_llm_stream(messages, |messages| async move {
+            let options = self.options.with_run_config();
+            let span = telemetry::chat_span("anthropic", &self.model, &options);
+            let stream = telemetry::in_span(span.clone(), self.stream_response(messages)).await?;
+
+            let response_span = span.clone();
+            let stream = stream.inspect(move |data| {
+                if let Some(stop_reason) = data
+                    .as_ref()
+                    .ok()
+                    .and_then(|data| data.value.pointer("/delta/stop_reason"))
+                    .and_then(Value::as_str)
+                {
+                    telemetry::record_response(&response_span, Some(stop_reason), None);
+                }
+            });
+
+            Ok(Box::pin(telemetry::stream_in_span(span, stream)))
+        })
+        .await
+    }
 
     fn add_call_options(&mut self, call_options: CallOptions) {
         self.options.merge_options(call_options)
@@ -1,30 +1,73 @@
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
-use crate::schemas::{Message, MessageType};
+use crate::schemas::{ContentPart, MediaSource, Message, MessageType, PartContent};
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum ClaudeContent {
+    Text(String),
+    Blocks(Vec<Value>),
+}
 
 #[derive(Serialize, Deserialize)]
 pub(crate) struct ClaudeMessage {
     pub role: String,
-    pub content: String,
+    pub content: ClaudeContent,
 }
 impl ClaudeMessage {
     pub fn new<S: Into<String>>(role: S, content: S) -> Self {
         Self {
             role: role.into(),
-            content: content.into(),
+            content: ClaudeContent::Text(content.into()),
         }
     }
 
     pub fn from_message(message: &Message) -> Self {
-        match message.message_type {
-            MessageType::System => Self::new("system", &message.content),
-            MessageType::Ai => Self::new("assistant", &message.content),
-            MessageType::Human => Self::new("user", &message.content),
-            MessageType::Tool => Self::new("tool", &message.content),
+        let role = match message.message_type {
+            MessageType::System => "system",
+            MessageType::Ai => "assistant",
+            MessageType::Human => "user",
+            MessageType::Tool => "tool",
+        };
+        let parts = message.content_parts();
+        if !message.is_multimodal() && parts.iter().all(|part| part.hints.is_empty()) {
+            return Self::new(role, &message.content);
+        }
+
+        Self {
+            role: role.into(),
+            content: ClaudeContent::Blocks(parts.iter().filter_map(content_block).collect()),
         }
     }
 }
 
+/// Returns the content block of `part`, with its `anthropic` hint merged in.
+fn content_block(part: &ContentPart) -> Option<Value> {
+    let source = |source: &MediaSource| match source.to_base64() {
+        Some((media_type, data)) => json!({
+            "type": "base64",
+            "media_type": media_type,
+            "data": data,
+        }),
+        None => json!({ "type": "url", "url": source.to_url() }),
+    };
+    let mut block = match &part.content {
+        PartContent::Text { text } => json!({ "type": "text", "text": text }),
+        PartContent::Image { source: s, .. } => json!({ "type": "image", "source": source(s) }),
+        PartContent::File { source: s, .. } => json!({ "type": "document", "source": source(s) }),
+        PartContent::Audio { .. } => {
+            log::warn!("Audio content is not supported by Claude, it is skipped");
+            return None;
+        }
+    };
+    if let (Some(Value::Object(hint)), Value::Object(block)) = (part.hint("anthropic"), &mut block)
+    {
+        block.extend(hint.clone());
+    }
+    Some(block)
+}
+
 #[derive(Serialize, Deserialize)]
 pub(crate) struct Payload {
     pub model: String,
@@ -69,3 +112,40 @@ pub(crate) struct Usage {
     pub input_tokens: u32,
     pub output_tokens: u32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_claude_message_content_blocks() {
+        let message = Message::new_human_message("").with_parts([
+            ContentPart::text("Summarize the report"),
+            ContentPart::file_base64("application/pdf", "JVBERi0=", None).with_hint(
+                "anthropic",
+                json!({ "cache_control": { "type": "ephemeral" } }),
+            ),
+        ]);
+
+        let value = serde_json::to_value(ClaudeMessage::from_message(&message)).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "role": "user",
+                "content": [
+                    { "type": "text", "text": "Summarize the report" },
+                    {
+                        "type": "document",
+                        "source": { "type": "base64", "media_type": "application/pdf", "data": "JVBERi0=" },
+                        "cache_control": { "type": "ephemeral" },
+                    },
+                ],
+            })
+        );
+
+        let value =
+            serde_json::to_value(ClaudeMessage::from_message(&Message::new_ai_message("Hi")))
+                .unwrap();
+        assert_eq!(value, json!({ "role": "assistant", "content": "Hi" }));
+    }
+}
@@ -0,0 +1,67 @@
+use std::pin::Pin;
+
+use async_trait::async_trait;
+use futures::{stream, Stream};
+use serde_json::json;
+
+use crate::{
+    callbacks::{trace_llm, trace_llm_stream},
+    llm::{options::CallOptions, LLMError, LLMOutput, LLM},
+    schemas::{IntoWithUsage, Message, StreamData, WithUsage},
+};
+
+/// An [`LLM`] for the tests, answering the content of the messages with a function.
+pub(crate) struct FakeLLM {
+    answer: Box<dyn Fn(&str) -> String + Send + Sync>,
+}
+
+impl FakeLLM {
+    /// Answers the prompt, the content of the messages joined by newlines, with `answer`.
+    pub(crate) fn new<F: Fn(&str) -> String + Send + Sync + 'static>(answer: F) -> Self {
+        Self {
+            answer: Box::new(answer),
+        }
+    }
+
+    /// Answers the same text to any prompt.
+    pub(crate) fn fixed<S: Into<String>>(answer: S) -> Self {
+        let answer = answer.into();
+        Self::new(move |_| answer.clone())
+    }
+
+    fn answer(&self, messages: &[Message]) -> String {
+        let prompt = messages
+            .iter()
+            .map(|message| message.content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        (self.answer)(&prompt)
+    }
+}
+
+#[async_trait]
+impl LLM for FakeLLM {
+    async fn generate(&self, messages: Vec<Message>) -> Result<WithUsage<LLMOutput>, LLMError> {
+        trace_llm(messages, |messages| async move {
+            Ok(LLMOutput::Text(self.answer(&messages)).with_usage(None))
+        })
+        .await
+    }
+
+    async fn stream(
+        &self,
+        messages: Vec<Message>,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamData, LLMError>> + Send>>, LLMError> {
+        trace_llm_stream(messages, |messages| async move {
+            let answer = self.answer(&messages);
+            let stream: Pin<Box<dyn Stream<Item = _> + Send>> =
+                Box::pin(stream::once(async move {
+                    Ok(StreamData::new(json!(answer), None, answer))
+                }));
+            Ok(stream)
+        })
+        .await
+    }
+
+    fn add_call_options(&mut self, _call_options: CallOptions) {}
+}
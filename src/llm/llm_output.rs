@@ -1,6 +1,8 @@
 use std::fmt::{self, Display};
 
-use async_openai::types::{ChatCompletionResponseMessage, Role};
+use async_openai::types::{
+    ChatCompletionResponseMessage, ChatCompletionResponseMessageAudio, Role,
+};
 use macros::Ctor;
 use serde::{Deserialize, Serialize};
 
@@ -10,6 +12,20 @@ use crate::{chain::ChainOutput, llm::LLMError, schemas::ToolCall};
 pub enum LLMOutput {
     Text(String),
     ToolCall(Vec<ToolCall>),
+    /// A spoken answer, from the audio-capable chat models.
+    Audio(AudioOutput),
+}
+
+/// An audio answer of a model, with its transcript.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AudioOutput {
+    /// The id of the audio, to refer to it in the next turns of the conversation.
+    pub id: String,
+    /// The Unix timestamp (in seconds) at which the audio stops being available to the next turns.
+    pub expires_at: u32,
+    /// The base64 encoded audio, in the format of the request.
+    pub data: String,
+    pub transcript: String,
 }
 
 impl LLMOutput {
@@ -17,11 +33,34 @@ impl LLMOutput {
         let text = match self {
             LLMOutput::Text(text) => text,
             LLMOutput::ToolCall(t) => serde_json::to_string_pretty(&t)?,
+            LLMOutput::Audio(audio) => audio.transcript,
         };
         Ok(text)
     }
 }
 
+impl From<ChatCompletionResponseMessageAudio> for AudioOutput {
+    fn from(value: ChatCompletionResponseMessageAudio) -> Self {
+        Self {
+            id: value.id,
+            expires_at: value.expires_at,
+            data: value.data,
+            transcript: value.transcript,
+        }
+    }
+}
+
+impl From<AudioOutput> for ChatCompletionResponseMessageAudio {
+    fn from(value: AudioOutput) -> Self {
+        Self {
+            id: value.id,
+            expires_at: value.expires_at,
+            data: value.data,
+            transcript: value.transcript,
+        }
+    }
+}
+
 impl Default for LLMOutput {
     fn default() -> Self {
         LLMOutput::Text("".into())
@@ -59,13 +98,15 @@ impl TryFrom<ChatCompletionResponseMessage> for LLMOutput {
         if let Some(function_call) = value.function_call {
             return Ok(LLMOutput::ToolCall(vec![function_call.try_into()?]));
         }
+        if let Some(audio) = value.audio {
+            return Ok(LLMOutput::Audio(audio.into()));
+        }
         if let Some(content) = value.content {
             return Ok(LLMOutput::Text(content));
         }
         if let Some(refusal) = value.refusal {
             return Err(LLMError::Refused(refusal));
         }
-        // TODO: Add other cases (Audio, etc.)
         Err(LLMError::OtherError(
             "Cannot convert LLM generation result to LLMOutput".into(),
         ))
@@ -99,6 +140,14 @@ impl TryFrom<LLMOutput> for ChatCompletionResponseMessage {
                 ),
                 function_call: None,
             }),
+            LLMOutput::Audio(audio) => Ok(ChatCompletionResponseMessage {
+                content: None,
+                refusal: None,
+                role: Role::Assistant,
+                audio: Some(audio.into()),
+                tool_calls: None,
+                function_call: None,
+            }),
         }
     }
 }
@@ -137,6 +186,35 @@ impl Display for LLMOutput {
                 }
                 Ok(())
             }
+            LLMOutput::Audio(audio) => write!(f, "{}", audio.transcript),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_audio_output_serde() {
+        let value = json!({
+            "role": "assistant",
+            "audio": {
+                "id": "audio_1",
+                "expires_at": 1729000000,
+                "data": "UklGRg==",
+                "transcript": "Hello there",
+            },
+        });
+
+        let output: LLMOutput = serde_json::from_value(value.clone()).unwrap();
+        assert!(matches!(&output, LLMOutput::Audio(audio) if audio.id == "audio_1"));
+        assert_eq!(output.to_string(), "Hello there");
+        assert_eq!(
+            serde_json::to_value(&output).unwrap()["audio"],
+            value["audio"]
+        );
+    }
+}
@@ -8,6 +8,9 @@ pub use llm_output::*;
 mod error;
 pub use error::*;
 
+#[cfg(test)]
+pub(crate) mod fake;
+
 pub mod options;
 
 pub mod openai;
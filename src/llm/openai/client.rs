@@ -8,10 +8,13 @@ use async_openai::{
 };
 use async_trait::async_trait;
 use futures::{Stream, StreamExt};
+use serde_json::Value;
 
 use crate::{
+    callbacks::{trace_llm, trace_llm_stream},
     llm::{options::CallOptions, LLMError, LLMOutput, LLM},
     schemas::{messages::Message, IntoWithUsage, MessageType, StreamData, TokenUsage, WithUsage},
+    telemetry,
 };
 
 use super::{
@@ -42,7 +45,11 @@ impl<C: Config> OpenAI<C> {
     fn process_prompt(&self, prompt: Vec<Message>) -> Vec<Message> {
         prompt
             .into_iter()
-            .filter(|message| !message.content.is_empty() || message.tool_calls.is_some())
+            .filter(|message| {
+                !message.content.is_empty()
+                    || message.tool_calls.is_some()
+                    || message.is_multimodal()
+            })
             .map(|message| match message.message_type {
                 MessageType::System if self.call_options.system_is_assistant => Message {
                     message_type: MessageType::Ai,
@@ -73,77 +80,105 @@ impl Default for OpenAI<OpenAIConfig> {
 #[async_trait]
 impl<C: Config + Send + Sync + 'static> LLM for OpenAI<C> {
     async fn generate(&self, prompt: Vec<Message>) -> Result<WithUsage<LLMOutput>, LLMError> {
-        let messages = self.process_prompt(prompt);
-        let options = self.call_options.clone();
-        let request = OpenAIRequest::new(&self.model, messages)?.with_options(options);
-
-        let response = match &self.call_options.stream_option {
-            Some(stream_option) => {
-                let stream = self
-                    .client
-                    .chat()
-                    .create_stream_byot::<_, CreateChatCompletionStreamResponse>(request)
-                    .await?;
-
-                construct_chat_completion_response(stream, &stream_option.streaming_func).await?
-            }
-            None => {
-                self.client
-                    .chat()
-                    .create_byot::<_, CreateChatCompletionResponse>(request)
-                    .await?
-            }
-        };
+        trace_llm(prompt, |prompt| async move {
+            let messages = self.process_prompt(prompt);
+            let options = self.call_options.with_run_config();
+            let stream_option = options.stream_option.clone();
+            let span = telemetry::chat_span("openai", &self.model, &options);
+            let request = OpenAIRequest::new(&self.model, messages)?.with_options(options);
+
+            telemetry::in_span(span.clone(), async move {
+                let response = match &stream_option {
+                    Some(stream_option) => {
+                        let stream = self
+                            .client
+                            .chat()
+                            .create_stream_byot::<_, CreateChatCompletionStreamResponse>(request)
+                            .await?;
+
+                        construct_chat_completion_response(stream, &stream_option.streaming_func)
+                            .await?
+                    }
+                    None => {
+                        self.client
+                            .chat()
+                            .create_byot::<_, CreateChatCompletionResponse>(request)
+                            .await?
+                    }
+                };
 
-        let choice: async_openai::types::ChatChoice = select_choice(response.choices)
-            .ok_or(LLMError::ContentNotFound("No choices".into()))?;
+                let choice: async_openai::types::ChatChoice = select_choice(response.choices)
+                    .ok_or(LLMError::ContentNotFound("No choices".into()))?;
 
-        let result: LLMOutput = choice.message.try_into()?;
-        let usage = response.usage.map(Into::into);
+                let finish_reason = choice
+                    .finish_reason
+                    .and_then(|r| serde_json::to_value(r).ok())
+                    .and_then(|r| r.as_str().map(String::from));
+                let result: LLMOutput = choice.message.try_into()?;
+                let usage = response.usage.map(Into::into);
+                telemetry::record_response(&span, finish_reason.as_deref(), usage.as_ref());
 
-        Ok(result.with_usage(usage))
+                Ok(result.with_usage(usage))
+            })
+            .await
+        })
+        .await
     }
 
     async fn stream(
         &self,
         messages: Vec<Message>,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamData, LLMError>> + Send>>, LLMError> {
-        let messages = self.process_prompt(messages);
-        let options = self.call_options.clone();
-        let request = OpenAIRequest::new(&self.model, messages)?.with_options(options);
-
-        let original_stream = self
-            .client
-            .chat()
-            .create_stream_byot::<_, CreateChatCompletionStreamResponse>(request)
+        trace_llm_stream(messages, |messages| async move {
+            let messages = self.process_prompt(messages);
+            let options = self.call_options.with_run_config();
+            let span = telemetry::chat_span("openai", &self.model, &options);
+            let request = OpenAIRequest::new(&self.model, messages)?.with_options(options);
+
+            let original_stream = telemetry::in_span(span.clone(), async {
+                self.client
+                    .chat()
+                    .create_stream_byot::<_, CreateChatCompletionStreamResponse>(request)
+                    .await
+                    .map_err(LLMError::from)
+            })
             .await?;
 
-        let new_stream = original_stream.map(|result| match result {
-            Ok(completion) => {
-                let value_completion = serde_json::to_value(completion).map_err(LLMError::from)?;
-                let usage = value_completion.pointer("/usage");
-                if usage.is_some() && !usage.unwrap().is_null() {
-                    let usage = serde_json::from_value::<TokenUsage>(usage.unwrap().clone())
-                        .map_err(LLMError::from)?;
-                    return Ok(StreamData::new(value_completion, Some(usage), ""));
+            let response_span = span.clone();
+            let new_stream = original_stream.map(move |result| match result {
+                Ok(completion) => {
+                    let value_completion =
+                        serde_json::to_value(completion).map_err(LLMError::from)?;
+                    let finish_reason = value_completion
+                        .pointer("/choices/0/finish_reason")
+                        .and_then(Value::as_str);
+                    telemetry::record_response(&response_span, finish_reason, None);
+                    if let Some(usage) = value_completion.pointer("/usage").filter(|u| !u.is_null())
+                    {
+                        let usage = serde_json::from_value::<TokenUsage>(usage.clone())
+                            .map_err(LLMError::from)?;
+                        telemetry::record_response(&response_span, None, Some(&usage));
+                        return Ok(StreamData::new(value_completion, Some(usage), ""));
+                    }
+                    let content = value_completion
+                        .pointer("/choices/0/delta/content")
+                        .ok_or(LLMError::ContentNotFound(
+                            "/choices/0/delta/content".to_string(),
+                        ))?
+                        .clone();
+
+                    Ok(StreamData::new(
+                        value_completion,
+                        None,
+                        content.as_str().unwrap_or(""),
+                    ))
                 }
-                let content = value_completion
-                    .pointer("/choices/0/delta/content")
-                    .ok_or(LLMError::ContentNotFound(
-                        "/choices/0/delta/content".to_string(),
-                    ))?
-                    .clone();
-
-                Ok(StreamData::new(
-                    value_completion,
-                    None,
-                    content.as_str().unwrap_or(""),
-                ))
-            }
-            Err(e) => Err(LLMError::from(e)),
-        });
+                Err(e) => Err(LLMError::from(e)),
+            });
 
-        Ok(Box::pin(new_stream))
+            Ok(Box::pin(telemetry::stream_in_span(span, new_stream)))
+        })
+        .await
     }
 
     fn add_call_options(&mut self, call_options: CallOptions) {
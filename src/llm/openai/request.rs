@@ -1,8 +1,9 @@
 use async_openai::types::{
-    ChatCompletionRequestMessage, ChatCompletionStreamOptions, ChatCompletionTool,
+    ChatCompletionAudio, ChatCompletionModalities, ChatCompletionStreamOptions, ChatCompletionTool,
     ChatCompletionToolChoiceOption, ResponseFormat,
 };
 use serde::Serialize;
+use serde_json::Value;
 
 use crate::{
     llm::{options::CallOptions, LLMError},
@@ -12,7 +13,8 @@ use crate::{
 /// Request payload sent to an OpenAPI-compatible API.
 #[derive(Serialize, Debug)]
 pub struct OpenAIRequest {
-    pub messages: Vec<ChatCompletionRequestMessage>,
+    /// The messages in the chat format, see [`Message::to_openai_value`].
+    pub messages: Vec<Value>,
     pub model: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stream: Option<bool>,
@@ -50,6 +52,10 @@ pub struct OpenAIRequest {
     pub tool_choice: Option<ChatCompletionToolChoiceOption>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub response_format: Option<ResponseFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modalities: Option<Vec<ChatCompletionModalities>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio: Option<ChatCompletionAudio>,
 }
 
 impl OpenAIRequest {
@@ -59,8 +65,8 @@ impl OpenAIRequest {
         messages: Vec<Message>,
     ) -> Result<OpenAIRequest, LLMError> {
         let messages = messages
-            .into_iter()
-            .map(TryInto::try_into)
+            .iter()
+            .map(Message::to_openai_value)
             .collect::<Result<Vec<_>, _>>()?;
 
         Ok(OpenAIRequest {
@@ -84,6 +90,8 @@ impl OpenAIRequest {
             tools: None,
             tool_choice: None,
             response_format: None,
+            modalities: None,
+            audio: None,
         })
     }
 
@@ -112,6 +120,13 @@ impl OpenAIRequest {
             tools: options.tools,
             tool_choice: options.tool_choice,
             response_format: options.response_format,
+            modalities: options.audio.as_ref().map(|_| {
+                vec![
+                    ChatCompletionModalities::Text,
+                    ChatCompletionModalities::Audio,
+                ]
+            }),
+            audio: options.audio,
             ..self
         }
     }
@@ -1,9 +1,11 @@
-use async_openai::types::{ChatCompletionTool, ChatCompletionToolChoiceOption, ResponseFormat};
+use async_openai::types::{
+    ChatCompletionAudio, ChatCompletionTool, ChatCompletionToolChoiceOption, ResponseFormat,
+};
 use futures::Future;
 use std::{error::Error, fmt, pin::Pin, sync::Arc};
 use tokio::sync::Mutex;
 
-use crate::schemas::StreamingFunc;
+use crate::{chain::RunConfig, schemas::StreamingFunc};
 
 #[derive(Clone, Default)]
 pub struct StreamOption {
@@ -59,6 +61,8 @@ pub struct CallOptions {
     pub tools: Option<Vec<ChatCompletionTool>>,
     pub tool_choice: Option<ChatCompletionToolChoiceOption>,
     pub response_format: Option<ResponseFormat>,
+    /// Asks the audio-capable models for a spoken answer, with this voice and format.
+    pub audio: Option<ChatCompletionAudio>,
     pub stream_option: Option<StreamOption>,
     pub system_is_assistant: bool,
 }
@@ -87,6 +91,7 @@ impl CallOptions {
             tools: None,
             tool_choice: None,
             response_format: None,
+            audio: None,
             stream_option: None,
             system_is_assistant: false,
         }
@@ -173,6 +178,11 @@ impl CallOptions {
         self
     }
 
+    pub fn with_audio(mut self, audio: ChatCompletionAudio) -> Self {
+        self.audio = Some(audio);
+        self
+    }
+
     pub fn with_stream(mut self, stream: StreamOption) -> Self {
         self.stream_option = Some(stream);
         self
@@ -183,6 +193,26 @@ impl CallOptions {
         self
     }
 
+    /// Returns these options merged with the call options of the current [`RunConfig`], if any.
+    /// LLMs should build their requests from the returned options.
+    pub fn with_run_config(&self) -> CallOptions {
+        let mut options = self.clone();
+        if let Some(call_options) = RunConfig::current().and_then(|c| c.call_options) {
+            options.override_options(call_options);
+        }
+        options
+    }
+
+    /// Merges the call options of a [`RunConfig`] into these ones, as
+    /// [`merge_options`](CallOptions::merge_options) except that their stop words replace the
+    /// existing ones, so that applying the same run options again changes nothing.
+    pub(crate) fn override_options(&mut self, mut overrides: CallOptions) {
+        if let Some(stop_words) = overrides.stop_words.take() {
+            self.stop_words = Some(stop_words);
+        }
+        self.merge_options(overrides);
+    }
+
     pub fn merge_options(&mut self, incoming_options: CallOptions) {
         // For simple scalar types wrapped in Option, prefer incoming option if it is Some
         self.candidate_count = incoming_options.candidate_count.or(self.candidate_count);
@@ -205,6 +235,7 @@ impl CallOptions {
         self.response_format = incoming_options
             .response_format
             .or(self.response_format.clone());
+        self.audio = incoming_options.audio.or(self.audio.clone());
 
         // For `Vec<String>`, merge if both are Some; prefer incoming if only incoming is Some
         if let Some(mut new_stop_words) = incoming_options.stop_words {
@@ -231,3 +262,30 @@ impl CallOptions {
         self.system_is_assistant = self.system_is_assistant || incoming_options.system_is_assistant;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_options_appends_stop_words() {
+        let mut options = CallOptions::new().with_stop_words(vec!["END".into()]);
+        options.merge_options(CallOptions::new().with_stop_words(vec!["STOP".into()]));
+        assert_eq!(
+            options.stop_words,
+            Some(vec!["END".to_string(), "STOP".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_override_options_replaces_stop_words() {
+        let mut options = CallOptions::new()
+            .with_stop_words(vec!["END".into()])
+            .with_temperature(0.1);
+        let overrides = CallOptions::new().with_stop_words(vec!["STOP".into()]);
+        options.override_options(overrides.clone());
+        options.override_options(overrides);
+        assert_eq!(options.stop_words, Some(vec!["STOP".to_string()]));
+        assert_eq!(options.temperature, Some(0.1));
+    }
+}
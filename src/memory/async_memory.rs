@@ -0,0 +1,172 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+
+use crate::{
+    agent::AgentStep,
+    schemas::{Message, ToolCall},
+};
+
+use super::{Memory, MemoryError};
+
+/// An asynchronous chat memory, for memories backed by a database or a remote service.
+///
+/// Implementations are shared between concurrent runs and synchronize themselves. Synchronous
+/// memories are used through [`SyncMemoryAdapter`].
+#[async_trait]
+pub trait AsyncMemory: Send + Sync {
+    async fn messages(&self) -> Result<Vec<Message>, MemoryError>;
+
+    async fn add_message(&self, message: Message) -> Result<(), MemoryError>;
+
+    async fn clear(&self) -> Result<(), MemoryError>;
+
+    /// Returns the messages to give to the model for the input `query`. Memories recalling
+    /// messages by relevance override it, the others return all their messages.
+    async fn relevant_messages(&self, _query: &str) -> Result<Vec<Message>, MemoryError> {
+        self.messages().await
+    }
+
+    async fn to_string(&self) -> Result<String, MemoryError> {
+        Ok(self
+            .messages()
+            .await?
+            .iter()
+            .map(|msg| msg.to_string())
+            .collect::<Vec<String>>()
+            .join("\n"))
+    }
+
+    async fn update(
+        &self,
+        human_message: String,
+        steps: Vec<AgentStep>,
+        final_answer: String,
+    ) -> Result<(), MemoryError> {
+        self.add_human_message(human_message).await?;
+        for step in steps {
+            let tool_call_id = step.tool_call.id.clone();
+            self.add_tool_call_message(vec![step.tool_call]).await?;
+            self.add_tool_message(Some(tool_call_id), step.result)
+                .await?;
+        }
+        self.add_ai_message(final_answer).await
+    }
+
+    async fn add_human_message(&self, content: String) -> Result<(), MemoryError> {
+        self.add_message(Message::new_human_message(content)).await
+    }
+
+    async fn add_ai_message(&self, content: String) -> Result<(), MemoryError> {
+        self.add_message(Message::new_ai_message(content)).await
+    }
+
+    async fn add_tool_call_message(&self, tool_calls: Vec<ToolCall>) -> Result<(), MemoryError> {
+        self.add_message(Message::new_tool_call_message(tool_calls))
+            .await
+    }
+
+    async fn add_tool_message(
+        &self,
+        id: Option<String>,
+        content: String,
+    ) -> Result<(), MemoryError> {
+        self.add_message(Message::new_tool_message(id, content))
+            .await
+    }
+}
+
+/// Adapts a synchronous [`Memory`] to [`AsyncMemory`].
+#[derive(Clone)]
+pub struct SyncMemoryAdapter {
+    memory: Arc<RwLock<dyn Memory>>,
+}
+
+impl SyncMemoryAdapter {
+    pub fn new(memory: Arc<RwLock<dyn Memory>>) -> Self {
+        Self { memory }
+    }
+
+    /// Returns the adapted memory.
+    pub fn inner(&self) -> &Arc<RwLock<dyn Memory>> {
+        &self.memory
+    }
+}
+
+impl From<Arc<RwLock<dyn Memory>>> for SyncMemoryAdapter {
+    fn from(memory: Arc<RwLock<dyn Memory>>) -> Self {
+        Self::new(memory)
+    }
+}
+
+impl From<SyncMemoryAdapter> for Arc<dyn AsyncMemory> {
+    fn from(val: SyncMemoryAdapter) -> Self {
+        Arc::new(val)
+    }
+}
+
+#[async_trait]
+impl AsyncMemory for SyncMemoryAdapter {
+    async fn messages(&self) -> Result<Vec<Message>, MemoryError> {
+        Ok(self.memory.read().await.messages())
+    }
+
+    async fn add_message(&self, message: Message) -> Result<(), MemoryError> {
+        self.memory.write().await.add_message(message);
+        Ok(())
+    }
+
+    async fn clear(&self) -> Result<(), MemoryError> {
+        self.memory.write().await.clear();
+        Ok(())
+    }
+
+    async fn to_string(&self) -> Result<String, MemoryError> {
+        Ok(self.memory.read().await.to_string())
+    }
+
+    async fn update(
+        &self,
+        human_message: String,
+        steps: Vec<AgentStep>,
+        final_answer: String,
+    ) -> Result<(), MemoryError> {
+        self.memory
+            .write()
+            .await
+            .update(human_message, steps, final_answer);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{memory::SimpleMemory, schemas::MessageType};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sync_memory_adapter() {
+        let memory: Arc<RwLock<dyn Memory>> = SimpleMemory::new().into();
+        let adapter: Arc<dyn AsyncMemory> = SyncMemoryAdapter::new(memory.clone()).into();
+
+        let step = AgentStep::new(
+            ToolCall::new("call_1", "calculator", serde_json::json!("2 + 2")),
+            "4",
+            None,
+        );
+        adapter
+            .update("What is 2 + 2?".into(), vec![step], "4".into())
+            .await
+            .unwrap();
+
+        let messages = adapter.messages().await.unwrap();
+        assert_eq!(messages.len(), 4);
+        assert_eq!(messages[2].message_type, MessageType::Tool);
+        assert_eq!(memory.read().await.messages().len(), 4);
+
+        adapter.clear().await.unwrap();
+        assert!(memory.read().await.messages().is_empty());
+    }
+}
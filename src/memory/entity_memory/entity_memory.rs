@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use indoc::indoc;
+use tokio::sync::Mutex;
+
+use crate::{
+    llm::LLM,
+    memory::{AsyncMemory, MemoryError},
+    schemas::{Message, MessageType},
+    template::MessageTemplate,
+};
+
+use super::{EntityStore, InMemoryEntityStore};
+
+pub const DEFAULT_ENTITY_EXTRACTION_TEMPLATE: &str = indoc! {"
+    You are an AI assistant reading the transcript of a conversation between an AI and a human.
+    Extract all of the proper nouns from the last lines of conversation. As a guideline, a proper
+    noun is generally capitalized. You should definitely extract all names and places.
+
+    The conversation history is provided just in case of a coreference (e.g. \"What do you know
+    about him\" where \"him\" is defined in a previous line) -- ignore items mentioned there that
+    are not in the last lines.
+
+    Return the output as a single comma-separated list, or NONE if there is nothing of note to
+    return.
+
+    Conversation history:
+    {history}
+
+    Last lines of conversation:
+    {input}
+
+    Output:"};
+
+pub const DEFAULT_ENTITY_SUMMARY_TEMPLATE: &str = indoc! {"
+    You are an AI assistant helping a human keep track of facts about relevant people, places,
+    and concepts in their life. Update the summary of the provided entity based on the last lines
+    of your conversation with the human. If you are writing the summary for the first time,
+    return a single sentence.
+    The update should only include facts that are relayed in the last lines of conversation
+    about the provided entity, and should only contain facts about the provided entity.
+
+    If there is no new information about the provided entity or the information is not worth
+    noting, return the existing summary unchanged.
+
+    Conversation history:
+    {history}
+
+    Entity to summarize:
+    {entity}
+
+    Existing summary of {entity}:
+    {summary}
+
+    Last lines of conversation:
+    {input}
+
+    Updated summary:"};
+
+#[derive(Default)]
+struct EntityState {
+    /// The most recent messages, given as history to the prompts.
+    history: Vec<Message>,
+    /// The messages of the turn being written.
+    turn: Vec<Message>,
+    /// The entities of the last turn.
+    entities: Vec<String>,
+}
+
+/// A memory remembering facts about the entities (people, places, concepts...) of the
+/// conversation, extracted by an [`LLM`] and kept in an [`EntityStore`].
+///
+/// A turn is processed when its final AI message is added, so that the tool calls and results
+/// written by [`AsyncMemory::update`] are part of it. The entities are extracted from the turn
+/// and the summary of each one is updated. The summaries of the entities mentioned in the input
+/// are given to the model by [`AsyncMemory::relevant_messages`], followed by the most recent
+/// messages.
+pub struct EntityMemory {
+    llm: Box<dyn LLM>,
+    store: Box<dyn EntityStore>,
+    extraction_template: MessageTemplate,
+    summary_template: MessageTemplate,
+    history_len: usize,
+    state: Mutex<EntityState>,
+}
+
+impl EntityMemory {
+    pub fn new(llm: impl Into<Box<dyn LLM>>) -> Self {
+        Self {
+            llm: llm.into(),
+            store: Box::new(InMemoryEntityStore::new()),
+            extraction_template: MessageTemplate::from_fstring(
+                MessageType::Human,
+                DEFAULT_ENTITY_EXTRACTION_TEMPLATE,
+            ),
+            summary_template: MessageTemplate::from_fstring(
+                MessageType::Human,
+                DEFAULT_ENTITY_SUMMARY_TEMPLATE,
+            ),
+            history_len: 6,
+            state: Mutex::default(),
+        }
+    }
+
+    pub fn with_store<S: EntityStore + 'static>(mut self, store: S) -> Self {
+        self.store = Box::new(store);
+        self
+    }
+
+    /// Sets the prompt used to extract the entities, with `{history}` and `{input}`
+    /// placeholders. The model must answer a comma-separated list, or `NONE`.
+    pub fn with_extraction_template(mut self, template: impl Into<String>) -> Self {
+        self.extraction_template = MessageTemplate::from_fstring(MessageType::Human, template);
+        self
+    }
+
+    /// Sets the prompt used to update the summary of an entity, with `{history}`, `{entity}`,
+    /// `{summary}` and `{input}` placeholders.
+    pub fn with_summary_template(mut self, template: impl Into<String>) -> Self {
+        self.summary_template = MessageTemplate::from_fstring(MessageType::Human, template);
+        self
+    }
+
+    /// Sets the number of recent messages kept as history, 6 by default.
+    pub fn with_history_len(mut self, history_len: usize) -> Self {
+        self.history_len = history_len;
+        self
+    }
+
+    pub fn store(&self) -> &dyn EntityStore {
+        self.store.as_ref()
+    }
+
+    /// Returns the entities mentioned in `input`, using `history` for coreferences.
+    pub async fn extract_entities(
+        &self,
+        history: &str,
+        input: &str,
+    ) -> Result<Vec<String>, MemoryError> {
+        let prompt = self.extraction_template.format(&HashMap::from([
+            ("history", history.into()),
+            ("input", input.into()),
+        ]))?;
+        let output = self.llm.invoke(&prompt.content).await?;
+
+        let mut entities: Vec<String> = Vec::new();
+        for entity in output.trim().split(',').map(str::trim) {
+            if !entity.is_empty()
+                && !entity.eq_ignore_ascii_case("none")
+                && !entities.iter().any(|e| e == entity)
+            {
+                entities.push(entity.to_string());
+            }
+        }
+        Ok(entities)
+    }
+
+    /// Returns the system message with the summaries of the known `entities`.
+    async fn entities_message(&self, entities: &[String]) -> Result<Option<Message>, MemoryError> {
+        let mut summaries = Vec::new();
+        for entity in entities {
+            if let Some(summary) = self.store.get(entity).await? {
+                summaries.push(format!("{entity}: {summary}"));
+            }
+        }
+        if summaries.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Message::new_system_message(format!(
+            "Context about the entities of the conversation:\n{}",
+            summaries.join("\n")
+        ))))
+    }
+
+    /// Extracts the entities of the turn and updates their summaries.
+    async fn remember(&self, state: &mut EntityState) -> Result<(), MemoryError> {
+        let history = Message::messages_to_string(&state.history);
+        let input = Message::messages_to_string(&state.turn);
+
+        let entities = self.extract_entities(&history, &input).await?;
+        for entity in &entities {
+            let summary = self.store.get(entity).await?.unwrap_or_default();
+            let prompt = self.summary_template.format(&HashMap::from([
+                ("history", history.as_str().into()),
+                ("entity", entity.as_str().into()),
+                ("summary", summary.into()),
+                ("input", input.as_str().into()),
+            ]))?;
+            let summary = self.llm.invoke(&prompt.content).await?;
+            self.store.set(entity, summary.trim()).await?;
+        }
+
+        let turn = std::mem::take(&mut state.turn);
+        state.history.extend(turn);
+        let mut evicted = state.history.len().saturating_sub(self.history_len);
+        // Never keep tool messages without their call.
+        while state
+            .history
+            .get(evicted)
+            .is_some_and(|m| m.message_type == MessageType::Tool)
+        {
+            evicted += 1;
+        }
+        state.history.drain(..evicted);
+        state.entities = entities;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AsyncMemory for EntityMemory {
+    async fn messages(&self) -> Result<Vec<Message>, MemoryError> {
+        let state = self.state.lock().await;
+        let mut messages = Vec::with_capacity(state.history.len() + 1);
+        messages.extend(self.entities_message(&state.entities).await?);
+        messages.extend(state.history.iter().cloned());
+        Ok(messages)
+    }
+
+    async fn relevant_messages(&self, query: &str) -> Result<Vec<Message>, MemoryError> {
+        let state = self.state.lock().await;
+        let history = Message::messages_to_string(&state.history);
+        let input = Message::new_human_message(query).to_string();
+        let entities = self.extract_entities(&history, &input).await?;
+
+        let mut messages = Vec::with_capacity(state.history.len() + 1);
+        messages.extend(self.entities_message(&entities).await?);
+        messages.extend(state.history.iter().cloned());
+        Ok(messages)
+    }
+
+    async fn add_message(&self, message: Message) -> Result<(), MemoryError> {
+        let mut state = self.state.lock().await;
+        let is_final_answer = message.message_type == MessageType::Ai
+            && message.tool_calls.as_ref().is_none_or(|c| c.is_empty());
+        state.turn.push(message);
+        if is_final_answer {
+            self.remember(&mut state).await?;
+        }
+        Ok(())
+    }
+
+    async fn clear(&self) -> Result<(), MemoryError> {
+        *self.state.lock().await = EntityState::default();
+        self.store.clear().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{agent::AgentStep, llm::fake::FakeLLM, schemas::ToolCall};
+
+    use super::*;
+
+    fn section<'a>(prompt: &'a str, start: &str, end: &str) -> &'a str {
+        prompt
+            .split(start)
+            .nth(1)
+            .and_then(|rest| rest.split(end).next())
+            .unwrap_or_default()
+    }
+
+    /// Extracts the capitalized words of the last lines, and summarizes an entity by appending
+    /// the last lines mentioning it to its summary.
+    fn capitalized_words() -> FakeLLM {
+        FakeLLM::new(|prompt| {
+            let input = section(prompt, "Last lines of conversation:\n", "\n\n");
+            if prompt.ends_with("Output:") {
+                input
+                    .split(|c: char| !c.is_alphanumeric())
+                    .filter(|w| w.starts_with(char::is_uppercase))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            } else {
+                let entity = section(prompt, "Entity to summarize:\n", "\n");
+                let summary = section(prompt, &format!("Existing summary of {entity}:\n"), "\n");
+                let facts = input
+                    .lines()
+                    .filter(|l| l.contains(entity))
+                    .map(|l| l.split_once(": ").map_or(l, |(_, fact)| fact))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                format!("{summary} {facts}")
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_entity_memory() {
+        let memory = EntityMemory::new(capitalized_words());
+
+        let step = AgentStep::new(
+            ToolCall::new("call_1", "weather", serde_json::json!("today")),
+            "Lima is sunny",
+            None,
+        );
+        memory
+            .update(
+                "Luis moved to Lima".into(),
+                vec![step],
+                "Say hi to Luis".into(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            memory.store().get("Luis").await.unwrap().as_deref(),
+            Some("Luis moved to Lima Say hi to Luis")
+        );
+        assert_eq!(
+            memory.store().get("Lima").await.unwrap().as_deref(),
+            Some("Luis moved to Lima Lima is sunny")
+        );
+        assert_eq!(memory.messages().await.unwrap().len(), 5);
+
+        let messages = memory.relevant_messages("Where is Luis?").await.unwrap();
+        assert_eq!(messages[0].message_type, MessageType::System);
+        assert!(messages[0].content.contains("Luis: Luis moved to Lima"));
+        assert!(!messages[0].content.contains("Lima: "));
+
+        memory.clear().await.unwrap();
+        assert!(memory.messages().await.unwrap().is_empty());
+        assert_eq!(memory.store().get("Luis").await.unwrap(), None);
+    }
+}
@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+
+use crate::memory::MemoryError;
+
+/// Storage of the summaries of the entities of an [`EntityMemory`](super::EntityMemory).
+#[async_trait]
+pub trait EntityStore: Send + Sync {
+    async fn get(&self, entity: &str) -> Result<Option<String>, MemoryError>;
+
+    async fn set(&self, entity: &str, summary: &str) -> Result<(), MemoryError>;
+
+    async fn delete(&self, entity: &str) -> Result<(), MemoryError>;
+
+    async fn clear(&self) -> Result<(), MemoryError>;
+}
+
+#[derive(Default)]
+pub struct InMemoryEntityStore {
+    entities: RwLock<HashMap<String, String>>,
+}
+
+impl InMemoryEntityStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl EntityStore for InMemoryEntityStore {
+    async fn get(&self, entity: &str) -> Result<Option<String>, MemoryError> {
+        Ok(self.entities.read().await.get(entity).cloned())
+    }
+
+    async fn set(&self, entity: &str, summary: &str) -> Result<(), MemoryError> {
+        self.entities
+            .write()
+            .await
+            .insert(entity.into(), summary.into());
+        Ok(())
+    }
+
+    async fn delete(&self, entity: &str) -> Result<(), MemoryError> {
+        self.entities.write().await.remove(entity);
+        Ok(())
+    }
+
+    async fn clear(&self) -> Result<(), MemoryError> {
+        self.entities.write().await.clear();
+        Ok(())
+    }
+}
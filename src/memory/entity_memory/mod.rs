@@ -0,0 +1,11 @@
+#[allow(clippy::module_inception)]
+mod entity_memory;
+pub use entity_memory::*;
+
+mod entity_store;
+pub use entity_store::*;
+
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(feature = "sqlite")]
+pub use sqlite::*;
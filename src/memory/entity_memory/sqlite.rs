@@ -0,0 +1,127 @@
+use async_trait::async_trait;
+use indoc::formatdoc;
+use sqlx::{Pool, Sqlite};
+
+use crate::memory::MemoryError;
+
+use super::EntityStore;
+
+/// Stores the summaries of the entities of a session in a sqlite table.
+pub struct SqliteEntityStore {
+    pool: Pool<Sqlite>,
+    table: String,
+    session_id: String,
+}
+
+impl SqliteEntityStore {
+    pub fn new(pool: Pool<Sqlite>, session_id: impl Into<String>) -> Self {
+        Self {
+            pool,
+            table: "entity_store".into(),
+            session_id: session_id.into(),
+        }
+    }
+
+    pub fn with_table(mut self, table: &str) -> Self {
+        self.table = table.into();
+        self
+    }
+
+    /// Creates the table of the entities if it does not exist.
+    pub async fn initialize(&self) -> Result<(), MemoryError> {
+        let table = &self.table;
+        sqlx::query(&formatdoc! {"
+            CREATE TABLE IF NOT EXISTS {table}
+            (
+                session_id TEXT NOT NULL,
+                entity TEXT NOT NULL,
+                summary TEXT NOT NULL,
+                PRIMARY KEY (session_id, entity)
+            );"
+        })
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EntityStore for SqliteEntityStore {
+    async fn get(&self, entity: &str) -> Result<Option<String>, MemoryError> {
+        let table = &self.table;
+        let row: Option<(String,)> = sqlx::query_as(&format!(
+            "SELECT summary FROM {table} WHERE session_id = ? AND entity = ?"
+        ))
+        .bind(&self.session_id)
+        .bind(entity)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(|(summary,)| summary))
+    }
+
+    async fn set(&self, entity: &str, summary: &str) -> Result<(), MemoryError> {
+        let table = &self.table;
+        sqlx::query(&formatdoc! {"
+            INSERT INTO {table} (session_id, entity, summary) VALUES (?, ?, ?)
+            ON CONFLICT (session_id, entity) DO UPDATE SET summary = excluded.summary"
+        })
+        .bind(&self.session_id)
+        .bind(entity)
+        .bind(summary)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn delete(&self, entity: &str) -> Result<(), MemoryError> {
+        let table = &self.table;
+        sqlx::query(&format!(
+            "DELETE FROM {table} WHERE session_id = ? AND entity = ?"
+        ))
+        .bind(&self.session_id)
+        .bind(entity)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn clear(&self) -> Result<(), MemoryError> {
+        let table = &self.table;
+        sqlx::query(&format!("DELETE FROM {table} WHERE session_id = ?"))
+            .bind(&self.session_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sqlite_entity_store() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let store = SqliteEntityStore::new(pool.clone(), "a");
+        store.initialize().await.unwrap();
+
+        store.set("Luis", "Lives in Peru.").await.unwrap();
+        store.set("Luis", "Lives in Lima.").await.unwrap();
+        assert_eq!(
+            store.get("Luis").await.unwrap().as_deref(),
+            Some("Lives in Lima.")
+        );
+
+        let other_session = SqliteEntityStore::new(pool, "b");
+        assert_eq!(other_session.get("Luis").await.unwrap(), None);
+
+        store.clear().await.unwrap();
+        assert_eq!(store.get("Luis").await.unwrap(), None);
+    }
+}
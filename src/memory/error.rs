@@ -0,0 +1,25 @@
+use thiserror::Error;
+
+use crate::{llm::LLMError, template::TemplateError};
+
+#[derive(Error, Debug)]
+pub enum MemoryError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("LLM error: {0}")]
+    LLMError(#[from] LLMError),
+
+    #[error("Template error: {0}")]
+    TemplateError(#[from] TemplateError),
+
+    #[error("Serialization error: {0}")]
+    SerdeError(#[from] serde_json::Error),
+
+    #[cfg(any(feature = "postgres", feature = "sqlite"))]
+    #[error("Database error: {0}")]
+    SqlxError(#[from] sqlx::Error),
+
+    #[error("Memory error: {0}")]
+    OtherError(String),
+}
@@ -0,0 +1,68 @@
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use tokio::{
+    fs::{self, OpenOptions},
+    io::AsyncWriteExt,
+};
+
+use crate::{memory::MemoryError, schemas::Message};
+
+use super::MessageStore;
+
+/// Stores every session in its own file of `directory`, named `<session_id>.jsonl`, with a
+/// JSON message per line.
+pub struct JsonlMessageStore {
+    directory: PathBuf,
+}
+
+impl JsonlMessageStore {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    fn path(&self, session_id: &str) -> Result<PathBuf, MemoryError> {
+        let file_name = format!("{session_id}.jsonl");
+        if Path::new(&file_name).file_name().and_then(|f| f.to_str()) != Some(&file_name) {
+            return Err(MemoryError::OtherError(format!(
+                "Invalid session id for a file name: {session_id}"
+            )));
+        }
+        Ok(self.directory.join(file_name))
+    }
+}
+
+#[async_trait]
+impl MessageStore for JsonlMessageStore {
+    async fn load(&self, session_id: &str) -> Result<Vec<Message>, MemoryError> {
+        let content = match fs::read_to_string(self.path(session_id)?).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Message::messages_from_jsonl(&content)?)
+    }
+
+    async fn append(&self, session_id: &str, message: &Message) -> Result<(), MemoryError> {
+        fs::create_dir_all(&self.directory).await?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path(session_id)?)
+            .await?;
+
+        let line = format!("{}\n", serde_json::to_string(message)?);
+        file.write_all(line.as_bytes()).await?;
+        Ok(())
+    }
+
+    async fn clear(&self, session_id: &str) -> Result<(), MemoryError> {
+        match fs::remove_file(self.path(session_id)?).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
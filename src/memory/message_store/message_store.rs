@@ -0,0 +1,15 @@
+use async_trait::async_trait;
+
+use crate::{memory::MemoryError, schemas::Message};
+
+/// Durable storage of the messages of chat sessions, used by
+/// [`PersistentMemory`](crate::memory::PersistentMemory).
+#[async_trait]
+pub trait MessageStore: Send + Sync {
+    /// Returns the messages of the session, in the order they were added.
+    async fn load(&self, session_id: &str) -> Result<Vec<Message>, MemoryError>;
+
+    async fn append(&self, session_id: &str, message: &Message) -> Result<(), MemoryError>;
+
+    async fn clear(&self, session_id: &str) -> Result<(), MemoryError>;
+}
@@ -0,0 +1,16 @@
+#[allow(clippy::module_inception)]
+mod message_store;
+pub use message_store::*;
+
+mod jsonl;
+pub use jsonl::*;
+
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(feature = "sqlite")]
+pub use sqlite::*;
+
+#[cfg(feature = "postgres")]
+mod postgres;
+#[cfg(feature = "postgres")]
+pub use postgres::*;
@@ -0,0 +1,86 @@
+use async_trait::async_trait;
+use indoc::formatdoc;
+use sqlx::{types::Json, Pool, Postgres};
+
+use crate::{memory::MemoryError, schemas::Message};
+
+use super::MessageStore;
+
+/// Stores the messages of every session in a postgres table, as JSONB.
+pub struct PostgresMessageStore {
+    pool: Pool<Postgres>,
+    table: String,
+}
+
+impl PostgresMessageStore {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self {
+            pool,
+            table: "message_store".into(),
+        }
+    }
+
+    pub fn with_table(mut self, table: &str) -> Self {
+        self.table = table.into();
+        self
+    }
+
+    /// Creates the table of the messages if it does not exist.
+    pub async fn initialize(&self) -> Result<(), MemoryError> {
+        let table = &self.table;
+        sqlx::query(&formatdoc! {"
+            CREATE TABLE IF NOT EXISTS {table}
+            (
+                id BIGSERIAL PRIMARY KEY,
+                session_id TEXT NOT NULL,
+                message JSONB NOT NULL
+            );"
+        })
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(&format!(
+            "CREATE INDEX IF NOT EXISTS {table}_session_id ON {table} (session_id);"
+        ))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MessageStore for PostgresMessageStore {
+    async fn load(&self, session_id: &str) -> Result<Vec<Message>, MemoryError> {
+        let table = &self.table;
+        let rows: Vec<(Json<Message>,)> = sqlx::query_as(&format!(
+            "SELECT message FROM {table} WHERE session_id = $1 ORDER BY id"
+        ))
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|(message,)| message.0).collect())
+    }
+
+    async fn append(&self, session_id: &str, message: &Message) -> Result<(), MemoryError> {
+        let table = &self.table;
+        sqlx::query(&format!(
+            "INSERT INTO {table} (session_id, message) VALUES ($1, $2)"
+        ))
+        .bind(session_id)
+        .bind(Json(message))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn clear(&self, session_id: &str) -> Result<(), MemoryError> {
+        let table = &self.table;
+        sqlx::query(&format!("DELETE FROM {table} WHERE session_id = $1"))
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
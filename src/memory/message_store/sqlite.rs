@@ -0,0 +1,131 @@
+use async_trait::async_trait;
+use indoc::formatdoc;
+use sqlx::{Pool, Sqlite};
+
+use crate::{memory::MemoryError, schemas::Message};
+
+use super::MessageStore;
+
+/// Stores the messages of every session in a sqlite table, as JSON.
+pub struct SqliteMessageStore {
+    pool: Pool<Sqlite>,
+    table: String,
+}
+
+impl SqliteMessageStore {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self {
+            pool,
+            table: "message_store".into(),
+        }
+    }
+
+    pub fn with_table(mut self, table: &str) -> Self {
+        self.table = table.into();
+        self
+    }
+
+    /// Creates the table of the messages if it does not exist.
+    pub async fn initialize(&self) -> Result<(), MemoryError> {
+        let table = &self.table;
+        sqlx::query(&formatdoc! {"
+            CREATE TABLE IF NOT EXISTS {table}
+            (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL,
+                message TEXT NOT NULL
+            );"
+        })
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(&format!(
+            "CREATE INDEX IF NOT EXISTS {table}_session_id ON {table} (session_id);"
+        ))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MessageStore for SqliteMessageStore {
+    async fn load(&self, session_id: &str) -> Result<Vec<Message>, MemoryError> {
+        let table = &self.table;
+        let rows: Vec<(String,)> = sqlx::query_as(&format!(
+            "SELECT message FROM {table} WHERE session_id = ? ORDER BY id"
+        ))
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|(message,)| serde_json::from_str(&message).map_err(Into::into))
+            .collect()
+    }
+
+    async fn append(&self, session_id: &str, message: &Message) -> Result<(), MemoryError> {
+        let table = &self.table;
+        sqlx::query(&format!(
+            "INSERT INTO {table} (session_id, message) VALUES (?, ?)"
+        ))
+        .bind(session_id)
+        .bind(serde_json::to_string(message)?)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn clear(&self, session_id: &str) -> Result<(), MemoryError> {
+        let table = &self.table;
+        sqlx::query(&format!("DELETE FROM {table} WHERE session_id = ?"))
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use crate::{
+        memory::{Memory, PersistentMemory},
+        schemas::ToolCall,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sqlite_message_store() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let store = SqliteMessageStore::new(pool.clone());
+        store.initialize().await.unwrap();
+
+        let mut memory = PersistentMemory::load(store, "session").await.unwrap();
+        memory.add_human_message("What is 2 + 2?".into());
+        memory.add_tool_call_message(vec![ToolCall::new(
+            "call_1",
+            "calculator",
+            serde_json::json!({ "expression": "2 + 2" }),
+        )]);
+        memory.add_tool_message(Some("call_1".into()), "4".into());
+        memory.flush().await.unwrap();
+
+        let store = SqliteMessageStore::new(pool);
+        let messages = store.load("session").await.unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(
+            messages[1].tool_calls.as_ref().unwrap()[0].name,
+            "calculator"
+        );
+        assert_eq!(messages[2].id.as_deref(), Some("call_1"));
+        assert!(store.load("other").await.unwrap().is_empty());
+    }
+}
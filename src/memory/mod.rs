@@ -2,6 +2,9 @@
 mod memory;
 pub use memory::*;
 
+mod async_memory;
+pub use async_memory::*;
+
 mod dummy_memory;
 pub use dummy_memory::*;
 
@@ -10,3 +13,24 @@ pub use simple_memory::*;
 
 mod window_buffer;
 pub use window_buffer::*;
+
+mod error;
+pub use error::*;
+
+mod message_store;
+pub use message_store::*;
+
+mod persistent_memory;
+pub use persistent_memory::*;
+
+mod summary_memory;
+pub use summary_memory::*;
+
+mod summary_buffer_memory;
+pub use summary_buffer_memory::*;
+
+mod vector_store_memory;
+pub use vector_store_memory::*;
+
+mod entity_memory;
+pub use entity_memory::*;
@@ -0,0 +1,340 @@
+use std::sync::{Arc, RwLock as SyncRwLock};
+
+use async_trait::async_trait;
+use tokio::sync::{mpsc, oneshot, RwLock};
+
+use crate::schemas::Message;
+
+use super::{AsyncMemory, Memory, MemoryError, MessageStore};
+
+type Ack = oneshot::Sender<Result<(), MemoryError>>;
+
+enum Operation {
+    /// Appends a message, reporting the result to the sender if any.
+    Append(Message, Option<Ack>),
+    /// Clears the session, reporting the result to the sender if any.
+    Clear(Option<Ack>),
+    Flush(Ack),
+}
+
+/// A memory persisting the messages of a session in a [`MessageStore`].
+///
+/// The messages are loaded once when the memory is created, and kept in memory. New messages
+/// are written to the store in the background, in order, so that [`Memory`] stays synchronous;
+/// use [`PersistentMemory::flush`] to wait for them to be written and to know whether they were.
+///
+/// As an [`AsyncMemory`], each change is awaited until it is written, and the error of that
+/// write, if any, is returned.
+pub struct PersistentMemory {
+    session_id: String,
+    messages: SyncRwLock<Vec<Message>>,
+    sender: mpsc::UnboundedSender<Operation>,
+}
+
+impl PersistentMemory {
+    /// Loads the messages of the session from `store`.
+    pub async fn load<S: MessageStore + 'static>(
+        store: S,
+        session_id: impl Into<String>,
+    ) -> Result<Self, MemoryError> {
+        let session_id = session_id.into();
+        let messages = store.load(&session_id).await?;
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(write_operations(store, session_id.clone(), receiver));
+
+        Ok(Self {
+            session_id,
+            messages: SyncRwLock::new(messages),
+            sender,
+        })
+    }
+
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Waits until every change made so far is written to the store. Returns the first error
+    /// of the writes made through [`Memory`] since the previous flush, if any.
+    pub async fn flush(&self) -> Result<(), MemoryError> {
+        let (done, written) = oneshot::channel();
+        self.sender
+            .send(Operation::Flush(done))
+            .map_err(|_| self.writer_stopped())?;
+        written.await.map_err(|_| self.writer_stopped())?
+    }
+
+    /// Waits for the result of a write queued through [`AsyncMemory`].
+    async fn written(
+        &self,
+        written: oneshot::Receiver<Result<(), MemoryError>>,
+    ) -> Result<(), MemoryError> {
+        written.await.map_err(|_| self.writer_stopped())?
+    }
+
+    fn writer_stopped(&self) -> MemoryError {
+        MemoryError::OtherError(format!(
+            "Failed to persist the messages of session {}: the writer task stopped",
+            self.session_id
+        ))
+    }
+
+    fn send(&self, operation: Operation) {
+        if self.sender.send(operation).is_err() {
+            log::error!(
+                "Failed to persist the messages of session {}: the writer task stopped",
+                self.session_id
+            );
+        }
+    }
+}
+
+async fn write_operations<S: MessageStore>(
+    store: S,
+    session_id: String,
+    mut receiver: mpsc::UnboundedReceiver<Operation>,
+) {
+    // The first error of the unacknowledged writes since the last flush, reported by the next
+    // flush.
+    let mut failure: Option<MemoryError> = None;
+    while let Some(operation) = receiver.recv().await {
+        let (result, ack) = match operation {
+            Operation::Append(message, ack) => (store.append(&session_id, &message).await, ack),
+            Operation::Clear(ack) => (store.clear(&session_id).await, ack),
+            Operation::Flush(done) => {
+                let _ = done.send(failure.take().map_or(Ok(()), Err));
+                continue;
+            }
+        };
+        if let Err(e) = &result {
+            log::error!("Failed to persist the messages of session {session_id}: {e}");
+        }
+        match (result, ack) {
+            (result, Some(ack)) => {
+                let _ = ack.send(result);
+            }
+            (Err(e), None) => {
+                failure.get_or_insert(e);
+            }
+            (Ok(()), None) => {}
+        }
+    }
+}
+
+impl From<PersistentMemory> for Arc<dyn Memory> {
+    fn from(val: PersistentMemory) -> Self {
+        Arc::new(val)
+    }
+}
+
+impl From<PersistentMemory> for Arc<RwLock<dyn Memory>> {
+    fn from(val: PersistentMemory) -> Self {
+        Arc::new(RwLock::new(val))
+    }
+}
+
+impl From<PersistentMemory> for Arc<dyn AsyncMemory> {
+    fn from(val: PersistentMemory) -> Self {
+        Arc::new(val)
+    }
+}
+
+impl Memory for PersistentMemory {
+    fn messages(&self) -> Vec<Message> {
+        self.messages
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn add_message(&mut self, message: Message) {
+        self.send(Operation::Append(message.clone(), None));
+        self.messages
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(message);
+    }
+
+    fn clear(&mut self) {
+        self.send(Operation::Clear(None));
+        self.messages
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    fn to_string(&self) -> String {
+        Memory::messages(self)
+            .iter()
+            .map(|msg| msg.to_string())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+#[async_trait]
+impl AsyncMemory for PersistentMemory {
+    async fn messages(&self) -> Result<Vec<Message>, MemoryError> {
+        Ok(Memory::messages(self))
+    }
+
+    async fn add_message(&self, message: Message) -> Result<(), MemoryError> {
+        let (ack, written) = oneshot::channel();
+        {
+            // The operation is queued under the lock so that the store and the cache agree on
+            // the order of the messages.
+            let mut messages = self.messages.write().unwrap_or_else(|e| e.into_inner());
+            self.send(Operation::Append(message.clone(), Some(ack)));
+            messages.push(message);
+        }
+        self.written(written).await
+    }
+
+    async fn clear(&self) -> Result<(), MemoryError> {
+        let (ack, written) = oneshot::channel();
+        {
+            let mut messages = self.messages.write().unwrap_or_else(|e| e.into_inner());
+            self.send(Operation::Clear(Some(ack)));
+            messages.clear();
+        }
+        self.written(written).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+
+    use crate::{
+        memory::JsonlMessageStore,
+        schemas::{ImageContent, MessageType, ToolCall},
+    };
+
+    // `AsyncMemory` is not imported, its methods would shadow the ones of `Memory`.
+    use super::{Arc, Memory, MemoryError, Message, MessageStore, PersistentMemory};
+
+    /// Fails to write messages containing "fail".
+    struct FailingStore;
+
+    #[async_trait]
+    impl MessageStore for FailingStore {
+        async fn load(&self, _session_id: &str) -> Result<Vec<Message>, MemoryError> {
+            Ok(Vec::new())
+        }
+
+        async fn append(&self, _session_id: &str, message: &Message) -> Result<(), MemoryError> {
+            if message.content.contains("fail") {
+                return Err(MemoryError::OtherError("disk full".into()));
+            }
+            Ok(())
+        }
+
+        async fn clear(&self, _session_id: &str) -> Result<(), MemoryError> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_flush_reports_write_errors() {
+        let mut memory = PersistentMemory::load(FailingStore, "session")
+            .await
+            .unwrap();
+        memory.add_human_message("fail".into());
+        memory.add_human_message("ok".into());
+        assert!(memory.flush().await.is_err());
+
+        memory.add_human_message("ok".into());
+        memory.flush().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_async_writes_report_their_own_result() {
+        let mut memory = PersistentMemory::load(FailingStore, "session")
+            .await
+            .unwrap();
+        memory.add_human_message("fail".into());
+
+        // The failed write above is left for the next flush.
+        super::AsyncMemory::add_message(&memory, Message::new_human_message("ok"))
+            .await
+            .unwrap();
+        assert!(
+            super::AsyncMemory::add_message(&memory, Message::new_human_message("fail"))
+                .await
+                .is_err()
+        );
+        assert!(memory.flush().await.is_err());
+        memory.flush().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_async_persistent_memory() {
+        let directory = std::env::temp_dir().join(format!("memory-{}", uuid::Uuid::new_v4()));
+        let memory: Arc<dyn super::AsyncMemory> =
+            PersistentMemory::load(JsonlMessageStore::new(&directory), "session")
+                .await
+                .unwrap()
+                .into();
+        memory
+            .update("What is 2 + 2?".into(), Vec::new(), "4".into())
+            .await
+            .unwrap();
+        assert_eq!(memory.messages().await.unwrap().len(), 2);
+
+        // The messages are written when the calls return.
+        let store = JsonlMessageStore::new(&directory);
+        assert_eq!(store.load("session").await.unwrap().len(), 2);
+
+        let failing: Arc<dyn super::AsyncMemory> = PersistentMemory::load(FailingStore, "session")
+            .await
+            .unwrap()
+            .into();
+        assert!(failing.add_human_message("fail".into()).await.is_err());
+        failing.add_human_message("ok".into()).await.unwrap();
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_jsonl_memory_survives_restart() {
+        let directory = std::env::temp_dir().join(format!("memory-{}", uuid::Uuid::new_v4()));
+
+        let mut memory = PersistentMemory::load(JsonlMessageStore::new(&directory), "session")
+            .await
+            .unwrap();
+        memory.add_message(
+            Message::new_human_message("What is in this image?")
+                .with_images(vec![ImageContent::from("https://example.com/cat.png")]),
+        );
+        memory.add_tool_call_message(vec![ToolCall::new(
+            "call_1",
+            "describe",
+            serde_json::json!({ "url": "https://example.com/cat.png" }),
+        )]);
+        memory.add_tool_message(Some("call_1".into()), "A cat".into());
+        memory.flush().await.unwrap();
+
+        let mut memory = PersistentMemory::load(JsonlMessageStore::new(&directory), "session")
+            .await
+            .unwrap();
+        let messages = memory.messages();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(
+            messages[0].images.as_ref().unwrap()[0].image_url,
+            "https://example.com/cat.png"
+        );
+        let tool_call = &messages[1].tool_calls.as_ref().unwrap()[0];
+        assert_eq!(tool_call.id, "call_1");
+        assert_eq!(tool_call.arguments["url"], "https://example.com/cat.png");
+        assert_eq!(messages[2].message_type, MessageType::Tool);
+        assert_eq!(messages[2].id.as_deref(), Some("call_1"));
+
+        memory.clear();
+        memory.flush().await.unwrap();
+        let store = JsonlMessageStore::new(&directory);
+        assert!(store.load("session").await.unwrap().is_empty());
+        assert!(store.load("../session").await.is_err());
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
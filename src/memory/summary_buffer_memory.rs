@@ -0,0 +1,228 @@
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use crate::{
+    llm::LLM,
+    schemas::{Message, MessageType},
+};
+
+use super::{
+    summary_memory::{summarize, summary_message},
+    AsyncMemory, MemoryError, DEFAULT_SUMMARY_TEMPLATE,
+};
+
+#[derive(Default)]
+struct SummaryBufferState {
+    summary: String,
+    messages: Vec<Message>,
+}
+
+/// A memory keeping the most recent messages, up to `max_token_limit` tokens, and a running
+/// summary of the older ones, written by an [`LLM`].
+///
+/// Messages are evicted oldest first. A tool call message is always evicted together with the
+/// tool messages answering it, so that the buffer never starts with an orphan tool message: no
+/// message is evicted while some calls of the last tool call message are unanswered.
+pub struct SummaryBufferMemory {
+    llm: Box<dyn LLM>,
+    template: String,
+    max_token_limit: usize,
+    state: Mutex<SummaryBufferState>,
+}
+
+impl SummaryBufferMemory {
+    pub fn new(llm: impl Into<Box<dyn LLM>>) -> Self {
+        Self {
+            llm: llm.into(),
+            template: DEFAULT_SUMMARY_TEMPLATE.into(),
+            max_token_limit: 2000,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Sets the maximum number of tokens of the buffered messages, counted with `cl100k_base`.
+    pub fn with_max_token_limit(mut self, max_token_limit: usize) -> Self {
+        self.max_token_limit = max_token_limit;
+        self
+    }
+
+    /// Sets the prompt used to summarize, with `{summary}` and `{new_lines}` placeholders.
+    pub fn with_template(mut self, template: impl Into<String>) -> Self {
+        self.template = template.into();
+        self
+    }
+
+    pub async fn summary(&self) -> String {
+        self.state.lock().await.summary.clone()
+    }
+
+    fn count_tokens(messages: &[Message]) -> usize {
+        let bpe = tiktoken_rs::cl100k_base_singleton();
+        messages
+            .iter()
+            .map(|m| bpe.encode_ordinary(&m.to_string()).len())
+            .sum()
+    }
+
+    /// Returns whether the last tool call message still waits for the answers to some of its
+    /// calls. It stops waiting when a message other than a tool message follows it.
+    fn awaits_tool_answers(messages: &[Message]) -> bool {
+        let Some(position) = messages
+            .iter()
+            .rposition(|m| m.tool_calls.as_ref().is_some_and(|c| !c.is_empty()))
+        else {
+            return false;
+        };
+        let answers = &messages[position + 1..];
+        if answers.iter().any(|m| m.message_type != MessageType::Tool) {
+            return false;
+        }
+        let answered: HashSet<&str> = answers.iter().filter_map(|m| m.id.as_deref()).collect();
+        messages[position]
+            .tool_calls
+            .iter()
+            .flatten()
+            .any(|call| !answered.contains(call.id.as_str()))
+    }
+
+    /// Returns the number of messages to evict from the start of `messages` to fit in the limit,
+    /// without separating tool messages from their call.
+    fn eviction_len(&self, messages: &[Message]) -> usize {
+        let mut evicted = 0;
+        while evicted < messages.len()
+            && Self::count_tokens(&messages[evicted..]) > self.max_token_limit
+        {
+            evicted += 1;
+            while evicted < messages.len() && messages[evicted].message_type == MessageType::Tool {
+                evicted += 1;
+            }
+        }
+        evicted
+    }
+}
+
+#[async_trait]
+impl AsyncMemory for SummaryBufferMemory {
+    async fn messages(&self) -> Result<Vec<Message>, MemoryError> {
+        let state = self.state.lock().await;
+        let mut messages = Vec::with_capacity(state.messages.len() + 1);
+        if !state.summary.is_empty() {
+            messages.push(summary_message(&state.summary));
+        }
+        messages.extend(state.messages.iter().cloned());
+        Ok(messages)
+    }
+
+    async fn add_message(&self, message: Message) -> Result<(), MemoryError> {
+        let mut state = self.state.lock().await;
+        state.messages.push(message);
+
+        // A tool call is evicted with its answers, wait for all of them before evicting it.
+        if Self::awaits_tool_answers(&state.messages) {
+            return Ok(());
+        }
+
+        let evicted = self.eviction_len(&state.messages);
+        if evicted > 0 {
+            let summary = summarize(
+                self.llm.as_ref(),
+                &self.template,
+                &state.summary,
+                &state.messages[..evicted],
+            )
+            .await?;
+            state.summary = summary;
+            state.messages.drain(..evicted);
+        }
+        Ok(())
+    }
+
+    async fn clear(&self) -> Result<(), MemoryError> {
+        *self.state.lock().await = SummaryBufferState::default();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{memory::summary_memory::tests::counting_summarizer, schemas::ToolCall};
+
+    use super::*;
+
+    fn tokens(message: &Message) -> usize {
+        SummaryBufferMemory::count_tokens(std::slice::from_ref(message))
+    }
+
+    #[tokio::test]
+    async fn test_summary_buffer_evicts_oldest_messages() {
+        let message = Message::new_human_message("one two three");
+        let memory = SummaryBufferMemory::new(counting_summarizer())
+            .with_max_token_limit(2 * tokens(&message));
+
+        for _ in 0..3 {
+            memory.add_message(message.clone()).await.unwrap();
+        }
+
+        assert_eq!(memory.summary().await, "[1]");
+        let messages = memory.messages().await.unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].message_type, MessageType::System);
+    }
+
+    #[tokio::test]
+    async fn test_summary_buffer_never_orphans_tool_messages() {
+        let call = Message::new_tool_call_message(vec![ToolCall::new(
+            "call_1",
+            "calculator",
+            serde_json::json!({ "expression": "2 + 2" }),
+        )]);
+        let result = Message::new_tool_message(Some("call_1"), "4");
+        let answer = Message::new_ai_message("2 + 2 is 4");
+        let memory = SummaryBufferMemory::new(counting_summarizer())
+            .with_max_token_limit(tokens(&result) + tokens(&answer));
+
+        memory
+            .add_human_message("What is 2 + 2?".into())
+            .await
+            .unwrap();
+        memory.add_message(call).await.unwrap();
+        memory.add_message(result).await.unwrap();
+        memory.add_message(answer).await.unwrap();
+
+        let messages = memory.messages().await.unwrap();
+        assert_eq!(messages[0].message_type, MessageType::System);
+        assert_ne!(messages[1].message_type, MessageType::Tool);
+        assert_eq!(messages.last().unwrap().content, "2 + 2 is 4");
+    }
+
+    #[tokio::test]
+    async fn test_summary_buffer_waits_for_every_tool_answer() {
+        let call = Message::new_tool_call_message(vec![
+            ToolCall::new("call_1", "weather", serde_json::json!({ "city": "Paris" })),
+            ToolCall::new("call_2", "weather", serde_json::json!({ "city": "Rome" })),
+        ]);
+        let first = Message::new_tool_message(Some("call_1"), "Sunny in Paris");
+        let second = Message::new_tool_message(Some("call_2"), "Rainy in Rome");
+        let question = Message::new_human_message("What is the weather in Paris and Rome?");
+        let memory =
+            SummaryBufferMemory::new(counting_summarizer()).with_max_token_limit(tokens(&question));
+
+        memory.add_message(question).await.unwrap();
+        memory.add_message(call).await.unwrap();
+        memory.add_message(first).await.unwrap();
+
+        // Over the limit, but the second answer is still expected.
+        assert!(memory.summary().await.is_empty());
+        assert_eq!(memory.messages().await.unwrap().len(), 3);
+
+        memory.add_message(second).await.unwrap();
+
+        let messages = memory.messages().await.unwrap();
+        assert_eq!(messages[0].message_type, MessageType::System);
+        assert!(messages[1..]
+            .iter()
+            .all(|m| m.message_type != MessageType::Tool));
+    }
+}
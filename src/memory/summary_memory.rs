@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use indoc::{formatdoc, indoc};
+use tokio::sync::Mutex;
+
+use crate::{
+    llm::LLM,
+    schemas::{Message, MessageType},
+    template::MessageTemplate,
+};
+
+use super::{AsyncMemory, MemoryError};
+
+pub const DEFAULT_SUMMARY_TEMPLATE: &str = indoc! {"
+    Progressively summarize the lines of conversation provided, adding onto the previous summary
+    and returning a new summary. Keep every fact, name and decision that may matter later in the
+    conversation.
+
+    Current summary:
+    {summary}
+
+    New lines of conversation:
+    {new_lines}
+
+    New summary:"};
+
+/// Returns the summary of `messages`, continuing `summary`.
+pub(super) async fn summarize(
+    llm: &dyn LLM,
+    template: &str,
+    summary: &str,
+    messages: &[Message],
+) -> Result<String, MemoryError> {
+    let new_lines = messages
+        .iter()
+        .map(|m| m.to_string())
+        .collect::<Vec<String>>()
+        .join("\n");
+    let prompt =
+        MessageTemplate::from_fstring(MessageType::Human, template).format(&HashMap::from([
+            ("summary", summary.into()),
+            ("new_lines", new_lines.into()),
+        ]))?;
+
+    Ok(llm.invoke(&prompt.content).await?.trim().to_string())
+}
+
+/// Returns the system message giving the summary to the model.
+pub(super) fn summary_message(summary: &str) -> Message {
+    Message::new_system_message(formatdoc! {"
+        Summary of the conversation so far:
+        {summary}"
+    })
+}
+
+#[derive(Default)]
+struct SummaryState {
+    summary: String,
+    /// Messages not folded into the summary yet.
+    pending: Vec<Message>,
+}
+
+/// A memory keeping only a running summary of the conversation, written by an [`LLM`].
+///
+/// New messages are folded into the summary when the messages are read, so that a whole
+/// exchange is summarized with a single call.
+pub struct ConversationSummaryMemory {
+    llm: Box<dyn LLM>,
+    template: String,
+    state: Mutex<SummaryState>,
+}
+
+impl ConversationSummaryMemory {
+    pub fn new(llm: impl Into<Box<dyn LLM>>) -> Self {
+        Self {
+            llm: llm.into(),
+            template: DEFAULT_SUMMARY_TEMPLATE.into(),
+            state: Mutex::default(),
+        }
+    }
+
+    /// Sets the prompt used to summarize, with `{summary}` and `{new_lines}` placeholders.
+    pub fn with_template(mut self, template: impl Into<String>) -> Self {
+        self.template = template.into();
+        self
+    }
+
+    /// Sets the summary to continue from, such as one saved from a previous session.
+    pub fn with_summary(mut self, summary: impl Into<String>) -> Self {
+        self.state.get_mut().summary = summary.into();
+        self
+    }
+
+    /// Returns the summary, folding the pending messages into it.
+    pub async fn summary(&self) -> Result<String, MemoryError> {
+        let mut state = self.state.lock().await;
+        if !state.pending.is_empty() {
+            state.summary = summarize(
+                self.llm.as_ref(),
+                &self.template,
+                &state.summary,
+                &state.pending,
+            )
+            .await?;
+            state.pending.clear();
+        }
+        Ok(state.summary.clone())
+    }
+}
+
+#[async_trait]
+impl AsyncMemory for ConversationSummaryMemory {
+    async fn messages(&self) -> Result<Vec<Message>, MemoryError> {
+        let summary = self.summary().await?;
+        if summary.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(vec![summary_message(&summary)])
+    }
+
+    async fn add_message(&self, message: Message) -> Result<(), MemoryError> {
+        self.state.lock().await.pending.push(message);
+        Ok(())
+    }
+
+    async fn clear(&self) -> Result<(), MemoryError> {
+        *self.state.lock().await = SummaryState::default();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use crate::llm::fake::FakeLLM;
+
+    use super::*;
+
+    /// Summarizes by appending the number of new lines to the previous summary.
+    pub fn counting_summarizer() -> FakeLLM {
+        FakeLLM::new(|prompt| {
+            let summary = prompt
+                .split("Current summary:\n")
+                .nth(1)
+                .and_then(|rest| rest.split("\n\nNew lines").next())
+                .unwrap_or_default();
+            let new_lines = prompt
+                .split("New lines of conversation:\n")
+                .nth(1)
+                .and_then(|rest| rest.split("\n\nNew summary:").next())
+                .unwrap_or_default();
+            let messages = new_lines.matches(": ").count();
+            format!("{summary}[{messages}]")
+        })
+    }
+
+    #[tokio::test]
+    async fn test_summary_memory() {
+        let memory = ConversationSummaryMemory::new(counting_summarizer()).with_summary("start");
+
+        memory.add_human_message("Hi".into()).await.unwrap();
+        memory.add_ai_message("Hello".into()).await.unwrap();
+        assert_eq!(memory.summary().await.unwrap(), "start[2]");
+
+        memory.add_human_message("Bye".into()).await.unwrap();
+        let messages = memory.messages().await.unwrap();
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].content.ends_with("start[2][1]"));
+
+        memory.clear().await.unwrap();
+        assert!(memory.messages().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,299 @@
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+use crate::{
+    schemas::{Document, Message, MessageType},
+    vectorstore::{MetadataFilter, VecStoreOptions, VectorStore},
+};
+
+use super::{AsyncMemory, MemoryError};
+
+pub const SESSION_ID_KEY: &str = "session_id";
+pub const TIMESTAMP_KEY: &str = "timestamp";
+pub const IMPORTANCE_KEY: &str = "importance";
+
+/// A long-term memory writing the conversation turns, or facts extracted from them, as
+/// [`Document`]s of a [`VectorStore`], and recalling the ones relevant to the current input.
+///
+/// Every document has the `session_id`, `timestamp` (in seconds since the Unix epoch) and
+/// `importance` metadata. With a session id, only the memories of the session are recalled. The
+/// `fetch_k` memories most similar to the query are weighted by their importance, and the
+/// `num_docs` first are recalled. The memories are recalled with
+/// [`AsyncMemory::relevant_messages`], which is used by the agents, or with
+/// [`VectorStoreRetrieverMemory::relevant_memories`] to fill a prompt placeholder.
+pub struct VectorStoreRetrieverMemory<F> {
+    vstore: Box<dyn VectorStore<Options = VecStoreOptions<F>>>,
+    num_docs: usize,
+    fetch_k: usize,
+    options: VecStoreOptions<F>,
+    session_id: Option<String>,
+    importance: f64,
+    /// The human message of the turn being written.
+    human_message: Mutex<Option<String>>,
+}
+
+impl<F> VectorStoreRetrieverMemory<F> {
+    pub fn new<V: Into<Box<dyn VectorStore<Options = VecStoreOptions<F>>>>>(
+        vstore: V,
+        num_docs: usize,
+    ) -> Self {
+        Self {
+            vstore: vstore.into(),
+            num_docs,
+            fetch_k: num_docs * 4,
+            options: VecStoreOptions::<F>::new(),
+            session_id: None,
+            importance: 1.0,
+            human_message: Mutex::new(None),
+        }
+    }
+
+    /// Sets the number of memories weighted by their importance, `4 * num_docs` by default.
+    pub fn with_fetch_k(mut self, fetch_k: usize) -> Self {
+        self.fetch_k = fetch_k;
+        self
+    }
+
+    pub fn with_options(mut self, options: VecStoreOptions<F>) -> Self {
+        self.options = options;
+        self.filter_session();
+        self
+    }
+
+    /// Writes the memories to the session, and recalls only the ones of the session.
+    pub fn with_session_id(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self.filter_session();
+        self
+    }
+
+    /// Adds the filter of the session to the metadata filter of the options.
+    fn filter_session(&mut self) {
+        let Some(session_id) = &self.session_id else {
+            return;
+        };
+        let session_filter = MetadataFilter::eq(SESSION_ID_KEY, session_id.as_str());
+        self.options.metadata_filter = Some(match self.options.metadata_filter.take() {
+            Some(filter) => filter.and(session_filter),
+            None => session_filter,
+        });
+    }
+
+    /// Sets the importance of the conversation turns, 1 by default.
+    pub fn with_importance(mut self, importance: f64) -> Self {
+        self.importance = importance;
+        self
+    }
+
+    fn metadata(&self, importance: f64) -> HashMap<String, Value> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        let mut metadata = HashMap::from([
+            (TIMESTAMP_KEY.to_string(), Value::from(timestamp)),
+            (IMPORTANCE_KEY.to_string(), Value::from(importance)),
+        ]);
+        if let Some(session_id) = &self.session_id {
+            metadata.insert(SESSION_ID_KEY.to_string(), session_id.as_str().into());
+        }
+        metadata
+    }
+
+    /// Writes a fact, such as one extracted from the conversation by an LLM.
+    pub async fn add_fact(
+        &self,
+        fact: impl Into<String>,
+        importance: f64,
+    ) -> Result<(), MemoryError>
+    where
+        F: Send + Sync,
+    {
+        let document = Document::new(fact).with_metadata(self.metadata(importance));
+        self.vstore
+            .add_documents(&[document], &self.options)
+            .await
+            .map_err(|e| MemoryError::OtherError(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Returns the memories relevant to `query`, most relevant first. The score of the
+    /// documents is their similarity weighted by their importance.
+    pub async fn relevant_documents(&self, query: &str) -> Result<Vec<Document>, MemoryError>
+    where
+        F: Send + Sync,
+    {
+        let mut documents = self
+            .vstore
+            .similarity_search(query, self.fetch_k.max(self.num_docs), &self.options)
+            .await
+            .map_err(|e| MemoryError::OtherError(e.to_string()))?;
+        for document in &mut documents {
+            let importance = document
+                .metadata
+                .get(IMPORTANCE_KEY)
+                .and_then(Value::as_f64)
+                .unwrap_or(1.0);
+            document.score *= importance;
+        }
+        documents.sort_by(|a, b| b.score.total_cmp(&a.score));
+        documents.truncate(self.num_docs);
+        Ok(documents)
+    }
+
+    /// Returns the memories relevant to `query` as text, to fill a prompt placeholder.
+    pub async fn relevant_memories(&self, query: &str) -> Result<String, MemoryError>
+    where
+        F: Send + Sync,
+    {
+        Ok(self
+            .relevant_documents(query)
+            .await?
+            .into_iter()
+            .map(|d| d.page_content)
+            .collect::<Vec<String>>()
+            .join("\n\n"))
+    }
+}
+
+#[async_trait]
+impl<F: Send + Sync> AsyncMemory for VectorStoreRetrieverMemory<F> {
+    /// Returns no messages, the memories are only recalled for a query.
+    async fn messages(&self) -> Result<Vec<Message>, MemoryError> {
+        Ok(Vec::new())
+    }
+
+    async fn relevant_messages(&self, query: &str) -> Result<Vec<Message>, MemoryError> {
+        let memories = self.relevant_memories(query).await?;
+        if memories.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(vec![Message::new_system_message(format!(
+            "Relevant pieces of previous conversations:\n{memories}"
+        ))])
+    }
+
+    /// Writes a document for every human message followed by an AI answer. Tool calls and tool
+    /// messages are not remembered.
+    async fn add_message(&self, message: Message) -> Result<(), MemoryError> {
+        let mut human_message = self.human_message.lock().await;
+        match message.message_type {
+            MessageType::Human => *human_message = Some(message.content),
+            MessageType::Ai if message.tool_calls.is_none() && !message.content.is_empty() => {
+                let turn = match human_message.take() {
+                    Some(human) => format!("Human: {human}\nAI: {}", message.content),
+                    None => format!("AI: {}", message.content),
+                };
+                let document = Document::new(turn).with_metadata(self.metadata(self.importance));
+                self.vstore
+                    .add_documents(&[document], &self.options)
+                    .await
+                    .map_err(|e| MemoryError::OtherError(e.to_string()))?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Forgets the turn being written. The documents already written stay in the store.
+    async fn clear(&self) -> Result<(), MemoryError> {
+        *self.human_message.lock().await = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        schemas::ToolCall,
+        vectorstore::{
+            memory::{tests::WordCountEmbedder, InMemoryStore},
+            DistanceMetric,
+        },
+    };
+
+    use super::*;
+
+    /// Returns a memory of the session recalling the memories sharing a word with the query.
+    fn memory(
+        vstore: impl Into<Box<dyn VectorStore<Options = VecStoreOptions<Value>>>>,
+        session_id: &str,
+    ) -> VectorStoreRetrieverMemory<Value> {
+        VectorStoreRetrieverMemory::new(vstore, 2)
+            .with_options(VecStoreOptions::new().with_score_threshold(0.1))
+            .with_session_id(session_id)
+    }
+
+    fn store() -> InMemoryStore {
+        InMemoryStore::new(Arc::new(WordCountEmbedder), DistanceMetric::Cosine)
+    }
+
+    #[tokio::test]
+    async fn test_vector_store_memory() {
+        let memory = memory(store(), "session");
+
+        memory
+            .add_human_message("My favorite pet is a cat".into())
+            .await
+            .unwrap();
+        memory
+            .add_tool_call_message(vec![ToolCall::new("1", "search", Value::Null)])
+            .await
+            .unwrap();
+        memory.add_ai_message("Noted!".into()).await.unwrap();
+        memory.add_fact("Luis has a fish", 0.5).await.unwrap();
+
+        let documents = memory.relevant_documents("cat").await.unwrap();
+        assert_eq!(documents.len(), 1);
+        assert_eq!(
+            documents[0].page_content,
+            "Human: My favorite pet is a cat\nAI: Noted!"
+        );
+        assert_eq!(documents[0].metadata[SESSION_ID_KEY], "session");
+        assert!(documents[0].metadata.contains_key(TIMESTAMP_KEY));
+
+        let messages = memory.relevant_messages("fish").await.unwrap();
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].content.ends_with("Luis has a fish"));
+        assert!(memory.relevant_messages("bird").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_vector_store_memory_sessions() {
+        let alice = memory(store(), "alice");
+        alice.add_fact("The cat is blue", 1.0).await.unwrap();
+
+        let bob = memory(alice.vstore, "bob");
+        bob.add_fact("The cat is red", 1.0).await.unwrap();
+        bob.add_fact("The cat is black", 1.0).await.unwrap();
+        bob.add_fact("The cat and the dog are never green", 3.0)
+            .await
+            .unwrap();
+
+        // The important memory is recalled first, although two memories are more similar to the
+        // query.
+        let documents = bob.relevant_documents("cat").await.unwrap();
+        assert_eq!(documents.len(), 2);
+        assert_eq!(
+            documents[0].page_content,
+            "The cat and the dog are never green"
+        );
+        assert!(
+            ["The cat is red", "The cat is black"].contains(&documents[1].page_content.as_str())
+        );
+
+        let alice = memory(bob.vstore, "alice");
+        let documents = alice.relevant_documents("cat").await.unwrap();
+        assert_eq!(documents.len(), 1);
+        assert_eq!(documents[0].page_content, "The cat is blue");
+    }
+}
@@ -0,0 +1,28 @@
+use reqwest::{Error as ReqwestError, StatusCode};
+use thiserror::Error;
+
+use crate::{llm::LLMError, template::TemplateError};
+
+#[derive(Error, Debug)]
+pub enum RerankerError {
+    #[error("Network request failed: {0}")]
+    RequestError(#[from] ReqwestError),
+
+    #[error("HTTP error: {status_code} {error_message}")]
+    HttpError {
+        status_code: StatusCode,
+        error_message: String,
+    },
+
+    #[error("LLM error: {0}")]
+    LLMError(#[from] LLMError),
+
+    #[error("Template error: {0}")]
+    TemplateError(#[from] TemplateError),
+
+    #[error("FastEmbed error: {0}")]
+    FastEmbedError(String),
+
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
+}
@@ -0,0 +1,108 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use fastembed::TextRerank;
+
+use crate::{
+    reranker::{sort_by_score, Reranker, RerankerError},
+    schemas::Document,
+};
+
+/// A reranker running a cross-encoder model of fastembed locally, `BAAI/bge-reranker-base` by
+/// default. The score of the documents is the logit of the model, not bounded.
+pub struct FastEmbedReranker {
+    model: Arc<Mutex<TextRerank>>,
+    batch_size: Option<usize>,
+    top_n: Option<usize>,
+}
+
+impl FastEmbedReranker {
+    pub fn try_new() -> Result<Self, RerankerError> {
+        Ok(TextRerank::try_new(Default::default())
+            .map_err(|e| RerankerError::FastEmbedError(e.to_string()))?
+            .into())
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = Some(batch_size);
+        self
+    }
+
+    /// Keeps only the `top_n` most relevant documents.
+    pub fn with_top_n(mut self, top_n: usize) -> Self {
+        self.top_n = Some(top_n);
+        self
+    }
+}
+
+impl From<TextRerank> for FastEmbedReranker {
+    fn from(model: TextRerank) -> Self {
+        Self {
+            model: Arc::new(Mutex::new(model)),
+            batch_size: None,
+            top_n: None,
+        }
+    }
+}
+
+#[async_trait]
+impl Reranker for FastEmbedReranker {
+    async fn rerank(
+        &self,
+        query: &str,
+        docs: Vec<Document>,
+    ) -> Result<Vec<Document>, RerankerError> {
+        if docs.is_empty() {
+            return Ok(docs);
+        }
+        let model = self.model.clone();
+        let query = query.to_string();
+        let texts: Vec<String> = docs.iter().map(|doc| doc.page_content.clone()).collect();
+        let batch_size = self.batch_size;
+        // The inference is blocking, it runs outside of the async executor.
+        let results = tokio::task::spawn_blocking(move || {
+            model
+                .lock()
+                .map_err(|e| RerankerError::FastEmbedError(e.to_string()))?
+                .rerank(query, texts, false, batch_size)
+                .map_err(|e| RerankerError::FastEmbedError(e.to_string()))
+        })
+        .await
+        .map_err(|e| RerankerError::FastEmbedError(e.to_string()))??;
+
+        let mut docs: Vec<Option<Document>> = docs.into_iter().map(Some).collect();
+        let mut reranked: Vec<Document> = results
+            .into_iter()
+            .filter_map(|result| {
+                docs.get_mut(result.index)?
+                    .take()
+                    .map(|doc| doc.with_score(result.score as f64))
+            })
+            .collect();
+        sort_by_score(&mut reranked, self.top_n);
+        Ok(reranked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_fastembed_reranker() {
+        let reranker = FastEmbedReranker::try_new().unwrap().with_top_n(1);
+        let docs = reranker
+            .rerank(
+                "What does the cat do?",
+                vec![
+                    Document::new("The dog barks"),
+                    Document::new("The cat sleeps"),
+                ],
+            )
+            .await
+            .unwrap();
+        assert_eq!(docs.len(), 1);
+        assert_eq!(docs[0].page_content, "The cat sleeps");
+    }
+}
@@ -0,0 +1,194 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::schemas::Document;
+
+use super::{sort_by_score, Reranker, RerankerError};
+
+#[derive(Deserialize)]
+struct RerankResponse {
+    results: Vec<RerankResult>,
+}
+
+#[derive(Deserialize)]
+struct RerankResult {
+    index: usize,
+    relevance_score: f64,
+}
+
+/// A reranker calling an HTTP endpoint compatible with the rerank APIs of Cohere and Jina: a
+/// `POST` of `{model, query, documents, top_n}` answered with `{results: [{index,
+/// relevance_score}]}`.
+///
+/// # Usage
+/// ```rust,ignore
+/// let reranker = HttpReranker::cohere().with_top_n(5);
+/// let docs = reranker.rerank("How do I reset my password?", docs).await?;
+/// ```
+#[derive(Clone)]
+pub struct HttpReranker {
+    client: Client,
+    url: String,
+    model: String,
+    api_key: Option<String>,
+    top_n: Option<usize>,
+}
+
+impl HttpReranker {
+    pub fn new<U: Into<String>, M: Into<String>>(url: U, model: M) -> Self {
+        Self {
+            client: Client::new(),
+            url: url.into(),
+            model: model.into(),
+            api_key: None,
+            top_n: None,
+        }
+    }
+
+    /// The rerank API of Cohere, with the API key of the `COHERE_API_KEY` environment variable.
+    pub fn cohere() -> Self {
+        let reranker = Self::new("https://api.cohere.com/v2/rerank", "rerank-v3.5");
+        match std::env::var("COHERE_API_KEY") {
+            Ok(api_key) => reranker.with_api_key(api_key),
+            Err(_) => reranker,
+        }
+    }
+
+    /// The rerank API of Jina, with the API key of the `JINA_API_KEY` environment variable.
+    pub fn jina() -> Self {
+        let reranker = Self::new(
+            "https://api.jina.ai/v1/rerank",
+            "jina-reranker-v2-base-multilingual",
+        );
+        match std::env::var("JINA_API_KEY") {
+            Ok(api_key) => reranker.with_api_key(api_key),
+            Err(_) => reranker,
+        }
+    }
+
+    pub fn with_model<S: Into<String>>(mut self, model: S) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// Sets the API key, sent as a bearer token.
+    pub fn with_api_key<S: Into<String>>(mut self, api_key: S) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Keeps only the `top_n` most relevant documents.
+    pub fn with_top_n(mut self, top_n: usize) -> Self {
+        self.top_n = Some(top_n);
+        self
+    }
+}
+
+#[async_trait]
+impl Reranker for HttpReranker {
+    async fn rerank(
+        &self,
+        query: &str,
+        docs: Vec<Document>,
+    ) -> Result<Vec<Document>, RerankerError> {
+        if docs.is_empty() {
+            return Ok(docs);
+        }
+        let texts: Vec<&str> = docs.iter().map(|doc| doc.page_content.as_str()).collect();
+        let body = json!({
+            "model": self.model,
+            "query": query,
+            "documents": texts,
+            "top_n": self.top_n.unwrap_or(docs.len()).min(docs.len()),
+        });
+
+        let mut request = self.client.post(&self.url).json(&body);
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        let response = request.send().await?;
+        let status_code = response.status();
+        if !status_code.is_success() {
+            return Err(RerankerError::HttpError {
+                status_code,
+                error_message: response.text().await?,
+            });
+        }
+        let response: RerankResponse = response.json().await?;
+
+        let mut docs: Vec<Option<Document>> = docs.into_iter().map(Some).collect();
+        let mut reranked = response
+            .results
+            .into_iter()
+            .map(|result| {
+                docs.get_mut(result.index)
+                    .and_then(Option::take)
+                    .map(|doc| doc.with_score(result.relevance_score))
+                    .ok_or_else(|| {
+                        RerankerError::InvalidResponse(format!(
+                            "Unknown or repeated document index {}",
+                            result.index
+                        ))
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        sort_by_score(&mut reranked, self.top_n);
+        Ok(reranked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::Matcher;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_http_reranker() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/rerank")
+            .match_header("authorization", "Bearer key")
+            .match_body(Matcher::PartialJson(json!({
+                "model": "reranker",
+                "query": "cat",
+                "documents": ["The dog barks", "The cat sleeps", "The fish swims"],
+                "top_n": 2,
+            })))
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "results": [
+                        { "index": 1, "relevance_score": 0.9 },
+                        { "index": 0, "relevance_score": 0.2 },
+                    ]
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let reranker = HttpReranker::new(format!("{}/v1/rerank", server.url()), "reranker")
+            .with_api_key("key")
+            .with_top_n(2);
+        let docs = reranker
+            .rerank(
+                "cat",
+                vec![
+                    Document::new("The dog barks"),
+                    Document::new("The cat sleeps"),
+                    Document::new("The fish swims"),
+                ],
+            )
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(docs.len(), 2);
+        assert_eq!(docs[0].page_content, "The cat sleeps");
+        assert_eq!(docs[0].score, 0.9);
+        assert_eq!(docs[1].page_content, "The dog barks");
+    }
+}
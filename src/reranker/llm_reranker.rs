@@ -0,0 +1,169 @@
+use std::{collections::HashMap, sync::LazyLock};
+
+use async_trait::async_trait;
+use futures::future::try_join_all;
+use indoc::indoc;
+use regex::Regex;
+
+use crate::{
+    llm::LLM,
+    schemas::{Document, MessageType},
+    template::MessageTemplate,
+};
+
+use super::{sort_by_score, Reranker, RerankerError};
+
+pub const DEFAULT_RERANK_TEMPLATE: &str = indoc! {"
+    Rate how relevant the following document is to the question, from 0 (not relevant at all)
+    to 10 (answers the question). Answer with the number only.
+
+    > Question: {question}
+    > Document:
+    >>>
+    {context}
+    >>>
+    > Relevance (0-10):"};
+
+static OUT_OF_TEN_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(\d+(?:\.\d+)?)\s*/\s*10\b").expect("Static regex is valid"));
+static NUMBER_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\d+(?:\.\d+)?").expect("Static regex is valid"));
+
+/// Returns the rating of the answer of the LLM: the number written out of 10, such as `8/10`, or
+/// else the last number, which follows any restatement of the scale.
+fn parse_rating(answer: &str) -> Option<f64> {
+    let rating = match OUT_OF_TEN_RE.captures(answer) {
+        Some(captures) => captures.get(1)?.as_str(),
+        None => NUMBER_RE.find_iter(answer).last()?.as_str(),
+    };
+    rating.parse().ok()
+}
+
+/// A reranker asking an [`LLM`] to rate the relevance of each document to the query. The score
+/// of the documents is their rating divided by 10, between 0 and 1, and 0 when the answer has no
+/// rating.
+///
+/// # Usage
+/// ```rust,ignore
+/// let reranker = LLMReranker::new(OpenAI::default()).with_top_n(3);
+/// let docs = reranker.rerank("How do I reset my password?", docs).await?;
+/// ```
+pub struct LLMReranker {
+    llm: Box<dyn LLM>,
+    template: MessageTemplate,
+    top_n: Option<usize>,
+}
+
+impl LLMReranker {
+    pub fn new<L: Into<Box<dyn LLM>>>(llm: L) -> Self {
+        Self {
+            llm: llm.into(),
+            template: MessageTemplate::from_fstring(MessageType::Human, DEFAULT_RERANK_TEMPLATE),
+            top_n: None,
+        }
+    }
+
+    /// Sets the prompt, with `{question}` and `{context}` placeholders. The LLM must answer with
+    /// a rating from 0 to 10.
+    pub fn with_template<S: Into<String>>(mut self, template: S) -> Self {
+        self.template = MessageTemplate::from_fstring(MessageType::Human, template);
+        self
+    }
+
+    /// Keeps only the `top_n` most relevant documents.
+    pub fn with_top_n(mut self, top_n: usize) -> Self {
+        self.top_n = Some(top_n);
+        self
+    }
+}
+
+#[async_trait]
+impl Reranker for LLMReranker {
+    async fn rerank(
+        &self,
+        query: &str,
+        docs: Vec<Document>,
+    ) -> Result<Vec<Document>, RerankerError> {
+        let prompts = docs
+            .iter()
+            .map(|doc| {
+                self.template.format(&HashMap::from([
+                    ("question", query.into()),
+                    ("context", doc.page_content.as_str().into()),
+                ]))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let answers = try_join_all(
+            prompts
+                .iter()
+                .map(|prompt| self.llm.invoke(&prompt.content)),
+        )
+        .await?;
+
+        let mut docs: Vec<Document> = docs
+            .into_iter()
+            .zip(answers)
+            .map(|(doc, answer)| match parse_rating(&answer) {
+                Some(rating) => doc.with_score((rating / 10.0).clamp(0.0, 1.0)),
+                None => {
+                    log::warn!("Expected a rating, got: {answer}");
+                    doc.with_score(0.0)
+                }
+            })
+            .collect();
+        sort_by_score(&mut docs, self.top_n);
+        Ok(docs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::llm::fake::FakeLLM;
+
+    use super::*;
+
+    /// Rates a document by the number of times it says "cat", and doesn't rate the dogs.
+    fn cat_judge() -> FakeLLM {
+        FakeLLM::new(|prompt| {
+            let document = prompt.split(">>>").nth(1).unwrap_or_default();
+            if document.contains("dog") {
+                return "I can't rate dogs.".into();
+            }
+            format!("On a scale of 0-10: {}.", document.matches("cat").count())
+        })
+    }
+
+    #[test]
+    fn test_parse_rating() {
+        assert_eq!(parse_rating("7"), Some(7.0));
+        assert_eq!(parse_rating("Relevance: 8.5/10."), Some(8.5));
+        assert_eq!(parse_rating("On a scale of 0-10: 7"), Some(7.0));
+        assert_eq!(parse_rating("not relevant"), None);
+    }
+
+    #[tokio::test]
+    async fn test_llm_reranker() {
+        let reranker = LLMReranker::new(cat_judge()).with_top_n(2);
+        let docs = reranker
+            .rerank(
+                "cat",
+                vec![
+                    Document::new("The dog barks"),
+                    Document::new("The cat chases the cat"),
+                    Document::new("The cat sleeps"),
+                ],
+            )
+            .await
+            .unwrap();
+
+        let texts: Vec<&str> = docs.iter().map(|d| d.page_content.as_str()).collect();
+        assert_eq!(texts, ["The cat chases the cat", "The cat sleeps"]);
+        assert_eq!(docs[0].score, 0.2);
+
+        let docs = reranker
+            .rerank("dog", vec![Document::new("The dog barks")])
+            .await
+            .unwrap();
+        assert_eq!(docs[0].score, 0.0);
+    }
+}
@@ -0,0 +1,16 @@
+mod error;
+pub use error::*;
+
+pub mod reranker_trait;
+pub use reranker_trait::*;
+
+mod http;
+pub use http::*;
+
+mod llm_reranker;
+pub use llm_reranker::*;
+
+#[cfg(feature = "fastembed")]
+mod fastembed;
+#[cfg(feature = "fastembed")]
+pub use fastembed::*;
@@ -0,0 +1,35 @@
+use async_trait::async_trait;
+
+use crate::schemas::Document;
+
+use super::RerankerError;
+
+/// Orders documents by their relevance to a query, as judged by a model reading the query and
+/// each document together, which is more accurate than comparing their embeddings.
+#[async_trait]
+pub trait Reranker: Send + Sync {
+    /// Returns the documents most relevant to the query first, with their relevance as score.
+    /// The score scale depends on the reranker, higher is more relevant.
+    async fn rerank(
+        &self,
+        query: &str,
+        docs: Vec<Document>,
+    ) -> Result<Vec<Document>, RerankerError>;
+}
+
+impl<R> From<R> for Box<dyn Reranker>
+where
+    R: Reranker + 'static,
+{
+    fn from(reranker: R) -> Self {
+        Box::new(reranker)
+    }
+}
+
+/// Sorts the documents by decreasing score and keeps the `top_n` first ones.
+pub(crate) fn sort_by_score(docs: &mut Vec<Document>, top_n: Option<usize>) {
+    docs.sort_by(|a, b| b.score.total_cmp(&a.score));
+    if let Some(top_n) = top_n {
+        docs.truncate(top_n);
+    }
+}
@@ -0,0 +1,103 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+
+use crate::{
+    callbacks::trace_retrieval,
+    schemas::{Document, Retriever},
+    vectorstore::{Bm25Index, Bm25Params},
+};
+
+/// A retriever ranking documents held in memory by BM25, the keyword relevance used by search
+/// engines. It finds the exact terms, such as identifiers and error codes, that embeddings
+/// tend to miss.
+///
+/// The score of the documents is their BM25 score, which is not bounded.
+///
+/// # Usage
+/// ```rust,ignore
+/// let retriever = Bm25Retriever::new(docs, 5);
+/// let docs = retriever.get_relevant_documents("ERR_CONN_RESET").await?;
+/// ```
+pub struct Bm25Retriever {
+    docs: Vec<Document>,
+    index: Bm25Index,
+    num_docs: usize,
+}
+
+impl Bm25Retriever {
+    pub fn new(docs: Vec<Document>, num_docs: usize) -> Self {
+        let index = Bm25Index::new(
+            docs.iter().map(|doc| doc.page_content.as_str()),
+            Bm25Params::default(),
+        );
+        Self {
+            docs,
+            index,
+            num_docs,
+        }
+    }
+
+    pub fn with_params(mut self, params: Bm25Params) -> Self {
+        self.index = Bm25Index::new(
+            self.docs.iter().map(|doc| doc.page_content.as_str()),
+            params,
+        );
+        self
+    }
+
+    pub fn add_documents(&mut self, docs: &[Document]) {
+        for doc in docs {
+            self.index.push(&doc.page_content);
+            self.docs.push(doc.clone());
+        }
+    }
+
+    /// Returns the `limit` documents matching the query best, with their BM25 score.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<Document> {
+        self.index
+            .search(query, limit)
+            .into_iter()
+            .map(|(i, score)| self.docs[i].clone().with_score(score))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Retriever for Bm25Retriever {
+    async fn get_relevant_documents(&self, query: &str) -> Result<Vec<Document>, Box<dyn Error>> {
+        trace_retrieval(query, async { Ok(self.search(query, self.num_docs)) }).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bm25_retriever() {
+        let mut retriever = Bm25Retriever::new(
+            vec![
+                Document::new("The connection was reset by the peer"),
+                Document::new("Error ERR_CONN_RESET: the connection was reset"),
+                Document::new("The cat sleeps on the sofa"),
+            ],
+            2,
+        );
+
+        let docs = retriever.search("err_conn_reset", 3);
+        assert_eq!(docs.len(), 1);
+        assert_eq!(
+            docs[0].page_content,
+            "Error ERR_CONN_RESET: the connection was reset"
+        );
+
+        let docs = retriever.search("connection reset", 3);
+        assert_eq!(docs.len(), 2);
+        assert!(docs[0].score >= docs[1].score);
+        assert!(retriever.search("dog", 3).is_empty());
+
+        retriever.add_documents(&[Document::new("The dog sleeps")]);
+        assert_eq!(retriever.search("dog", 3).len(), 1);
+    }
+}
@@ -0,0 +1,345 @@
+use std::{collections::HashMap, error::Error, sync::Arc};
+
+use async_trait::async_trait;
+use futures::future::try_join_all;
+use indoc::indoc;
+
+use crate::{
+    callbacks::trace_retrieval,
+    embedding::embedder_trait::Embedder,
+    llm::LLM,
+    schemas::{Document, MessageType, Retriever},
+    template::MessageTemplate,
+    vectorstore::DistanceMetric,
+};
+
+pub const DEFAULT_EXTRACT_TEMPLATE: &str = indoc! {"
+    Given the following question and context, extract any part of the context *AS IS* that is
+    relevant to answer the question. If none of the context is relevant return {no_output}.
+
+    Remember, *DO NOT* edit the extracted parts of the context.
+
+    > Question: {question}
+    > Context:
+    >>>
+    {context}
+    >>>
+    Extracted relevant parts:"};
+
+pub const DEFAULT_FILTER_TEMPLATE: &str = indoc! {"
+    Given the following question and context, return YES if the context is relevant to the
+    question and NO if it isn't.
+
+    > Question: {question}
+    > Context:
+    >>>
+    {context}
+    >>>
+    > Relevant (YES / NO):"};
+
+/// Reduces the documents retrieved for a query to the relevant ones, or to their relevant
+/// parts.
+#[async_trait]
+pub trait DocumentCompressor: Send + Sync {
+    async fn compress_documents(
+        &self,
+        query: &str,
+        docs: Vec<Document>,
+    ) -> Result<Vec<Document>, Box<dyn Error>>;
+}
+
+impl<C> From<C> for Box<dyn DocumentCompressor>
+where
+    C: DocumentCompressor + 'static,
+{
+    fn from(compressor: C) -> Self {
+        Box::new(compressor)
+    }
+}
+
+/// Applies the compressors one after the other.
+#[async_trait]
+impl DocumentCompressor for Vec<Box<dyn DocumentCompressor>> {
+    async fn compress_documents(
+        &self,
+        query: &str,
+        mut docs: Vec<Document>,
+    ) -> Result<Vec<Document>, Box<dyn Error>> {
+        for compressor in self {
+            docs = compressor.compress_documents(query, docs).await?;
+        }
+        Ok(docs)
+    }
+}
+
+/// Replaces the content of each document by the parts an [`LLM`] extracted as relevant to the
+/// query, dropping the documents without any.
+pub struct LLMExtractor {
+    llm: Box<dyn LLM>,
+    template: MessageTemplate,
+    no_output: String,
+}
+
+impl LLMExtractor {
+    pub fn new<L: Into<Box<dyn LLM>>>(llm: L) -> Self {
+        Self {
+            llm: llm.into(),
+            template: MessageTemplate::from_fstring(MessageType::Human, DEFAULT_EXTRACT_TEMPLATE),
+            no_output: "NO_OUTPUT".into(),
+        }
+    }
+
+    /// Sets the prompt, with `{question}`, `{context}` and `{no_output}` placeholders.
+    pub fn with_template<S: Into<String>>(mut self, template: S) -> Self {
+        self.template = MessageTemplate::from_fstring(MessageType::Human, template);
+        self
+    }
+}
+
+#[async_trait]
+impl DocumentCompressor for LLMExtractor {
+    async fn compress_documents(
+        &self,
+        query: &str,
+        docs: Vec<Document>,
+    ) -> Result<Vec<Document>, Box<dyn Error>> {
+        let prompts = docs
+            .iter()
+            .map(|doc| {
+                self.template.format(&HashMap::from([
+                    ("question", query.into()),
+                    ("context", doc.page_content.as_str().into()),
+                    ("no_output", self.no_output.as_str().into()),
+                ]))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let answers = try_join_all(
+            prompts
+                .iter()
+                .map(|prompt| self.llm.invoke(&prompt.content)),
+        )
+        .await?;
+
+        Ok(docs
+            .into_iter()
+            .zip(answers)
+            .filter_map(|(mut doc, answer)| {
+                let answer = answer.trim();
+                if answer.is_empty() || answer == self.no_output {
+                    return None;
+                }
+                doc.page_content = answer.to_string();
+                Some(doc)
+            })
+            .collect())
+    }
+}
+
+/// Keeps the documents an [`LLM`] judges relevant to the query.
+pub struct LLMFilter {
+    llm: Box<dyn LLM>,
+    template: MessageTemplate,
+}
+
+impl LLMFilter {
+    pub fn new<L: Into<Box<dyn LLM>>>(llm: L) -> Self {
+        Self {
+            llm: llm.into(),
+            template: MessageTemplate::from_fstring(MessageType::Human, DEFAULT_FILTER_TEMPLATE),
+        }
+    }
+
+    /// Sets the prompt, with `{question}` and `{context}` placeholders. The LLM must answer
+    /// `YES` for the relevant documents.
+    pub fn with_template<S: Into<String>>(mut self, template: S) -> Self {
+        self.template = MessageTemplate::from_fstring(MessageType::Human, template);
+        self
+    }
+}
+
+#[async_trait]
+impl DocumentCompressor for LLMFilter {
+    async fn compress_documents(
+        &self,
+        query: &str,
+        docs: Vec<Document>,
+    ) -> Result<Vec<Document>, Box<dyn Error>> {
+        let prompts = docs
+            .iter()
+            .map(|doc| {
+                self.template.format(&HashMap::from([
+                    ("question", query.into()),
+                    ("context", doc.page_content.as_str().into()),
+                ]))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let answers = try_join_all(
+            prompts
+                .iter()
+                .map(|prompt| self.llm.invoke(&prompt.content)),
+        )
+        .await?;
+
+        Ok(docs
+            .into_iter()
+            .zip(answers)
+            .filter(|(_, answer)| answer.trim().to_uppercase().starts_with("YES"))
+            .map(|(doc, _)| doc)
+            .collect())
+    }
+}
+
+/// Keeps the documents whose embedding is similar enough to the one of the query, most similar
+/// first, with their cosine similarity as score.
+pub struct EmbeddingsFilter {
+    embedder: Arc<dyn Embedder>,
+    similarity_threshold: Option<f64>,
+    top_k: Option<usize>,
+}
+
+impl EmbeddingsFilter {
+    /// Creates a filter keeping the documents with a similarity of at least 0.76.
+    pub fn new<E: Embedder + 'static>(embedder: E) -> Self {
+        Self {
+            embedder: Arc::new(embedder),
+            similarity_threshold: Some(0.76),
+            top_k: None,
+        }
+    }
+
+    pub fn with_similarity_threshold(mut self, similarity_threshold: Option<f64>) -> Self {
+        self.similarity_threshold = similarity_threshold;
+        self
+    }
+
+    /// Keeps at most the `top_k` most similar documents.
+    pub fn with_top_k(mut self, top_k: usize) -> Self {
+        self.top_k = Some(top_k);
+        self
+    }
+}
+
+#[async_trait]
+impl DocumentCompressor for EmbeddingsFilter {
+    async fn compress_documents(
+        &self,
+        query: &str,
+        docs: Vec<Document>,
+    ) -> Result<Vec<Document>, Box<dyn Error>> {
+        if docs.is_empty() {
+            return Ok(docs);
+        }
+        let query_vector = self.embedder.embed_query(query).await?;
+        let texts: Vec<String> = docs.iter().map(|d| d.page_content.clone()).collect();
+        let vectors = self.embedder.embed_documents(&texts).await?;
+
+        let mut docs: Vec<Document> = docs
+            .into_iter()
+            .zip(vectors)
+            .map(|(doc, vector)| {
+                let similarity = DistanceMetric::Cosine.score(&query_vector, &vector);
+                doc.with_score(similarity)
+            })
+            .filter(|doc| {
+                self.similarity_threshold
+                    .is_none_or(|threshold| doc.score >= threshold)
+            })
+            .collect();
+        docs.sort_by(|a, b| b.score.total_cmp(&a.score));
+        if let Some(top_k) = self.top_k {
+            docs.truncate(top_k);
+        }
+        Ok(docs)
+    }
+}
+
+/// A retriever passing the documents of another retriever through a [`DocumentCompressor`], so
+/// that only what is relevant to the query is returned.
+///
+/// # Usage
+/// ```rust,ignore
+/// let retriever = ContextualCompressionRetriever::new(
+///     vectorstore::Retriever::new(store, 10),
+///     EmbeddingsFilter::new(OpenAiEmbedder::default()).with_top_k(4),
+/// );
+/// ```
+pub struct ContextualCompressionRetriever {
+    retriever: Box<dyn Retriever>,
+    compressor: Box<dyn DocumentCompressor>,
+}
+
+impl ContextualCompressionRetriever {
+    pub fn new<R: Into<Box<dyn Retriever>>, C: Into<Box<dyn DocumentCompressor>>>(
+        retriever: R,
+        compressor: C,
+    ) -> Self {
+        Self {
+            retriever: retriever.into(),
+            compressor: compressor.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Retriever for ContextualCompressionRetriever {
+    async fn get_relevant_documents(&self, query: &str) -> Result<Vec<Document>, Box<dyn Error>> {
+        trace_retrieval(query, async {
+            let docs = self.retriever.get_relevant_documents(query).await?;
+            if docs.is_empty() {
+                return Ok(docs);
+            }
+            self.compressor.compress_documents(query, docs).await
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        llm::fake::FakeLLM, retrievers::Bm25Retriever,
+        vectorstore::memory::tests::WordCountEmbedder,
+    };
+
+    use super::*;
+
+    fn docs() -> Vec<Document> {
+        vec![
+            Document::new("The cat sleeps"),
+            Document::new("The dog barks at the cat"),
+            Document::new("The fish swims"),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_contextual_compression_retriever() {
+        let retriever = ContextualCompressionRetriever::new(
+            Bm25Retriever::new(docs(), 3),
+            EmbeddingsFilter::new(WordCountEmbedder).with_similarity_threshold(Some(0.9)),
+        );
+        let found = retriever.get_relevant_documents("cat").await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].page_content, "The cat sleeps");
+        assert!((found[0].score - 1.0).abs() < 1e-12);
+
+        let compressors: Vec<Box<dyn DocumentCompressor>> = vec![
+            EmbeddingsFilter::new(WordCountEmbedder)
+                .with_similarity_threshold(None)
+                .with_top_k(2)
+                .into(),
+            LLMExtractor::new(FakeLLM::fixed("the cat")).into(),
+        ];
+        let retriever =
+            ContextualCompressionRetriever::new(Bm25Retriever::new(docs(), 3), compressors);
+        let found = retriever.get_relevant_documents("cat dog").await.unwrap();
+        assert_eq!(found.len(), 2);
+        assert!(found.iter().all(|doc| doc.page_content == "the cat"));
+
+        let extractor = LLMExtractor::new(FakeLLM::fixed("NO_OUTPUT"));
+        assert!(extractor
+            .compress_documents("cat", docs())
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+
+use crate::schemas::Document;
+
+use super::DocStoreError;
+
+/// Stores documents by id, e.g. the parent documents whose chunks are indexed in a vector store.
+#[async_trait]
+pub trait DocStore: Send + Sync {
+    /// Returns the documents of the given ids, in the order of `ids`.
+    async fn get(&self, ids: &[String]) -> Result<Vec<Option<Document>>, DocStoreError>;
+
+    /// Inserts the documents, replacing the ones with the same id.
+    async fn set(&self, docs: &[(String, Document)]) -> Result<(), DocStoreError>;
+
+    /// Deletes the documents of the given ids. Unknown ids are ignored.
+    async fn delete(&self, ids: &[String]) -> Result<(), DocStoreError>;
+}
+
+impl<S> From<S> for Box<dyn DocStore>
+where
+    S: DocStore + 'static,
+{
+    fn from(store: S) -> Self {
+        Box::new(store)
+    }
+}
+
+#[derive(Default)]
+pub struct InMemoryDocStore {
+    docs: RwLock<HashMap<String, Document>>,
+}
+
+impl InMemoryDocStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl DocStore for InMemoryDocStore {
+    async fn get(&self, ids: &[String]) -> Result<Vec<Option<Document>>, DocStoreError> {
+        let docs = self.docs.read().await;
+        Ok(ids.iter().map(|id| docs.get(id).cloned()).collect())
+    }
+
+    async fn set(&self, docs: &[(String, Document)]) -> Result<(), DocStoreError> {
+        let mut stored = self.docs.write().await;
+        for (id, doc) in docs {
+            stored.insert(id.clone(), doc.clone());
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, ids: &[String]) -> Result<(), DocStoreError> {
+        let mut docs = self.docs.write().await;
+        for id in ids {
+            docs.remove(id);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_in_memory_doc_store() {
+        let store = InMemoryDocStore::new();
+        store
+            .set(&[
+                ("a".into(), Document::new("The cat sleeps")),
+                ("b".into(), Document::new("The dog barks")),
+            ])
+            .await
+            .unwrap();
+        store
+            .set(&[("a".into(), Document::new("The cat wakes up"))])
+            .await
+            .unwrap();
+        store.delete(&["b".into(), "c".into()]).await.unwrap();
+
+        let docs = store.get(&["a".into(), "b".into()]).await.unwrap();
+        assert_eq!(docs[0].as_ref().unwrap().page_content, "The cat wakes up");
+        assert!(docs[1].is_none());
+    }
+}
@@ -0,0 +1,14 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum DocStoreError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Serialization error: {0}")]
+    SerdeError(#[from] serde_json::Error),
+
+    #[cfg(feature = "sqlite")]
+    #[error("Database error: {0}")]
+    SqlxError(#[from] sqlx::Error),
+}
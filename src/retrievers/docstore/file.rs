@@ -0,0 +1,98 @@
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+
+use crate::schemas::Document;
+
+use super::{DocStore, DocStoreError};
+
+/// Stores each document as a JSON file of a directory, named after the hash of its id so that
+/// any id is a valid file name.
+pub struct FileDocStore {
+    root: PathBuf,
+}
+
+impl FileDocStore {
+    /// Creates a store in the directory `root`, created on the first write if needed.
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+        }
+    }
+
+    fn path(&self, id: &str) -> PathBuf {
+        let name: String = Sha256::digest(id.as_bytes())
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        self.root.join(name).with_extension("json")
+    }
+}
+
+#[async_trait]
+impl DocStore for FileDocStore {
+    async fn get(&self, ids: &[String]) -> Result<Vec<Option<Document>>, DocStoreError> {
+        let mut docs = Vec::with_capacity(ids.len());
+        for id in ids {
+            match tokio::fs::read(self.path(id)).await {
+                Ok(bytes) => docs.push(Some(serde_json::from_slice(&bytes)?)),
+                Err(e) if e.kind() == ErrorKind::NotFound => docs.push(None),
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(docs)
+    }
+
+    async fn set(&self, docs: &[(String, Document)]) -> Result<(), DocStoreError> {
+        tokio::fs::create_dir_all(&self.root).await?;
+        for (id, doc) in docs {
+            let path = self.path(id);
+            let tmp_path = path.with_extension("tmp");
+            tokio::fs::write(&tmp_path, serde_json::to_vec(doc)?).await?;
+            tokio::fs::rename(&tmp_path, path).await?;
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, ids: &[String]) -> Result<(), DocStoreError> {
+        for id in ids {
+            match tokio::fs::remove_file(self.path(id)).await {
+                Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_doc_store() {
+        let root = env::temp_dir().join("file_doc_store_test_dir");
+        let _ = tokio::fs::remove_dir_all(&root).await;
+        let store = FileDocStore::new(&root);
+
+        let id = "docs/../cat.txt".to_string();
+        store
+            .set(&[(id.clone(), Document::new("The cat sleeps"))])
+            .await
+            .unwrap();
+        let docs = store.get(&[id.clone(), "dog".into()]).await.unwrap();
+        assert_eq!(docs[0].as_ref().unwrap().page_content, "The cat sleeps");
+        assert!(docs[1].is_none());
+
+        store.delete(&[id.clone(), "dog".into()]).await.unwrap();
+        assert!(store.get(&[id]).await.unwrap()[0].is_none());
+
+        tokio::fs::remove_dir_all(&root).await.unwrap();
+    }
+}
@@ -0,0 +1,14 @@
+mod error;
+pub use error::*;
+
+#[allow(clippy::module_inception)]
+mod docstore;
+pub use docstore::*;
+
+mod file;
+pub use file::*;
+
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(feature = "sqlite")]
+pub use sqlite::*;
@@ -0,0 +1,148 @@
+use async_trait::async_trait;
+use indoc::formatdoc;
+use sqlx::{Pool, Sqlite};
+
+use crate::{schemas::Document, vectorstore::sqlite_rows::placeholders};
+
+use super::{DocStore, DocStoreError};
+
+/// Stores the documents of a name space as JSON in a sqlite table.
+pub struct SqliteDocStore {
+    pool: Pool<Sqlite>,
+    table: String,
+    namespace: String,
+}
+
+impl SqliteDocStore {
+    pub fn new(pool: Pool<Sqlite>, namespace: impl Into<String>) -> Self {
+        Self {
+            pool,
+            table: "docstore".into(),
+            namespace: namespace.into(),
+        }
+    }
+
+    pub fn with_table(mut self, table: &str) -> Self {
+        self.table = table.into();
+        self
+    }
+
+    /// Creates the table of the documents if it does not exist.
+    pub async fn initialize(&self) -> Result<(), DocStoreError> {
+        let table = &self.table;
+        sqlx::query(&formatdoc! {"
+            CREATE TABLE IF NOT EXISTS {table}
+            (
+                namespace TEXT NOT NULL,
+                id TEXT NOT NULL,
+                document TEXT NOT NULL,
+                PRIMARY KEY (namespace, id)
+            );"
+        })
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DocStore for SqliteDocStore {
+    async fn get(&self, ids: &[String]) -> Result<Vec<Option<Document>>, DocStoreError> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let table = &self.table;
+        let sql = format!(
+            "SELECT id, document FROM {table} WHERE namespace = ? AND id IN ({})",
+            placeholders(ids.len())
+        );
+        let mut query = sqlx::query_as::<_, (String, String)>(&sql).bind(&self.namespace);
+        for id in ids {
+            query = query.bind(id);
+        }
+        let rows = query.fetch_all(&self.pool).await?;
+
+        ids.iter()
+            .map(|id| {
+                rows.iter()
+                    .find(|(row_id, _)| row_id == id)
+                    .map(|(_, document)| serde_json::from_str(document))
+                    .transpose()
+                    .map_err(DocStoreError::from)
+            })
+            .collect()
+    }
+
+    async fn set(&self, docs: &[(String, Document)]) -> Result<(), DocStoreError> {
+        let table = &self.table;
+        let mut tx = self.pool.begin().await?;
+        for (id, doc) in docs {
+            sqlx::query(&formatdoc! {"
+                INSERT INTO {table} (namespace, id, document)
+                VALUES (?, ?, ?)
+                ON CONFLICT (namespace, id) DO UPDATE SET document = excluded.document"
+            })
+            .bind(&self.namespace)
+            .bind(id)
+            .bind(serde_json::to_string(doc)?)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn delete(&self, ids: &[String]) -> Result<(), DocStoreError> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let table = &self.table;
+        let sql = format!(
+            "DELETE FROM {table} WHERE namespace = ? AND id IN ({})",
+            placeholders(ids.len())
+        );
+        let mut query = sqlx::query(&sql).bind(&self.namespace);
+        for id in ids {
+            query = query.bind(id);
+        }
+        query.execute(&self.pool).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sqlite_doc_store() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let store = SqliteDocStore::new(pool.clone(), "a");
+        store.initialize().await.unwrap();
+        let other = SqliteDocStore::new(pool, "b");
+
+        store
+            .set(&[
+                ("1".into(), Document::new("The cat sleeps")),
+                ("2".into(), Document::new("The dog barks")),
+            ])
+            .await
+            .unwrap();
+        store
+            .set(&[("1".into(), Document::new("The cat wakes up"))])
+            .await
+            .unwrap();
+        store.delete(&["2".into()]).await.unwrap();
+
+        let docs = store.get(&["2".into(), "1".into()]).await.unwrap();
+        assert!(docs[0].is_none());
+        assert_eq!(docs[1].as_ref().unwrap().page_content, "The cat wakes up");
+        assert!(other.get(&["1".into()]).await.unwrap()[0].is_none());
+    }
+}
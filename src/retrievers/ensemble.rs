@@ -0,0 +1,108 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+
+use crate::{
+    callbacks::trace_retrieval,
+    schemas::{Document, Retriever},
+    vectorstore::Fusion,
+};
+
+use super::join::try_join_retrievals;
+
+/// A retriever merging the results of several retrievers with a [`Fusion`], weighted
+/// reciprocal rank fusion by default. The score of the documents is their fused score.
+///
+/// # Usage
+/// ```rust,ignore
+/// let retriever = EnsembleRetriever::new(5)
+///     .with_retriever(Bm25Retriever::new(docs, 10), 0.4)
+///     .with_retriever(vectorstore::Retriever::new(store, 10), 0.6);
+/// ```
+pub struct EnsembleRetriever {
+    retrievers: Vec<Box<dyn Retriever>>,
+    weights: Vec<f64>,
+    num_docs: usize,
+    fusion: Fusion,
+}
+
+impl EnsembleRetriever {
+    pub fn new(num_docs: usize) -> Self {
+        Self {
+            retrievers: Vec::new(),
+            weights: Vec::new(),
+            num_docs,
+            fusion: Fusion::default(),
+        }
+    }
+
+    /// Adds a retriever whose results weigh `weight` in the fusion.
+    pub fn with_retriever<R: Into<Box<dyn Retriever>>>(
+        mut self,
+        retriever: R,
+        weight: f64,
+    ) -> Self {
+        self.retrievers.push(retriever.into());
+        self.weights.push(weight);
+        self
+    }
+
+    pub fn with_fusion(mut self, fusion: Fusion) -> Self {
+        self.fusion = fusion;
+        self
+    }
+}
+
+#[async_trait]
+impl Retriever for EnsembleRetriever {
+    async fn get_relevant_documents(&self, query: &str) -> Result<Vec<Document>, Box<dyn Error>> {
+        trace_retrieval(query, async {
+            let results = try_join_retrievals(
+                self.retrievers
+                    .iter()
+                    .map(|retriever| retriever.get_relevant_documents(query)),
+            )
+            .await?;
+            Ok(self.fusion.fuse(results, &self.weights, self.num_docs))
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        llm::fake::FakeLLM,
+        retrievers::{Bm25Retriever, ContextualCompressionRetriever, LLMFilter},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_ensemble_retriever() {
+        let animals = vec![
+            Document::new("The cat sleeps"),
+            Document::new("The dog barks"),
+        ];
+        let errors = vec![
+            Document::new("E1042: the cat left the server"),
+            Document::new("E2001: the disk is full"),
+        ];
+        let retriever = EnsembleRetriever::new(3)
+            .with_retriever(Bm25Retriever::new(animals, 2), 0.3)
+            .with_retriever(Bm25Retriever::new(errors, 2), 0.7);
+
+        let found = retriever.get_relevant_documents("cat").await.unwrap();
+        let texts: Vec<&str> = found.iter().map(|d| d.page_content.as_str()).collect();
+        assert_eq!(texts, ["E1042: the cat left the server", "The cat sleeps"]);
+
+        // Retrievers compose: the ensemble is filtered by an LLM answering no to everything.
+        let retriever =
+            ContextualCompressionRetriever::new(retriever, LLMFilter::new(FakeLLM::fixed("NO")));
+        assert!(retriever
+            .get_relevant_documents("cat")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,130 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+
+use crate::{
+    callbacks::trace_retrieval,
+    schemas::{Document, Retriever},
+    vectorstore::Fusion,
+};
+
+use super::join::try_join_retrievals;
+
+/// A retriever combining a keyword retriever, such as a [`Bm25Retriever`](super::Bm25Retriever),
+/// with a vector store retriever, so that exact terms and meaning are both found.
+///
+/// The two result lists are merged with a [`Fusion`], reciprocal rank fusion by default, and the
+/// score of the documents is their fused score. To search a single store supporting full-text
+/// search, use [`SearchType::Hybrid`](crate::vectorstore::SearchType::Hybrid) instead.
+///
+/// # Usage
+/// ```rust,ignore
+/// let retriever = HybridRetriever::new(
+///     Bm25Retriever::new(docs, 10),
+///     vectorstore::Retriever::new(store, 10),
+///     5,
+/// )
+/// .with_keyword_weight(0.3);
+/// ```
+pub struct HybridRetriever {
+    keyword_retriever: Box<dyn Retriever>,
+    vector_retriever: Box<dyn Retriever>,
+    num_docs: usize,
+    fusion: Fusion,
+    keyword_weight: f64,
+}
+
+impl HybridRetriever {
+    pub fn new<K: Into<Box<dyn Retriever>>, V: Into<Box<dyn Retriever>>>(
+        keyword_retriever: K,
+        vector_retriever: V,
+        num_docs: usize,
+    ) -> Self {
+        Self {
+            keyword_retriever: keyword_retriever.into(),
+            vector_retriever: vector_retriever.into(),
+            num_docs,
+            fusion: Fusion::default(),
+            keyword_weight: 0.5,
+        }
+    }
+
+    pub fn with_fusion(mut self, fusion: Fusion) -> Self {
+        self.fusion = fusion;
+        self
+    }
+
+    /// Sets the weight of the keyword results, between 0 and 1, the vector results weighing the
+    /// rest. Both weigh 0.5 by default.
+    pub fn with_keyword_weight(mut self, keyword_weight: f64) -> Self {
+        self.keyword_weight = keyword_weight.clamp(0.0, 1.0);
+        self
+    }
+}
+
+#[async_trait]
+impl Retriever for HybridRetriever {
+    async fn get_relevant_documents(&self, query: &str) -> Result<Vec<Document>, Box<dyn Error>> {
+        trace_retrieval(query, async {
+            let results = try_join_retrievals([
+                self.keyword_retriever.get_relevant_documents(query),
+                self.vector_retriever.get_relevant_documents(query),
+            ])
+            .await?;
+            Ok(self.fusion.fuse(
+                results,
+                &[self.keyword_weight, 1.0 - self.keyword_weight],
+                self.num_docs,
+            ))
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        retrievers::Bm25Retriever,
+        vectorstore::{
+            memory::{tests::WordCountEmbedder, InMemoryOptions, InMemoryStore},
+            DistanceMetric, Retriever as VectorStoreRetriever, VectorStore,
+        },
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_hybrid_retriever() {
+        let docs = vec![
+            Document::new("The cat sleeps").with_id("1"),
+            Document::new("The dog barks at the cat").with_id("2"),
+            Document::new("Error E1042: the fish swims away").with_id("3"),
+        ];
+        let store = InMemoryStore::new(Arc::new(WordCountEmbedder), DistanceMetric::Cosine);
+        store
+            .add_documents(&docs, &InMemoryOptions::default())
+            .await
+            .unwrap();
+
+        let retriever = HybridRetriever::new(
+            Bm25Retriever::new(docs.clone(), 2),
+            VectorStoreRetriever::new(store, 2),
+            3,
+        );
+        let found = retriever.get_relevant_documents("cat E1042").await.unwrap();
+        let ids: Vec<_> = found.iter().filter_map(|d| d.id.as_deref()).collect();
+        assert_eq!(ids.len(), 3);
+        assert!(ids.contains(&"3"));
+
+        let retriever = HybridRetriever::new(
+            Bm25Retriever::new(docs, 2),
+            Bm25Retriever::new(Vec::new(), 2),
+            1,
+        )
+        .with_keyword_weight(1.0);
+        let found = retriever.get_relevant_documents("E1042").await.unwrap();
+        assert_eq!(found[0].id.as_deref(), Some("3"));
+    }
+}
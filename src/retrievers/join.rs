@@ -0,0 +1,77 @@
+use std::{error::Error, future::Future, pin::Pin, task::Poll};
+
+use futures::future::{poll_fn, try_maybe_done, TryMaybeDone};
+
+use crate::schemas::Document;
+
+/// Runs the `retrievals` concurrently, returning their documents in order, or the first error.
+///
+/// Unlike `try_join!` or `try_join_all`, only the successful results are kept while the other
+/// retrievals run, so that the future is `Send` although the errors are not.
+pub(crate) async fn try_join_retrievals<F>(
+    retrievals: impl IntoIterator<Item = F>,
+) -> Result<Vec<Vec<Document>>, Box<dyn Error>>
+where
+    F: Future<Output = Result<Vec<Document>, Box<dyn Error>>> + Unpin,
+{
+    let mut retrievals: Vec<TryMaybeDone<F>> = retrievals.into_iter().map(try_maybe_done).collect();
+    poll_fn(|cx| {
+        let mut done = true;
+        for retrieval in retrievals.iter_mut() {
+            match Pin::new(retrieval).poll(cx) {
+                Poll::Ready(Ok(())) => {}
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => done = false,
+            }
+        }
+        if done {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Pending
+        }
+    })
+    .await?;
+
+    Ok(retrievals
+        .iter_mut()
+        .filter_map(|retrieval| Pin::new(retrieval).take_output())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::time::{sleep, Instant};
+
+    use super::*;
+
+    async fn retrieve(content: &str, delay: u64) -> Result<Vec<Document>, Box<dyn Error>> {
+        sleep(Duration::from_millis(delay)).await;
+        if content.is_empty() {
+            return Err("no documents".into());
+        }
+        Ok(vec![Document::new(content)])
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_try_join_retrievals() {
+        let start = Instant::now();
+        let results = try_join_retrievals([
+            Box::pin(retrieve("cat", 100)),
+            Box::pin(retrieve("dog", 50)),
+        ])
+        .await
+        .unwrap();
+
+        assert_eq!(start.elapsed(), Duration::from_millis(100));
+        assert_eq!(results[0][0].page_content, "cat");
+        assert_eq!(results[1][0].page_content, "dog");
+
+        let error =
+            try_join_retrievals([Box::pin(retrieve("cat", 100)), Box::pin(retrieve("", 50))])
+                .await
+                .unwrap_err();
+        assert_eq!(error.to_string(), "no documents");
+    }
+}
@@ -0,0 +1,30 @@
+mod bm25;
+pub use crate::vectorstore::{Bm25Params, Fusion};
+pub use bm25::*;
+
+mod compression;
+pub use compression::*;
+
+mod docstore;
+pub use docstore::*;
+
+mod ensemble;
+pub use ensemble::*;
+
+mod hybrid;
+pub use hybrid::*;
+
+mod join;
+pub(crate) use join::try_join_retrievals;
+
+mod multi_query;
+pub use multi_query::*;
+
+mod multi_vector;
+pub use multi_vector::*;
+
+mod parent_document;
+pub use parent_document::*;
+
+mod rerank;
+pub use rerank::*;
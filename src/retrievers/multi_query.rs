@@ -0,0 +1,181 @@
+use std::{collections::HashMap, error::Error, sync::LazyLock};
+
+use async_trait::async_trait;
+use indoc::indoc;
+use regex::Regex;
+
+use crate::{
+    callbacks::trace_retrieval,
+    llm::LLM,
+    schemas::{Document, MessageType, Retriever},
+    template::MessageTemplate,
+    vectorstore::document_key,
+};
+
+use super::join::try_join_retrievals;
+
+pub const DEFAULT_MULTI_QUERY_TEMPLATE: &str = indoc! {"
+    You are an AI language model assistant. Your task is to generate {num_queries} different
+    versions of the given user question to retrieve relevant documents from a vector database.
+    By generating multiple perspectives on the user question, your goal is to help the user
+    overcome some of the limitations of distance-based similarity search. Provide these
+    alternative questions separated by newlines, without numbering.
+
+    Original question: {question}"};
+
+static LIST_MARKER_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:\d+[.)]|[-*])(?:\s+|$)").expect("Static regex is valid"));
+
+/// Removes the numbering, such as `1.` or `2)`, or the bullet an LLM may put before a query.
+pub(crate) fn clean_query(line: &str) -> &str {
+    let line = line.trim();
+    match LIST_MARKER_RE.find(line) {
+        Some(marker) => line[marker.end()..].trim(),
+        None => line,
+    }
+}
+
+/// A retriever asking an [`LLM`] for variants of the query and returning the documents found for
+/// any of them, so that the wording of a question matters less.
+///
+/// Each document is returned once, in the order it was first found, with its best score.
+///
+/// # Usage
+/// ```rust,ignore
+/// let retriever = MultiQueryRetriever::new(vectorstore::Retriever::new(store, 5), OpenAI::default())
+///     .with_num_queries(4);
+/// let docs = retriever.get_relevant_documents("How do I reset my password?").await?;
+/// ```
+pub struct MultiQueryRetriever {
+    retriever: Box<dyn Retriever>,
+    llm: Box<dyn LLM>,
+    template: MessageTemplate,
+    num_queries: usize,
+    include_original: bool,
+}
+
+impl MultiQueryRetriever {
+    pub fn new<R: Into<Box<dyn Retriever>>, L: Into<Box<dyn LLM>>>(retriever: R, llm: L) -> Self {
+        Self {
+            retriever: retriever.into(),
+            llm: llm.into(),
+            template: MessageTemplate::from_fstring(
+                MessageType::Human,
+                DEFAULT_MULTI_QUERY_TEMPLATE,
+            ),
+            num_queries: 3,
+            include_original: true,
+        }
+    }
+
+    /// Sets the prompt generating the queries, with `{question}` and `{num_queries}`
+    /// placeholders. The LLM must answer one query per line.
+    pub fn with_template<S: Into<String>>(mut self, template: S) -> Self {
+        self.template = MessageTemplate::from_fstring(MessageType::Human, template);
+        self
+    }
+
+    pub fn with_num_queries(mut self, num_queries: usize) -> Self {
+        self.num_queries = num_queries;
+        self
+    }
+
+    /// Whether the original query is searched too, `true` by default.
+    pub fn with_include_original(mut self, include_original: bool) -> Self {
+        self.include_original = include_original;
+        self
+    }
+
+    /// Returns the queries to search: the ones generated by the LLM, and the original query if
+    /// included.
+    pub async fn generate_queries(&self, query: &str) -> Result<Vec<String>, Box<dyn Error>> {
+        let prompt = self.template.format(&HashMap::from([
+            ("question", query.into()),
+            ("num_queries", self.num_queries.to_string().into()),
+        ]))?;
+        let answer = self.llm.invoke(&prompt.content).await?;
+
+        let mut queries: Vec<String> = Vec::new();
+        if self.include_original {
+            queries.push(query.to_string());
+        }
+        let generated = answer
+            .lines()
+            .map(clean_query)
+            .filter(|line| !line.is_empty())
+            .take(self.num_queries);
+        for line in generated {
+            if !queries.iter().any(|q| q == line) {
+                queries.push(line.to_string());
+            }
+        }
+        Ok(queries)
+    }
+}
+
+#[async_trait]
+impl Retriever for MultiQueryRetriever {
+    async fn get_relevant_documents(&self, query: &str) -> Result<Vec<Document>, Box<dyn Error>> {
+        trace_retrieval(query, async {
+            let mut docs: Vec<Document> = Vec::new();
+            let mut positions: HashMap<String, usize> = HashMap::new();
+            let queries = self.generate_queries(query).await?;
+            let results = try_join_retrievals(
+                queries
+                    .iter()
+                    .map(|query| self.retriever.get_relevant_documents(query)),
+            )
+            .await?;
+            for found in results {
+                for doc in found {
+                    match positions.get(document_key(&doc)) {
+                        Some(&position) => {
+                            docs[position].score = docs[position].score.max(doc.score);
+                        }
+                        None => {
+                            positions.insert(document_key(&doc).to_string(), docs.len());
+                            docs.push(doc);
+                        }
+                    }
+                }
+            }
+            Ok(docs)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{llm::fake::FakeLLM, retrievers::Bm25Retriever};
+
+    use super::*;
+
+    #[test]
+    fn test_clean_query() {
+        assert_eq!(clean_query(" 1. Where is the cat?"), "Where is the cat?");
+        assert_eq!(clean_query("2) Where is the cat?"), "Where is the cat?");
+        assert_eq!(clean_query("- Where is the cat?"), "Where is the cat?");
+        assert_eq!(clean_query("2024 tax rules"), "2024 tax rules");
+        assert_eq!(clean_query("3.5 percent loans"), "3.5 percent loans");
+        assert_eq!(clean_query("-5 degrees"), "-5 degrees");
+    }
+
+    #[tokio::test]
+    async fn test_multi_query_retriever() {
+        let docs = vec![
+            Document::new("The cat sleeps"),
+            Document::new("A kitten naps on the sofa"),
+            Document::new("The dog barks"),
+        ];
+        let llm = FakeLLM::fixed("1. Where does the kitten nap?\n\n2. cat sleeps\n");
+        let retriever = MultiQueryRetriever::new(Bm25Retriever::new(docs, 2), llm);
+
+        let queries = retriever.generate_queries("cat").await.unwrap();
+        assert_eq!(queries, ["cat", "Where does the kitten nap?", "cat sleeps"]);
+
+        let found = retriever.get_relevant_documents("cat").await.unwrap();
+        let texts: Vec<&str> = found.iter().map(|d| d.page_content.as_str()).collect();
+        assert_eq!(texts, ["The cat sleeps", "A kitten naps on the sofa"]);
+    }
+}
@@ -0,0 +1,325 @@
+use std::{collections::HashMap, error::Error};
+
+use async_trait::async_trait;
+use futures::future::try_join_all;
+use indoc::indoc;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+    callbacks::trace_retrieval,
+    llm::LLM,
+    schemas::{Document, MessageType, Retriever},
+    template::MessageTemplate,
+    vectorstore::{VecStoreOptions, VectorStore},
+};
+
+use super::{multi_query::clean_query, DocStore};
+
+pub const DEFAULT_SUMMARY_TEMPLATE: &str = indoc! {"
+    Summarize the following document in a few sentences, keeping the names, numbers and terms
+    someone could search for.
+
+    >>>
+    {context}
+    >>>
+    Summary:"};
+
+pub const DEFAULT_QUESTIONS_TEMPLATE: &str = indoc! {"
+    Write {num_questions} questions that the following document answers. Provide these questions
+    separated by newlines, without numbering.
+
+    >>>
+    {context}
+    >>>
+    Questions:"};
+
+/// A retriever indexing several documents per stored document, e.g. its chunks, a summary or
+/// questions it answers, and returning the stored documents whose indexed documents are the most
+/// similar to the query.
+///
+/// The indexed documents keep the id of their document in the `id_key` metadata, `doc_id` by
+/// default. The stored documents are returned once each, with the best score of their indexed
+/// documents.
+///
+/// # Usage
+/// ```rust,ignore
+/// let retriever = MultiVectorRetriever::new(store, InMemoryDocStore::new(), 4);
+/// retriever.add_summaries(&docs, &OpenAI::default()).await?;
+/// let docs = retriever.get_relevant_documents("How do I reset my password?").await?;
+/// ```
+pub struct MultiVectorRetriever<F> {
+    vstore: Box<dyn VectorStore<Options = VecStoreOptions<F>>>,
+    docstore: Box<dyn DocStore>,
+    id_key: String,
+    num_docs: usize,
+    fetch_k: usize,
+    options: VecStoreOptions<F>,
+    summary_template: MessageTemplate,
+    questions_template: MessageTemplate,
+}
+
+impl<F: Sync + Send> MultiVectorRetriever<F> {
+    pub fn new<V, D>(vstore: V, docstore: D, num_docs: usize) -> Self
+    where
+        V: Into<Box<dyn VectorStore<Options = VecStoreOptions<F>>>>,
+        D: Into<Box<dyn DocStore>>,
+    {
+        Self {
+            vstore: vstore.into(),
+            docstore: docstore.into(),
+            id_key: "doc_id".into(),
+            num_docs,
+            fetch_k: num_docs * 4,
+            options: VecStoreOptions::new(),
+            summary_template: MessageTemplate::from_fstring(
+                MessageType::Human,
+                DEFAULT_SUMMARY_TEMPLATE,
+            ),
+            questions_template: MessageTemplate::from_fstring(
+                MessageType::Human,
+                DEFAULT_QUESTIONS_TEMPLATE,
+            ),
+        }
+    }
+
+    /// Sets the metadata key of the indexed documents holding the id of their document.
+    pub fn with_id_key<S: Into<String>>(mut self, id_key: S) -> Self {
+        self.id_key = id_key.into();
+        self
+    }
+
+    /// Sets the number of indexed documents searched, `4 * num_docs` by default.
+    pub fn with_fetch_k(mut self, fetch_k: usize) -> Self {
+        self.fetch_k = fetch_k;
+        self
+    }
+
+    pub fn with_options(mut self, options: VecStoreOptions<F>) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Sets the prompt of [`MultiVectorRetriever::add_summaries`], with a `{context}`
+    /// placeholder.
+    pub fn with_summary_template<S: Into<String>>(mut self, template: S) -> Self {
+        self.summary_template = MessageTemplate::from_fstring(MessageType::Human, template);
+        self
+    }
+
+    /// Sets the prompt of [`MultiVectorRetriever::add_hypothetical_questions`], with
+    /// `{context}` and `{num_questions}` placeholders. The LLM must answer one question per line.
+    pub fn with_questions_template<S: Into<String>>(mut self, template: S) -> Self {
+        self.questions_template = MessageTemplate::from_fstring(MessageType::Human, template);
+        self
+    }
+
+    /// Stores the documents and indexes the `vectors` of each of them, in the order of `docs`.
+    /// The documents keep their id, or get a new one. Returns the ids of the documents.
+    pub async fn add_documents(
+        &self,
+        docs: &[Document],
+        vectors: Vec<Vec<Document>>,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        if docs.len() != vectors.len() {
+            return Err(format!(
+                "Expected the indexed documents of {} documents, got {}",
+                docs.len(),
+                vectors.len()
+            )
+            .into());
+        }
+
+        let mut ids = Vec::with_capacity(docs.len());
+        let mut stored = Vec::with_capacity(docs.len());
+        let mut indexed = Vec::new();
+        for (doc, vectors) in docs.iter().zip(vectors) {
+            let id = doc.id.clone().unwrap_or_else(|| Uuid::new_v4().to_string());
+            for mut vector in vectors {
+                vector
+                    .metadata
+                    .insert(self.id_key.clone(), Value::String(id.clone()));
+                indexed.push(vector);
+            }
+            let mut doc = doc.clone();
+            doc.id = Some(id.clone());
+            stored.push((id.clone(), doc));
+            ids.push(id);
+        }
+
+        if !indexed.is_empty() {
+            self.vstore.add_documents(&indexed, &self.options).await?;
+        }
+        self.docstore.set(&stored).await?;
+        Ok(ids)
+    }
+
+    /// Stores the documents and indexes a summary of each of them written by an [`LLM`].
+    pub async fn add_summaries(
+        &self,
+        docs: &[Document],
+        llm: &dyn LLM,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let prompts = docs
+            .iter()
+            .map(|doc| {
+                self.summary_template.format(&HashMap::from([(
+                    "context",
+                    doc.page_content.as_str().into(),
+                )]))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let answers =
+            try_join_all(prompts.iter().map(|prompt| llm.invoke(&prompt.content))).await?;
+
+        let vectors = docs
+            .iter()
+            .zip(answers)
+            .map(|(doc, answer)| {
+                vec![Document::new(answer.trim()).with_metadata(doc.metadata.clone())]
+            })
+            .collect();
+        self.add_documents(docs, vectors).await
+    }
+
+    /// Stores the documents and indexes `num_questions` questions each of them answers, written
+    /// by an [`LLM`], so that questions are matched against questions.
+    pub async fn add_hypothetical_questions(
+        &self,
+        docs: &[Document],
+        llm: &dyn LLM,
+        num_questions: usize,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let prompts = docs
+            .iter()
+            .map(|doc| {
+                self.questions_template.format(&HashMap::from([
+                    ("context", doc.page_content.as_str().into()),
+                    ("num_questions", num_questions.to_string().into()),
+                ]))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let answers =
+            try_join_all(prompts.iter().map(|prompt| llm.invoke(&prompt.content))).await?;
+
+        let vectors = docs
+            .iter()
+            .zip(answers)
+            .map(|(doc, answer)| {
+                answer
+                    .lines()
+                    .map(clean_query)
+                    .filter(|line| !line.is_empty())
+                    .take(num_questions)
+                    .map(|line| Document::new(line).with_metadata(doc.metadata.clone()))
+                    .collect()
+            })
+            .collect();
+        self.add_documents(docs, vectors).await
+    }
+}
+
+#[async_trait]
+impl<F: Sync + Send> Retriever for MultiVectorRetriever<F> {
+    async fn get_relevant_documents(&self, query: &str) -> Result<Vec<Document>, Box<dyn Error>> {
+        trace_retrieval(query, async {
+            let found = self
+                .vstore
+                .similarity_search(query, self.fetch_k, &self.options)
+                .await?;
+
+            let mut ids: Vec<String> = Vec::new();
+            let mut scores: HashMap<String, f64> = HashMap::new();
+            for doc in found {
+                let Some(id) = doc.metadata.get(&self.id_key).and_then(Value::as_str) else {
+                    continue;
+                };
+                match scores.get_mut(id) {
+                    Some(score) => *score = score.max(doc.score),
+                    None => {
+                        scores.insert(id.to_string(), doc.score);
+                        ids.push(id.to_string());
+                    }
+                }
+            }
+            if ids.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let docs = self.docstore.get(&ids).await?;
+            Ok(ids
+                .iter()
+                .zip(docs)
+                .filter_map(|(id, doc)| Some(doc?.with_score(scores[id])))
+                .take(self.num_docs)
+                .collect())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        llm::fake::FakeLLM,
+        retrievers::InMemoryDocStore,
+        vectorstore::{
+            memory::{tests::WordCountEmbedder, InMemoryStore},
+            DistanceMetric,
+        },
+    };
+
+    use super::*;
+
+    fn store() -> InMemoryStore {
+        InMemoryStore::new(Arc::new(WordCountEmbedder), DistanceMetric::Cosine)
+    }
+
+    #[tokio::test]
+    async fn test_multi_vector_retriever() {
+        let retriever = MultiVectorRetriever::new(store(), InMemoryDocStore::new(), 2);
+        let docs = vec![
+            Document::new("A long report about pets").with_id("pets"),
+            Document::new("A long report about the sea"),
+        ];
+        let ids = retriever
+            .add_documents(
+                &docs,
+                vec![
+                    vec![Document::new("cat"), Document::new("dog")],
+                    vec![Document::new("fish")],
+                ],
+            )
+            .await
+            .unwrap();
+        assert_eq!(ids[0], "pets");
+
+        let found = retriever.get_relevant_documents("dog").await.unwrap();
+        assert_eq!(found[0].page_content, "A long report about pets");
+        assert_eq!(found[0].id.as_deref(), Some("pets"));
+        assert!((found[0].score - 1.0).abs() < 1e-12);
+        // The pets are indexed twice but returned once.
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[1].score, 0.0);
+
+        let found = retriever.get_relevant_documents("fish").await.unwrap();
+        assert_eq!(found[0].page_content, "A long report about the sea");
+        assert_eq!(found[0].id.as_ref(), Some(&ids[1]));
+    }
+
+    #[tokio::test]
+    async fn test_hypothetical_questions() {
+        let retriever = MultiVectorRetriever::new(store(), InMemoryDocStore::new(), 2);
+        let llm = FakeLLM::fixed("1. What does the bird eat?\n2. Where does the bird sleep?\n");
+        retriever
+            .add_hypothetical_questions(&[Document::new("Sparrows eat seeds")], &llm, 2)
+            .await
+            .unwrap();
+
+        let found = retriever.get_relevant_documents("bird food").await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].page_content, "Sparrows eat seeds");
+    }
+}
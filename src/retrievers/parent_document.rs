@@ -0,0 +1,152 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+
+use crate::{
+    schemas::{Document, Retriever},
+    text_splitter::TextSplitter,
+    vectorstore::{VecStoreOptions, VectorStore},
+};
+
+use super::{DocStore, MultiVectorRetriever};
+
+/// A retriever indexing small chunks of the documents, which embed well, and returning the
+/// larger documents they come from, which give the context to answer.
+///
+/// The documents are split in parents by the optional parent splitter, the parents in children
+/// by the child splitter. The children are indexed in the vector store and the parents kept in
+/// the [`DocStore`].
+///
+/// # Usage
+/// ```rust,ignore
+/// let retriever = ParentDocumentRetriever::new(
+///     store,
+///     InMemoryDocStore::new(),
+///     TokenSplitter::new(SplitterOptions::new().with_chunk_size(200)),
+///     4,
+/// );
+/// retriever.add_documents(&docs).await?;
+/// let docs = retriever.get_relevant_documents("How do I reset my password?").await?;
+/// ```
+pub struct ParentDocumentRetriever<F> {
+    retriever: MultiVectorRetriever<F>,
+    child_splitter: Box<dyn TextSplitter>,
+    parent_splitter: Option<Box<dyn TextSplitter>>,
+}
+
+impl<F: Sync + Send> ParentDocumentRetriever<F> {
+    pub fn new<V, D, S>(vstore: V, docstore: D, child_splitter: S, num_docs: usize) -> Self
+    where
+        V: Into<Box<dyn VectorStore<Options = VecStoreOptions<F>>>>,
+        D: Into<Box<dyn DocStore>>,
+        S: TextSplitter + 'static,
+    {
+        Self {
+            retriever: MultiVectorRetriever::new(vstore, docstore, num_docs),
+            child_splitter: Box::new(child_splitter),
+            parent_splitter: None,
+        }
+    }
+
+    /// Splits the documents in parents first, instead of storing them whole.
+    pub fn with_parent_splitter<S: TextSplitter + 'static>(mut self, parent_splitter: S) -> Self {
+        self.parent_splitter = Some(Box::new(parent_splitter));
+        self
+    }
+
+    /// Sets the metadata key of the children holding the id of their parent, `doc_id` by
+    /// default.
+    pub fn with_id_key<S: Into<String>>(mut self, id_key: S) -> Self {
+        self.retriever = self.retriever.with_id_key(id_key);
+        self
+    }
+
+    /// Sets the number of children searched, `4 * num_docs` by default.
+    pub fn with_fetch_k(mut self, fetch_k: usize) -> Self {
+        self.retriever = self.retriever.with_fetch_k(fetch_k);
+        self
+    }
+
+    pub fn with_options(mut self, options: VecStoreOptions<F>) -> Self {
+        self.retriever = self.retriever.with_options(options);
+        self
+    }
+
+    /// Splits, indexes and stores the documents. Returns the ids of the parents.
+    pub async fn add_documents(&self, docs: &[Document]) -> Result<Vec<String>, Box<dyn Error>> {
+        let parents = match &self.parent_splitter {
+            Some(splitter) => splitter.split_documents(docs).await?,
+            None => docs.to_vec(),
+        };
+        let mut children = Vec::with_capacity(parents.len());
+        for parent in &parents {
+            let chunks = self
+                .child_splitter
+                .split_documents(std::slice::from_ref(parent))
+                .await?;
+            children.push(chunks);
+        }
+        self.retriever.add_documents(&parents, children).await
+    }
+}
+
+#[async_trait]
+impl<F: Sync + Send> Retriever for ParentDocumentRetriever<F> {
+    async fn get_relevant_documents(&self, query: &str) -> Result<Vec<Document>, Box<dyn Error>> {
+        self.retriever.get_relevant_documents(query).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        retrievers::InMemoryDocStore,
+        text_splitter::TextSplitterError,
+        vectorstore::{
+            memory::{tests::WordCountEmbedder, InMemoryStore},
+            DistanceMetric,
+        },
+    };
+
+    use super::*;
+
+    /// Splits a text on a separator.
+    struct SeparatorSplitter(&'static str);
+
+    #[async_trait]
+    impl TextSplitter for SeparatorSplitter {
+        async fn split_text(&self, text: &str) -> Result<Vec<String>, TextSplitterError> {
+            Ok(text
+                .split(self.0)
+                .map(str::trim)
+                .filter(|chunk| !chunk.is_empty())
+                .map(String::from)
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parent_document_retriever() {
+        let store = InMemoryStore::new(Arc::new(WordCountEmbedder), DistanceMetric::Cosine);
+        let retriever =
+            ParentDocumentRetriever::new(store, InMemoryDocStore::new(), SeparatorSplitter("."), 2)
+                .with_parent_splitter(SeparatorSplitter("\n\n"));
+        let ids = retriever
+            .add_documents(&[Document::new(
+                "The cat sleeps. The dog barks.\n\nThe fish swims. The bird sings.",
+            )])
+            .await
+            .unwrap();
+        assert_eq!(ids.len(), 2);
+
+        let found = retriever.get_relevant_documents("dog").await.unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].page_content, "The cat sleeps. The dog barks.");
+        assert_eq!(found[0].id.as_ref(), Some(&ids[0]));
+
+        let found = retriever.get_relevant_documents("bird fish").await.unwrap();
+        assert_eq!(found[0].page_content, "The fish swims. The bird sings.");
+    }
+}
@@ -0,0 +1,77 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+
+use crate::{
+    callbacks::trace_retrieval,
+    reranker::Reranker,
+    schemas::{Document, Retriever},
+};
+
+/// A retriever reranking the documents of another retriever with a [`Reranker`], typically
+/// fetching more documents than needed by similarity and keeping the `top_n` best reranked.
+///
+/// # Usage
+/// ```rust,ignore
+/// let retriever = RerankRetriever::new(
+///     vectorstore::Retriever::new(store, 20),
+///     HttpReranker::cohere().with_top_n(5),
+/// );
+/// ```
+pub struct RerankRetriever {
+    retriever: Box<dyn Retriever>,
+    reranker: Box<dyn Reranker>,
+}
+
+impl RerankRetriever {
+    pub fn new<R: Into<Box<dyn Retriever>>, K: Into<Box<dyn Reranker>>>(
+        retriever: R,
+        reranker: K,
+    ) -> Self {
+        Self {
+            retriever: retriever.into(),
+            reranker: reranker.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Retriever for RerankRetriever {
+    async fn get_relevant_documents(&self, query: &str) -> Result<Vec<Document>, Box<dyn Error>> {
+        trace_retrieval(query, async {
+            let docs = self.retriever.get_relevant_documents(query).await?;
+            if docs.is_empty() {
+                return Ok(docs);
+            }
+            Ok(self.reranker.rerank(query, docs).await?)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{llm::fake::FakeLLM, reranker::LLMReranker, retrievers::Bm25Retriever};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rerank_retriever() {
+        let docs = vec![
+            Document::new("The cat sleeps"),
+            Document::new("The dog barks at the cat"),
+        ];
+        let retriever = RerankRetriever::new(
+            Bm25Retriever::new(docs, 2),
+            LLMReranker::new(FakeLLM::fixed("3")).with_top_n(1),
+        );
+        let found = retriever.get_relevant_documents("cat").await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].score, 0.3);
+        assert!(retriever
+            .get_relevant_documents("fish")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
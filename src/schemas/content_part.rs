@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::ImageContent;
+
+/// Where the data of an image, audio or file part comes from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MediaSource {
+    Url { url: String },
+    Base64 { media_type: String, data: String },
+}
+
+impl MediaSource {
+    /// Returns the URL of the data, as a `data:` URL for base64 data.
+    pub fn to_url(&self) -> String {
+        match self {
+            MediaSource::Url { url } => url.clone(),
+            MediaSource::Base64 { media_type, data } => format!("data:{media_type};base64,{data}"),
+        }
+    }
+
+    /// Returns the media type and the data of base64 data, also parsing `data:` URLs.
+    pub fn to_base64(&self) -> Option<(String, String)> {
+        match self {
+            MediaSource::Base64 { media_type, data } => Some((media_type.clone(), data.clone())),
+            MediaSource::Url { url } => {
+                let (media_type, data) = url.strip_prefix("data:")?.split_once(";base64,")?;
+                Some((media_type.into(), data.into()))
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PartContent {
+    Text {
+        text: String,
+    },
+    Image {
+        source: MediaSource,
+        /// The level of detail of the image, `low`, `high` or `auto`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        detail: Option<String>,
+    },
+    Audio {
+        source: MediaSource,
+    },
+    File {
+        source: MediaSource,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        filename: Option<String>,
+    },
+}
+
+/// A part of the content of a [`Message`](super::Message): text, an image, audio or a file.
+///
+/// `hints` holds provider-specific settings, keyed by provider (`openai`, `anthropic`...). The
+/// object of a provider is merged into the native representation of the part by the clients
+/// building their requests as JSON, e.g.
+/// `{"anthropic": {"cache_control": {"type": "ephemeral"}}}`.
+///
+/// # Usage
+/// ```rust,ignore
+/// let message = Message::new_human_message("").with_parts([
+///     ContentPart::text("What is in this image?"),
+///     ContentPart::image_url("https://example.com/image.png"),
+/// ]);
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ContentPart {
+    #[serde(flatten)]
+    pub content: PartContent,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub hints: HashMap<String, Value>,
+}
+
+impl ContentPart {
+    pub fn new(content: PartContent) -> Self {
+        Self {
+            content,
+            hints: HashMap::new(),
+        }
+    }
+
+    pub fn text<S: Into<String>>(text: S) -> Self {
+        Self::new(PartContent::Text { text: text.into() })
+    }
+
+    pub fn image_url<S: Into<String>>(url: S) -> Self {
+        Self::new(PartContent::Image {
+            source: MediaSource::Url { url: url.into() },
+            detail: None,
+        })
+    }
+
+    pub fn image_base64<S: Into<String>>(media_type: S, data: S) -> Self {
+        Self::new(PartContent::Image {
+            source: MediaSource::Base64 {
+                media_type: media_type.into(),
+                data: data.into(),
+            },
+            detail: None,
+        })
+    }
+
+    /// Creates an audio part from base64 data, with a media type such as `audio/wav`.
+    pub fn audio_base64<S: Into<String>>(media_type: S, data: S) -> Self {
+        Self::new(PartContent::Audio {
+            source: MediaSource::Base64 {
+                media_type: media_type.into(),
+                data: data.into(),
+            },
+        })
+    }
+
+    /// Creates a file part from base64 data, with a media type such as `application/pdf`.
+    pub fn file_base64<S: Into<String>>(media_type: S, data: S, filename: Option<String>) -> Self {
+        Self::new(PartContent::File {
+            source: MediaSource::Base64 {
+                media_type: media_type.into(),
+                data: data.into(),
+            },
+            filename,
+        })
+    }
+
+    /// Sets the hint of `provider`, replacing the previous one.
+    pub fn with_hint<S: Into<String>>(mut self, provider: S, hint: Value) -> Self {
+        self.hints.insert(provider.into(), hint);
+        self
+    }
+
+    /// Returns the hint of `provider`, if any.
+    pub fn hint(&self, provider: &str) -> Option<&Value> {
+        self.hints.get(provider)
+    }
+
+    /// Returns the text of a text part.
+    pub fn as_text(&self) -> Option<&str> {
+        match &self.content {
+            PartContent::Text { text } => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the kind of the part: `text`, `image`, `audio` or `file`.
+    pub fn kind(&self) -> &'static str {
+        match self.content {
+            PartContent::Text { .. } => "text",
+            PartContent::Image { .. } => "image",
+            PartContent::Audio { .. } => "audio",
+            PartContent::File { .. } => "file",
+        }
+    }
+}
+
+impl From<ImageContent> for ContentPart {
+    fn from(image: ImageContent) -> Self {
+        Self::new(PartContent::Image {
+            source: MediaSource::Url {
+                url: image.image_url,
+            },
+            detail: image.detail,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_content_part_serde() {
+        let part = ContentPart::image_base64("image/png", "aGk=").with_hint(
+            "anthropic",
+            json!({ "cache_control": { "type": "ephemeral" } }),
+        );
+
+        let value = serde_json::to_value(&part).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "type": "image",
+                "source": { "type": "base64", "media_type": "image/png", "data": "aGk=" },
+                "hints": { "anthropic": { "cache_control": { "type": "ephemeral" } } },
+            })
+        );
+        assert_eq!(serde_json::from_value::<ContentPart>(value).unwrap(), part);
+    }
+
+    #[test]
+    fn test_media_source_data_url() {
+        let source = MediaSource::Base64 {
+            media_type: "image/png".into(),
+            data: "aGk=".into(),
+        };
+        let url = MediaSource::Url {
+            url: source.to_url(),
+        };
+        assert_eq!(url.to_url(), "data:image/png;base64,aGk=");
+        assert_eq!(url.to_base64(), source.to_base64());
+    }
+}
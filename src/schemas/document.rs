@@ -7,6 +7,7 @@ use serde_json::Value;
 /// The `page_content` field is a string that contains the content of the document.
 /// The `metadata` field is a `HashMap` where the keys represent metadata properties and the values represent property values.
 /// The `score` field represents a relevance score for the document and is a floating point number.
+/// The `id` field is the id of the document in a vector store, if any.
 ///
 /// # Usage
 /// ```rust,ignore
@@ -23,6 +24,8 @@ pub struct Document {
     pub page_content: String,
     pub metadata: HashMap<String, Value>,
     pub score: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
 }
 
 impl Document {
@@ -32,6 +35,7 @@ impl Document {
             page_content: page_content.into(),
             metadata: HashMap::new(),
             score: 0.0,
+            id: None,
         }
     }
 
@@ -46,6 +50,12 @@ impl Document {
         self.score = score;
         self
     }
+
+    /// Sets the `id` of the `Document`. Vector stores replace the document with the same id.
+    pub fn with_id<S: Into<String>>(mut self, id: S) -> Self {
+        self.id = Some(id.into());
+        self
+    }
 }
 
 impl Default for Document {
@@ -55,6 +65,7 @@ impl Default for Document {
             page_content: "".to_string(),
             metadata: HashMap::new(),
             score: 0.0,
+            id: None,
         }
     }
 }
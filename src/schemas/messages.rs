@@ -2,22 +2,42 @@ use std::fmt;
 
 use async_openai::error::OpenAIError;
 use async_openai::types::ChatCompletionRequestAssistantMessageArgs;
+use async_openai::types::ChatCompletionRequestAssistantMessageContent;
+use async_openai::types::ChatCompletionRequestAssistantMessageContentPart;
+use async_openai::types::ChatCompletionRequestDeveloperMessageContent;
 use async_openai::types::ChatCompletionRequestMessage;
+use async_openai::types::ChatCompletionRequestMessageContentPartAudioArgs;
 use async_openai::types::ChatCompletionRequestMessageContentPartImageArgs;
+use async_openai::types::ChatCompletionRequestMessageContentPartTextArgs;
 use async_openai::types::ChatCompletionRequestSystemMessageArgs;
+use async_openai::types::ChatCompletionRequestSystemMessageContent;
+use async_openai::types::ChatCompletionRequestSystemMessageContentPart;
 use async_openai::types::ChatCompletionRequestToolMessageArgs;
+use async_openai::types::ChatCompletionRequestToolMessageContent;
+use async_openai::types::ChatCompletionRequestToolMessageContentPart;
 use async_openai::types::ChatCompletionRequestUserMessageArgs;
 use async_openai::types::ChatCompletionRequestUserMessageContent;
+use async_openai::types::ChatCompletionRequestUserMessageContentPart;
+use async_openai::types::ImageDetail;
+use async_openai::types::ImageUrlArgs;
+use async_openai::types::InputAudio;
+use async_openai::types::InputAudioFormat;
 use serde::Deserialize;
 use serde::Serialize;
+use serde_json::json;
+use serde_json::Value;
 
+use super::ContentPart;
+use super::MediaSource;
 use super::MessageType;
+use super::PartContent;
 use super::ToolCall;
 
 /// Struct `ImageContent` represents an image provided to an LLM.
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct ImageContent {
     pub image_url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub detail: Option<String>,
 }
 
@@ -38,13 +58,19 @@ impl<S: AsRef<str>> From<S> for ImageContent {
 /// let system_message = Message::new_system_message("System Alert");
 /// let ai_message = Message::new_ai_message("AI Response");
 /// ```
-#[derive(Debug, Default, Clone)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct Message {
     pub content: String,
     pub message_type: MessageType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub tool_calls: Option<Vec<ToolCall>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub images: Option<Vec<ImageContent>>,
+    /// The ordered parts of the content, for multimodal messages. `content` holds their text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parts: Option<Vec<ContentPart>>,
 }
 
 impl Message {
@@ -55,6 +81,7 @@ impl Message {
             id: None,
             tool_calls: None,
             images: None,
+            parts: None,
         }
     }
 
@@ -65,6 +92,7 @@ impl Message {
             id: None,
             tool_calls: None,
             images: None,
+            parts: None,
         }
     }
 
@@ -75,6 +103,7 @@ impl Message {
             id: None,
             tool_calls: None,
             images: None,
+            parts: None,
         }
     }
 
@@ -85,6 +114,7 @@ impl Message {
             id: None,
             tool_calls: None,
             images: None,
+            parts: None,
         }
     }
 
@@ -103,6 +133,7 @@ impl Message {
             id: id.map(|id| id.into()),
             tool_calls: None,
             images: None,
+            parts: None,
         }
     }
 
@@ -125,6 +156,43 @@ impl Message {
         self
     }
 
+    /// Sets the ordered parts of the content, and the text of the message to the text of the
+    /// parts.
+    pub fn with_parts(mut self, parts: impl IntoIterator<Item = ContentPart>) -> Self {
+        let parts: Vec<ContentPart> = parts.into_iter().collect();
+        self.content = parts
+            .iter()
+            .filter_map(ContentPart::as_text)
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.parts = Some(parts);
+        self
+    }
+
+    /// Returns the ordered parts of the content: `parts` when set, else the text followed by the
+    /// images.
+    pub fn content_parts(&self) -> Vec<ContentPart> {
+        if let Some(parts) = &self.parts {
+            return parts.clone();
+        }
+        let mut parts = Vec::new();
+        if !self.content.is_empty() {
+            parts.push(ContentPart::text(self.content.clone()));
+        }
+        parts.extend(self.images.iter().flatten().cloned().map(ContentPart::from));
+        parts
+    }
+
+    /// Returns whether the message has content other than text.
+    pub fn is_multimodal(&self) -> bool {
+        self.images.is_some()
+            || self
+                .parts
+                .iter()
+                .flatten()
+                .any(|part| part.as_text().is_none())
+    }
+
     pub fn messages_to_string(messages: &[Message]) -> String {
         messages
             .iter()
@@ -149,6 +217,20 @@ impl fmt::Display for Message {
                 "{}: {}\nImages: {:?}",
                 self.message_type, self.content, images
             )
+        } else if self.is_multimodal() {
+            let kinds = self
+                .content_parts()
+                .iter()
+                .filter(|part| part.as_text().is_none())
+                .map(ContentPart::kind)
+                .collect::<Vec<_>>();
+            write!(
+                f,
+                "{}: {}\nAttachments: {}",
+                self.message_type,
+                self.content,
+                kinds.join(", ")
+            )
         } else if !self.content.is_empty() {
             write!(f, "{}: {}", self.message_type, self.content)
         } else {
@@ -181,18 +263,15 @@ impl TryFrom<Message> for ChatCompletionRequestMessage {
                     .into(),
             }),
             MessageType::Human => {
-                let content: ChatCompletionRequestUserMessageContent = match value.images {
-                    Some(images) => images
+                let content: ChatCompletionRequestUserMessageContent = if value.is_multimodal() {
+                    value
+                        .content_parts()
                         .into_iter()
-                        .map(|image| {
-                            ChatCompletionRequestMessageContentPartImageArgs::default()
-                                .image_url(image.image_url)
-                                .build()
-                                .map(Into::into)
-                        })
+                        .map(ChatCompletionRequestUserMessageContentPart::try_from)
                         .collect::<Result<Vec<_>, _>>()?
-                        .into(),
-                    None => value.content.into(),
+                        .into()
+                } else {
+                    value.content.into()
                 };
 
                 Ok(ChatCompletionRequestUserMessageArgs::default()
@@ -212,3 +291,267 @@ impl TryFrom<Message> for ChatCompletionRequestMessage {
         }
     }
 }
+
+impl TryFrom<ChatCompletionRequestMessage> for Message {
+    type Error = serde_json::Error;
+
+    fn try_from(value: ChatCompletionRequestMessage) -> Result<Self, Self::Error> {
+        match value {
+            ChatCompletionRequestMessage::Developer(message) => {
+                Ok(Message::new_system_message(match message.content {
+                    ChatCompletionRequestDeveloperMessageContent::Text(text) => text,
+                    ChatCompletionRequestDeveloperMessageContent::Array(parts) => {
+                        join_texts(parts.into_iter().map(|part| part.text))
+                    }
+                }))
+            }
+            ChatCompletionRequestMessage::System(message) => {
+                Ok(Message::new_system_message(match message.content {
+                    ChatCompletionRequestSystemMessageContent::Text(text) => text,
+                    ChatCompletionRequestSystemMessageContent::Array(parts) => {
+                        join_texts(parts.into_iter().map(|part| match part {
+                            ChatCompletionRequestSystemMessageContentPart::Text(part) => part.text,
+                        }))
+                    }
+                }))
+            }
+            ChatCompletionRequestMessage::User(message) => match message.content {
+                ChatCompletionRequestUserMessageContent::Text(text) => {
+                    Ok(Message::new_human_message(text))
+                }
+                ChatCompletionRequestUserMessageContent::Array(parts) => {
+                    let parts: Vec<ContentPart> = parts.into_iter().map(Into::into).collect();
+                    if parts.iter().all(|part| part.as_text().is_some()) {
+                        Ok(Message::new_human_message(join_texts(
+                            parts.iter().filter_map(|p| p.as_text().map(String::from)),
+                        )))
+                    } else {
+                        Ok(Message::new_human_message("").with_parts(parts))
+                    }
+                }
+            },
+            ChatCompletionRequestMessage::Assistant(message) => {
+                let content = match message.content {
+                    Some(ChatCompletionRequestAssistantMessageContent::Text(text)) => text,
+                    Some(ChatCompletionRequestAssistantMessageContent::Array(parts)) => {
+                        join_texts(parts.into_iter().map(|part| match part {
+                            ChatCompletionRequestAssistantMessageContentPart::Text(part) => {
+                                part.text
+                            }
+                            ChatCompletionRequestAssistantMessageContentPart::Refusal(part) => {
+                                part.refusal
+                            }
+                        }))
+                    }
+                    None => message.refusal.unwrap_or_default(),
+                };
+                #[allow(deprecated)]
+                let tool_calls = match (message.tool_calls, message.function_call) {
+                    (Some(tool_calls), _) => Some(
+                        tool_calls
+                            .into_iter()
+                            .map(TryInto::try_into)
+                            .collect::<Result<Vec<ToolCall>, _>>()?,
+                    ),
+                    (None, Some(function_call)) => Some(vec![function_call.try_into()?]),
+                    (None, None) => None,
+                };
+                let message = Message::new_ai_message(content);
+                Ok(match tool_calls {
+                    Some(tool_calls) => message.with_tool_calls(tool_calls),
+                    None => message,
+                })
+            }
+            ChatCompletionRequestMessage::Tool(message) => Ok(Message::new_tool_message(
+                Some(message.tool_call_id),
+                match message.content {
+                    ChatCompletionRequestToolMessageContent::Text(text) => text,
+                    ChatCompletionRequestToolMessageContent::Array(parts) => {
+                        join_texts(parts.into_iter().map(|part| match part {
+                            ChatCompletionRequestToolMessageContentPart::Text(part) => part.text,
+                        }))
+                    }
+                },
+            )),
+            ChatCompletionRequestMessage::Function(message) => Ok(Message::new_tool_message(
+                Some(message.name),
+                message.content.unwrap_or_default(),
+            )),
+        }
+    }
+}
+
+impl Message {
+    /// Returns the message in the format of the OpenAI chat completions API. Unlike the
+    /// conversion to [`ChatCompletionRequestMessage`], it keeps the file parts and merges the
+    /// `openai` hint of each part into it.
+    pub fn to_openai_value(&self) -> Result<Value, OpenAIError> {
+        let parts = self.content_parts();
+        let needs_json = self.message_type == MessageType::Human
+            && parts.iter().any(|part| {
+                matches!(part.content, PartContent::File { .. }) || part.hint("openai").is_some()
+            });
+        if !needs_json {
+            let message = ChatCompletionRequestMessage::try_from(self.clone())?;
+            return serde_json::to_value(message).map_err(OpenAIError::JSONDeserialize);
+        }
+
+        let content = parts
+            .iter()
+            .map(openai_part_value)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(json!({ "role": "user", "content": content }))
+    }
+}
+
+/// Returns the OpenAI content part of `part`, with its `openai` hint merged in.
+fn openai_part_value(part: &ContentPart) -> Result<Value, OpenAIError> {
+    let mut value = match &part.content {
+        PartContent::File { source, filename } => {
+            if source.to_base64().is_none() {
+                return Err(OpenAIError::InvalidArgument(
+                    "File parts must be base64 data".into(),
+                ));
+            }
+            let mut file = json!({ "file_data": source.to_url() });
+            if let Some(filename) = filename {
+                file["filename"] = filename.as_str().into();
+            }
+            json!({ "type": "file", "file": file })
+        }
+        _ => {
+            let part = ChatCompletionRequestUserMessageContentPart::try_from(part.clone())?;
+            serde_json::to_value(part).map_err(OpenAIError::JSONDeserialize)?
+        }
+    };
+    if let (Some(Value::Object(hint)), Value::Object(value)) = (part.hint("openai"), &mut value) {
+        value.extend(hint.clone());
+    }
+    Ok(value)
+}
+
+fn join_texts(texts: impl IntoIterator<Item = String>) -> String {
+    texts.into_iter().collect::<Vec<_>>().join("\n")
+}
+
+impl TryFrom<ContentPart> for ChatCompletionRequestUserMessageContentPart {
+    type Error = OpenAIError;
+
+    fn try_from(value: ContentPart) -> Result<Self, Self::Error> {
+        match value.content {
+            PartContent::Text { text } => {
+                Ok(ChatCompletionRequestMessageContentPartTextArgs::default()
+                    .text(text)
+                    .build()?
+                    .into())
+            }
+            PartContent::Image { source, detail } => {
+                let mut image_url = ImageUrlArgs::default();
+                image_url.url(source.to_url());
+                if let Some(detail) = detail {
+                    image_url.detail(match detail.as_str() {
+                        "low" => ImageDetail::Low,
+                        "high" => ImageDetail::High,
+                        _ => ImageDetail::Auto,
+                    });
+                }
+                Ok(ChatCompletionRequestMessageContentPartImageArgs::default()
+                    .image_url(image_url.build()?)
+                    .build()?
+                    .into())
+            }
+            PartContent::Audio { source } => {
+                let (media_type, data) = source.to_base64().ok_or_else(|| {
+                    OpenAIError::InvalidArgument("Audio parts must be base64 data".into())
+                })?;
+                let format = match media_type.as_str() {
+                    "audio/wav" | "audio/x-wav" => InputAudioFormat::Wav,
+                    "audio/mpeg" | "audio/mp3" => InputAudioFormat::Mp3,
+                    _ => {
+                        return Err(OpenAIError::InvalidArgument(format!(
+                            "Unsupported audio format: {media_type}"
+                        )))
+                    }
+                };
+                Ok(ChatCompletionRequestMessageContentPartAudioArgs::default()
+                    .input_audio(InputAudio { data, format })
+                    .build()?
+                    .into())
+            }
+            PartContent::File { .. } => Err(OpenAIError::InvalidArgument(
+                "File parts have no typed representation, use Message::to_openai_value".into(),
+            )),
+        }
+    }
+}
+
+impl From<ChatCompletionRequestUserMessageContentPart> for ContentPart {
+    fn from(value: ChatCompletionRequestUserMessageContentPart) -> Self {
+        match value {
+            ChatCompletionRequestUserMessageContentPart::Text(part) => ContentPart::text(part.text),
+            ChatCompletionRequestUserMessageContentPart::ImageUrl(part) => {
+                ContentPart::new(PartContent::Image {
+                    source: MediaSource::Url {
+                        url: part.image_url.url,
+                    },
+                    detail: part.image_url.detail.map(|detail| {
+                        match detail {
+                            ImageDetail::Auto => "auto",
+                            ImageDetail::Low => "low",
+                            ImageDetail::High => "high",
+                        }
+                        .into()
+                    }),
+                })
+            }
+            ChatCompletionRequestUserMessageContentPart::InputAudio(part) => {
+                let media_type = match part.input_audio.format {
+                    InputAudioFormat::Wav => "audio/wav",
+                    InputAudioFormat::Mp3 => "audio/mpeg",
+                };
+                ContentPart::audio_base64(media_type.to_string(), part.input_audio.data)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_openai_value() {
+        let message = Message::new_human_message("").with_parts([
+            ContentPart::text("Summarize the report"),
+            ContentPart::file_base64("application/pdf", "JVBERi0=", Some("report.pdf".into())),
+            ContentPart::image_url("https://example.com/chart.png")
+                .with_hint("openai", json!({ "image_url": { "url": "https://example.com/chart.png", "detail": "high" } }))
+                .with_hint("anthropic", json!({ "cache_control": { "type": "ephemeral" } })),
+        ]);
+
+        assert_eq!(
+            message.to_openai_value().unwrap(),
+            json!({
+                "role": "user",
+                "content": [
+                    { "type": "text", "text": "Summarize the report" },
+                    {
+                        "type": "file",
+                        "file": {
+                            "file_data": "data:application/pdf;base64,JVBERi0=",
+                            "filename": "report.pdf",
+                        },
+                    },
+                    {
+                        "type": "image_url",
+                        "image_url": { "url": "https://example.com/chart.png", "detail": "high" },
+                    },
+                ],
+            })
+        );
+        assert_eq!(
+            Message::new_ai_message("Hi").to_openai_value().unwrap(),
+            json!({ "role": "assistant", "content": "Hi" })
+        );
+    }
+}
@@ -4,6 +4,9 @@ pub use message_type::*;
 pub mod messages;
 pub use messages::*;
 
+mod content_part;
+pub use content_part::*;
+
 pub mod prompt;
 pub use prompt::*;
 
@@ -31,6 +34,9 @@ pub use builder_error::*;
 mod tool_call;
 pub use tool_call::*;
 
+mod transcript;
+pub use transcript::*;
+
 mod with_usage;
 pub use with_usage::*;
 
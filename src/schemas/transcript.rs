@@ -0,0 +1,164 @@
+use async_openai::{error::OpenAIError, types::ChatCompletionRequestMessage};
+use thiserror::Error;
+
+use super::Message;
+
+#[derive(Error, Debug)]
+pub enum TranscriptError {
+    #[error("Serde error: {0}")]
+    SerdeError(#[from] serde_json::Error),
+
+    #[error("OpenAI error: {0}")]
+    OpenAIError(#[from] OpenAIError),
+}
+
+/// Import and export of conversation transcripts.
+///
+/// Two formats are supported: the OpenAI chat format, a JSON array of the `messages` of a chat
+/// completion request, and JSONL, with the serde representation of a [`Message`] per line.
+impl Message {
+    /// Exports the messages as they are sent to OpenAI, see [`Message::to_openai_value`].
+    pub fn messages_to_openai_json(messages: &[Message]) -> Result<String, TranscriptError> {
+        let messages = messages
+            .iter()
+            .map(Message::to_openai_value)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(serde_json::to_string(&messages)?)
+    }
+
+    pub fn messages_from_openai_json(json: &str) -> Result<Vec<Message>, TranscriptError> {
+        let messages: Vec<ChatCompletionRequestMessage> = serde_json::from_str(json)?;
+        Ok(messages
+            .into_iter()
+            .map(Message::try_from)
+            .collect::<Result<Vec<_>, _>>()?)
+    }
+
+    pub fn messages_to_jsonl(messages: &[Message]) -> Result<String, serde_json::Error> {
+        let mut jsonl = String::new();
+        for message in messages {
+            jsonl.push_str(&serde_json::to_string(message)?);
+            jsonl.push('\n');
+        }
+        Ok(jsonl)
+    }
+
+    /// Parses a JSONL transcript, skipping the blank lines.
+    pub fn messages_from_jsonl(jsonl: &str) -> Result<Vec<Message>, serde_json::Error> {
+        jsonl
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::schemas::{ContentPart, MessageType, Prompt, ToolCall};
+
+    use super::*;
+
+    fn conversation() -> Vec<Message> {
+        vec![
+            Message::new_system_message("You are a calculator"),
+            Message::new_human_message("").with_parts([
+                ContentPart::text("What is in the picture?"),
+                ContentPart::image_url("https://example.com/sum.png"),
+                ContentPart::audio_base64("audio/wav", "UklGRg=="),
+            ]),
+            Message::new_tool_call_message([ToolCall::new(
+                "call_1",
+                "calculator",
+                json!({ "expression": "2 + 2" }),
+            )]),
+            Message::new_tool_message(Some("call_1"), "4"),
+            Message::new_ai_message("The sum is 4"),
+        ]
+    }
+
+    fn assert_same(left: &[Message], right: &[Message]) {
+        assert_eq!(
+            serde_json::to_value(left).unwrap(),
+            serde_json::to_value(right).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_jsonl_round_trip() {
+        let messages = conversation();
+        let jsonl = Message::messages_to_jsonl(&messages).unwrap();
+        assert_eq!(jsonl.lines().count(), 5);
+
+        let first: Value = serde_json::from_str(jsonl.lines().next().unwrap()).unwrap();
+        assert_eq!(
+            first,
+            json!({ "content": "You are a calculator", "message_type": "system" })
+        );
+
+        assert_same(&Message::messages_from_jsonl(&jsonl).unwrap(), &messages);
+    }
+
+    #[test]
+    fn test_openai_json_round_trip() {
+        let messages = conversation();
+        let json = Message::messages_to_openai_json(&messages).unwrap();
+
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value[1]["role"], "user");
+        assert_eq!(value[1]["content"][0]["text"], "What is in the picture?");
+        assert_eq!(value[1]["content"][2]["input_audio"]["format"], "wav");
+        assert_eq!(value[2]["tool_calls"][0]["function"]["name"], "calculator");
+        assert_eq!(value[3]["tool_call_id"], "call_1");
+
+        assert_same(
+            &Message::messages_from_openai_json(&json).unwrap(),
+            &messages,
+        );
+    }
+
+    #[test]
+    fn test_openai_json_export_keeps_files_and_hints() {
+        let messages = [Message::new_human_message("").with_parts([
+            ContentPart::file_base64("application/pdf", "JVBERi0=", Some("report.pdf".into())),
+            ContentPart::image_url("https://example.com/chart.png").with_hint(
+                "openai",
+                json!({ "image_url": { "url": "https://example.com/chart.png", "detail": "high" } }),
+            ),
+        ])];
+
+        let json = Message::messages_to_openai_json(&messages).unwrap();
+
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value[0]["content"][0]["file"]["filename"], "report.pdf");
+        assert_eq!(value[0]["content"][1]["image_url"]["detail"], "high");
+    }
+
+    #[test]
+    fn test_openai_json_import() {
+        let messages = Message::messages_from_openai_json(
+            &json!([
+                { "role": "developer", "content": "Be brief" },
+                { "role": "user", "content": [{ "type": "text", "text": "Hi" }] },
+                { "role": "assistant", "content": "Hello" },
+            ])
+            .to_string(),
+        )
+        .unwrap();
+
+        assert_eq!(messages[0].message_type, MessageType::System);
+        assert_eq!(messages[1].content, "Hi");
+        assert!(messages[1].images.is_none());
+        assert_eq!(messages[2].message_type, MessageType::Ai);
+    }
+
+    #[test]
+    fn test_prompt_serde() {
+        let prompt = Prompt::new(conversation());
+        let json = serde_json::to_string(&prompt).unwrap();
+        let prompt: Prompt = serde_json::from_str(&json).unwrap();
+        assert_same(&prompt.to_messages(), &conversation());
+    }
+}
@@ -0,0 +1,288 @@
+//! Spans following the OpenTelemetry semantic conventions for generative AI.
+//!
+//! The spans are created with `tracing` when the `otel` feature is enabled, and can be exported
+//! to any OpenTelemetry backend with a `tracing-opentelemetry` layer, re-exported with the
+//! `opentelemetry` crate it is built on. Span names are given in the `otel.name` field, and
+//! attributes use the `gen_ai.*` names of the conventions. Integers are recorded as `i64`, the
+//! only integer type OpenTelemetry attributes have.
+
+use std::{fmt::Display, future::Future};
+
+use async_stream::stream;
+use futures::{pin_mut, Stream, StreamExt};
+use tracing::{Instrument, Span};
+
+use crate::{llm::options::CallOptions, schemas::TokenUsage};
+
+/// Returns the span of a chat completion request to `model`.
+pub(crate) fn chat_span(system: &str, model: &str, options: &CallOptions) -> Span {
+    if !cfg!(feature = "otel") {
+        return Span::none();
+    }
+    tracing::info_span!(
+        "gen_ai.chat",
+        otel.name = %format!("chat {model}"),
+        otel.kind = "client",
+        otel.status_code = tracing::field::Empty,
+        gen_ai.operation.name = "chat",
+        gen_ai.system = system,
+        gen_ai.request.model = model,
+        gen_ai.request.temperature = options.temperature.map(f64::from),
+        gen_ai.request.max_tokens = options.max_tokens.map(i64::from),
+        gen_ai.response.finish_reasons = tracing::field::Empty,
+        gen_ai.usage.input_tokens = tracing::field::Empty,
+        gen_ai.usage.output_tokens = tracing::field::Empty,
+        error.type = tracing::field::Empty,
+    )
+}
+
+/// Returns the span of an embeddings request to `model`.
+pub(crate) fn embeddings_span(system: &str, model: &str, inputs: usize) -> Span {
+    if !cfg!(feature = "otel") {
+        return Span::none();
+    }
+    tracing::info_span!(
+        "gen_ai.embeddings",
+        otel.name = %format!("embeddings {model}"),
+        otel.kind = "client",
+        otel.status_code = tracing::field::Empty,
+        gen_ai.operation.name = "embeddings",
+        gen_ai.system = system,
+        gen_ai.request.model = model,
+        gen_ai.request.input_count = inputs as i64,
+        error.type = tracing::field::Empty,
+    )
+}
+
+/// Returns the span of the execution of the tool `name`.
+pub(crate) fn tool_span(name: &str) -> Span {
+    if !cfg!(feature = "otel") {
+        return Span::none();
+    }
+    tracing::info_span!(
+        "gen_ai.execute_tool",
+        otel.name = %format!("execute_tool {name}"),
+        otel.kind = "internal",
+        otel.status_code = tracing::field::Empty,
+        gen_ai.operation.name = "execute_tool",
+        gen_ai.tool.name = name,
+        error.type = tracing::field::Empty,
+    )
+}
+
+/// Returns the span of a similarity search in the vector store `system`.
+pub(crate) fn similarity_search_span(system: &str, limit: usize) -> Span {
+    if !cfg!(feature = "otel") {
+        return Span::none();
+    }
+    tracing::info_span!(
+        "db.similarity_search",
+        otel.name = %format!("similarity_search {system}"),
+        otel.kind = "client",
+        otel.status_code = tracing::field::Empty,
+        db.system = system,
+        db.operation.name = "similarity_search",
+        db.vector.query.top_k = limit as i64,
+        error.type = tracing::field::Empty,
+    )
+}
+
+/// Returns the span of the execution of an agent. Without the `otel` feature, the span is only
+/// created for agents with an id.
+pub(crate) fn agent_span(id: Option<String>) -> Span {
+    if cfg!(feature = "otel") {
+        return tracing::info_span!(
+            "agent_execution",
+            otel.name = "invoke_agent",
+            otel.kind = "internal",
+            gen_ai.operation.name = "invoke_agent",
+            gen_ai.agent.id = id.as_deref(),
+        );
+    }
+    match id {
+        Some(id) => tracing::info_span!("agent_execution", %id),
+        None => Span::none(),
+    }
+}
+
+/// Records the finish reason and the token usage of a chat completion response. The finish
+/// reasons are a string array in the conventions, recorded as a JSON array.
+pub(crate) fn record_response(
+    span: &Span,
+    finish_reason: Option<&str>,
+    usage: Option<&TokenUsage>,
+) {
+    if let Some(finish_reason) = finish_reason {
+        let finish_reasons = serde_json::json!([finish_reason]).to_string();
+        span.record("gen_ai.response.finish_reasons", finish_reasons.as_str());
+    }
+    if let Some(usage) = usage {
+        span.record("gen_ai.usage.input_tokens", i64::from(usage.prompt_tokens));
+        span.record(
+            "gen_ai.usage.output_tokens",
+            i64::from(usage.completion_tokens),
+        );
+    }
+}
+
+/// Marks the span as failed with `error`.
+pub(crate) fn record_error(span: &Span, error: &impl Display) {
+    span.record("otel.status_code", "ERROR");
+    span.record("error.type", error.to_string());
+}
+
+/// Runs `future` in `span`, marking the span as failed if the future returns an error.
+pub(crate) async fn in_span<T, E, F>(span: Span, future: F) -> Result<T, E>
+where
+    E: Display,
+    F: Future<Output = Result<T, E>>,
+{
+    let result = future.instrument(span.clone()).await;
+    if let Err(e) = &result {
+        record_error(&span, e);
+    }
+    result
+}
+
+/// Polls `stream` in `span`, which ends with the stream, marking the span as failed on errors.
+pub(crate) fn stream_in_span<T, E, S>(span: Span, stream: S) -> impl Stream<Item = Result<T, E>>
+where
+    E: Display,
+    S: Stream<Item = Result<T, E>>,
+{
+    stream! {
+        pin_mut!(stream);
+        while let Some(item) = stream.next().instrument(span.clone()).await {
+            if let Err(e) = &item {
+                record_error(&span, e);
+            }
+            yield item;
+        }
+    }
+}
+
+#[cfg(all(test, feature = "otel"))]
+mod tests {
+    use async_trait::async_trait;
+    use opentelemetry::{
+        trace::{Status, TracerProvider as _},
+        Value,
+    };
+    use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider, SpanData};
+    use tracing::subscriber::DefaultGuard;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use crate::tools::{DefaultToolInput, Tool, ToolDyn};
+
+    use super::*;
+
+    /// Exports the spans of the current thread to an in-memory OpenTelemetry exporter.
+    fn export_spans() -> (InMemorySpanExporter, DefaultGuard) {
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        (exporter, tracing::subscriber::set_default(subscriber))
+    }
+
+    fn find(exporter: &InMemorySpanExporter, name: &str) -> SpanData {
+        exporter
+            .get_finished_spans()
+            .unwrap()
+            .into_iter()
+            .find(|span| span.name == name)
+            .unwrap()
+    }
+
+    fn attribute(span: &SpanData, key: &str) -> Value {
+        span.attributes
+            .iter()
+            .find(|attribute| attribute.key.as_str() == key)
+            .map(|attribute| attribute.value.clone())
+            .unwrap()
+    }
+
+    struct Failing;
+
+    #[async_trait]
+    impl Tool for Failing {
+        type Input = DefaultToolInput;
+        type Output = String;
+
+        fn name(&self) -> String {
+            "failing".into()
+        }
+
+        fn description(&self) -> String {
+            "Always fails".into()
+        }
+
+        async fn run(
+            &self,
+            _input: Self::Input,
+        ) -> Result<Self::Output, Box<dyn std::error::Error + Send + Sync>> {
+            Err("boom".into())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spans_are_parented_under_agent_span() {
+        let (exporter, _guard) = export_spans();
+
+        let agent = agent_span(Some("agent".into()));
+        async {
+            let chat = chat_span(
+                "openai",
+                "gpt-4o",
+                &CallOptions::new().with_temperature(0.5),
+            );
+            in_span(chat.clone(), async {
+                record_response(&chat, Some("tool_calls"), Some(&TokenUsage::new(3, 5)));
+                Ok::<_, String>(())
+            })
+            .await
+            .unwrap();
+
+            let tool: Box<dyn ToolDyn> = Box::new(Failing);
+            assert!(tool.call(serde_json::json!("input")).await.is_err());
+        }
+        .instrument(agent)
+        .await;
+
+        let agent = find(&exporter, "invoke_agent");
+        assert_eq!(attribute(&agent, "gen_ai.agent.id"), "agent".into());
+
+        let chat = find(&exporter, "chat gpt-4o");
+        assert_eq!(chat.parent_span_id, agent.span_context.span_id());
+        assert_eq!(attribute(&chat, "gen_ai.operation.name"), "chat".into());
+        assert_eq!(attribute(&chat, "gen_ai.request.model"), "gpt-4o".into());
+        assert_eq!(attribute(&chat, "gen_ai.request.temperature"), 0.5.into());
+        assert_eq!(
+            attribute(&chat, "gen_ai.response.finish_reasons"),
+            r#"["tool_calls"]"#.into()
+        );
+        assert_eq!(attribute(&chat, "gen_ai.usage.input_tokens"), 3.into());
+        assert_eq!(attribute(&chat, "gen_ai.usage.output_tokens"), 5.into());
+
+        let tool = find(&exporter, "execute_tool failing");
+        assert_eq!(tool.parent_span_id, agent.span_context.span_id());
+        assert_eq!(attribute(&tool, "gen_ai.tool.name"), "failing".into());
+        assert!(matches!(tool.status, Status::Error { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_stream_span_ends_with_the_stream() {
+        let (exporter, _guard) = export_spans();
+
+        let chat = chat_span("anthropic", "claude", &CallOptions::new());
+        let chunks = futures::stream::iter(vec![Ok("Hello"), Err("overloaded")]);
+        let results: Vec<_> = stream_in_span(chat, chunks).collect().await;
+
+        assert_eq!(results.len(), 2);
+        let chat = find(&exporter, "chat claude");
+        assert_eq!(attribute(&chat, "error.type"), "overloaded".into());
+        assert!(matches!(chat.status, Status::Error { .. }));
+    }
+}
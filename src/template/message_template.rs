@@ -1,4 +1,6 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, sync::LazyLock};
+
+use regex::{Captures, Regex};
 
 use crate::{
     chain::TextReplacements,
@@ -6,6 +8,11 @@ use crate::{
     template::TemplateError,
 };
 
+static FSTRING_VARIABLE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{(\w+)\}").expect("Static regex is valid"));
+static JINJA2_VARIABLE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{(\w+)\}\}").expect("Static regex is valid"));
+
 #[derive(Debug, Clone)]
 pub enum TemplateFormat {
     FString,
@@ -38,8 +45,7 @@ impl MessageTemplate {
     pub fn from_fstring(message_type: MessageType, content: impl Into<String>) -> Self {
         let content = content.into();
 
-        let re = regex::Regex::new(r"\{(\w+)\}").unwrap();
-        let variables = re
+        let variables = FSTRING_VARIABLE_RE
             .captures_iter(&content)
             .map(|cap| cap[1].to_string())
             .collect();
@@ -50,8 +56,7 @@ impl MessageTemplate {
     pub fn from_jinja2(message_type: MessageType, content: impl Into<String>) -> Self {
         let content = content.into();
 
-        let re = regex::Regex::new(r"\{\{(\w+)\}\}").unwrap();
-        let variables = re
+        let variables = JINJA2_VARIABLE_RE
             .captures_iter(&content)
             .map(|cap| cap[1].to_string())
             .collect();
@@ -62,15 +67,18 @@ impl MessageTemplate {
     pub fn format(&self, input: &TextReplacements) -> Result<Message, TemplateError> {
         self.validate_input(input)?;
 
-        let mut content = self.template.clone();
-
-        for (key, value) in input {
-            let key = match self.format {
-                TemplateFormat::FString => format!("{{{key}}}"),
-                TemplateFormat::Jinja2 => format!("{{{{{key}}}}}"),
-            };
-            content = content.replace(&key, value);
-        }
+        // The variables are replaced in one pass, so that a value containing a placeholder is
+        // kept as is.
+        let re = match self.format {
+            TemplateFormat::FString => &FSTRING_VARIABLE_RE,
+            TemplateFormat::Jinja2 => &JINJA2_VARIABLE_RE,
+        };
+        let content = re.replace_all(&self.template, |caps: &Captures| {
+            match input.get(&caps[1]) {
+                Some(value) => value.to_string(),
+                None => caps[0].to_string(),
+            }
+        });
 
         Ok(Message::new(self.message_type.clone(), content))
     }
@@ -111,6 +119,19 @@ mod tests {
         assert_eq!(message.content, "Hello Alice, how are you?");
     }
 
+    #[test]
+    fn test_fstring_template_keeps_placeholders_of_values() {
+        let template = MessageTemplate::from_fstring(MessageType::Human, "{question}\n{context}");
+
+        let input = HashMap::from([
+            ("question", "What does {context} mean?".into()),
+            ("context", "A context".into()),
+        ]);
+
+        let message = template.format(&input).unwrap();
+        assert_eq!(message.content, "What does {context} mean?\nA context");
+    }
+
     #[test]
     fn test_jinja2_template() {
         let template =
@@ -36,6 +36,9 @@ pub use command_executor::*;
 mod text2speech;
 pub use text2speech::*;
 
+mod speech2text;
+pub use speech2text::*;
+
 #[cfg(feature = "mcp")]
 mod mcp;
 #[cfg(feature = "mcp")]
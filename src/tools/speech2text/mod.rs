@@ -0,0 +1,5 @@
+mod openai;
+pub use openai::*;
+
+mod speech_to_text;
+pub use speech_to_text::*;
@@ -0,0 +1,143 @@
+use std::{error::Error, path::Path};
+
+use async_openai::{
+    config::{Config, OpenAIConfig},
+    types::{AudioInput, CreateTranscriptionRequestArgs},
+    Client,
+};
+use async_trait::async_trait;
+
+use crate::tools::{tool_input::DefaultToolInput, SpeechToText, Tool};
+
+/// Transcribes audio with the OpenAI transcription API, `whisper-1` by default.
+///
+/// It is also a tool transcribing the audio file at the path given as input.
+#[derive(Clone)]
+pub struct Speech2TextOpenAI<C: Config> {
+    api_config: C,
+    model: String,
+    language: Option<String>,
+    prompt: Option<String>,
+    temperature: Option<f32>,
+}
+
+impl<C: Config> Speech2TextOpenAI<C> {
+    pub fn new(api_config: C) -> Self {
+        Self {
+            api_config,
+            model: "whisper-1".to_string(),
+            language: None,
+            prompt: None,
+            temperature: None,
+        }
+    }
+
+    pub fn with_model<S: Into<String>>(mut self, model: S) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// Sets the language of the audio, in ISO-639-1 format, which improves accuracy and latency.
+    pub fn with_language<S: Into<String>>(mut self, language: S) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Sets a text guiding the style of the transcript, or continuing a previous segment.
+    pub fn with_prompt<S: Into<String>>(mut self, prompt: S) -> Self {
+        self.prompt = Some(prompt.into());
+        self
+    }
+
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    pub fn with_api_config(mut self, api_config: C) -> Self {
+        self.api_config = api_config;
+        self
+    }
+}
+
+impl Default for Speech2TextOpenAI<OpenAIConfig> {
+    fn default() -> Self {
+        Self::new(OpenAIConfig::default())
+    }
+}
+
+#[async_trait]
+impl<C: Config + Clone + Send + Sync> SpeechToText for Speech2TextOpenAI<C> {
+    async fn transcribe(
+        &self,
+        filename: &str,
+        audio: Vec<u8>,
+    ) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let client = Client::with_config(self.api_config.clone());
+
+        let mut request = CreateTranscriptionRequestArgs::default();
+        request
+            .file(AudioInput::from_vec_u8(filename.to_string(), audio))
+            .model(&self.model);
+        if let Some(language) = &self.language {
+            request.language(language);
+        }
+        if let Some(prompt) = &self.prompt {
+            request.prompt(prompt);
+        }
+        if let Some(temperature) = self.temperature {
+            request.temperature(temperature);
+        }
+
+        let response = client.audio().transcribe(request.build()?).await?;
+        Ok(response.text)
+    }
+}
+
+#[async_trait]
+impl<C: Config + Clone + Send + Sync> Tool for Speech2TextOpenAI<C> {
+    type Input = DefaultToolInput;
+    type Output = String;
+
+    fn name(&self) -> String {
+        "Speech2TextOpenAI".into()
+    }
+
+    fn description(&self) -> String {
+        "A wrapper around OpenAI Speech2Text. \
+        Useful for when you need to transcribe an audio file to text. \
+        The input should be the path of the audio file, in one of these formats: \
+        mp3, mp4, mpeg, mpga, m4a, wav, or webm"
+            .into()
+    }
+
+    async fn run(&self, input: Self::Input) -> Result<Self::Output, Box<dyn Error + Send + Sync>> {
+        self.transcribe_file(Path::new(&input.0)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use crate::tools::{Speech2TextOpenAI, ToolDyn};
+
+    #[test]
+    fn test_description_has_no_quotes() {
+        let description = ToolDyn::description(&Speech2TextOpenAI::default());
+        assert!(!description.contains('"'));
+        assert!(description.contains("Speech2Text. Useful"));
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn openai_speech2text_tool() {
+        let openai = Speech2TextOpenAI::default();
+        let s = openai
+            .call(Value::String("./data/audio.mp3".to_string()))
+            .await
+            .unwrap()
+            .data;
+        println!("{s}");
+    }
+}
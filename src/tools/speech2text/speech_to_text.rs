@@ -0,0 +1,79 @@
+use std::{error::Error, path::Path};
+
+use async_trait::async_trait;
+use base64::prelude::*;
+
+use crate::schemas::{ContentPart, PartContent};
+
+/// A transcription model, turning speech into text.
+#[async_trait]
+pub trait SpeechToText: Send + Sync {
+    /// Returns the transcript of `audio`, the content of a file named `filename`. The extension
+    /// of `filename` gives the format of the audio.
+    async fn transcribe(
+        &self,
+        filename: &str,
+        audio: Vec<u8>,
+    ) -> Result<String, Box<dyn Error + Send + Sync>>;
+
+    async fn transcribe_file(&self, path: &Path) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let filename = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| format!("Invalid audio file path: {}", path.display()))?
+            .to_string();
+        let audio = tokio::fs::read(path).await?;
+        self.transcribe(&filename, audio).await
+    }
+
+    /// Returns the transcript of an audio part with base64 data.
+    async fn transcribe_part(
+        &self,
+        part: &ContentPart,
+    ) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let PartContent::Audio { source } = &part.content else {
+            return Err(format!("Expected an audio part, got a {} part", part.kind()).into());
+        };
+        let (media_type, data) = source
+            .to_base64()
+            .ok_or("Only base64 audio parts can be transcribed")?;
+        let extension = match media_type.as_str() {
+            "audio/mpeg" | "audio/mp3" => "mp3",
+            "audio/x-wav" => "wav",
+            other => other.strip_prefix("audio/").unwrap_or("wav"),
+        };
+        let audio = BASE64_STANDARD.decode(data)?;
+        self.transcribe(&format!("audio.{extension}"), audio).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Answers the file name and the size of the audio.
+    struct FileInfo;
+
+    #[async_trait]
+    impl SpeechToText for FileInfo {
+        async fn transcribe(
+            &self,
+            filename: &str,
+            audio: Vec<u8>,
+        ) -> Result<String, Box<dyn Error + Send + Sync>> {
+            Ok(format!("{filename} {}", audio.len()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_part() {
+        let part = ContentPart::audio_base64("audio/mpeg", "SUQz");
+        assert_eq!(
+            FileInfo.transcribe_part(&part).await.unwrap(),
+            "audio.mp3 3"
+        );
+
+        let part = ContentPart::text("Hello");
+        assert!(FileInfo.transcribe_part(&part).await.is_err());
+    }
+}
@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+
+/// How the vectors are compared. The score of a document is higher for closer vectors, and
+/// normalized between 0 and 1 by [`DistanceMetric::normalized_score`] for the searches.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DistanceMetric {
+    /// The cosine similarity, between -1 and 1.
+    #[default]
+    Cosine,
+    /// The dot product, for normalized embeddings.
+    DotProduct,
+    /// The euclidean distance `d`, scored `1 / (1 + d)`, between 0 and 1.
+    Euclidean,
+}
+
+impl DistanceMetric {
+    pub fn score(&self, a: &[f64], b: &[f64]) -> f64 {
+        match self {
+            DistanceMetric::Cosine => {
+                let norm = norm(a) * norm(b);
+                if norm == 0.0 {
+                    0.0
+                } else {
+                    dot(a, b) / norm
+                }
+            }
+            DistanceMetric::DotProduct => dot(a, b),
+            DistanceMetric::Euclidean => {
+                let distance = a
+                    .iter()
+                    .zip(b)
+                    .map(|(x, y)| (x - y).powi(2))
+                    .sum::<f64>()
+                    .sqrt();
+                1.0 / (1.0 + distance)
+            }
+        }
+    }
+
+    /// Returns the score clamped between 0 and 1 for the cosine and the dot product.
+    pub fn normalized_score(&self, a: &[f64], b: &[f64]) -> f64 {
+        match self {
+            DistanceMetric::Cosine | DistanceMetric::DotProduct => self.score(a, b).clamp(0.0, 1.0),
+            DistanceMetric::Euclidean => self.score(a, b),
+        }
+    }
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn norm(a: &[f64]) -> f64 {
+    dot(a, a).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance_metrics() {
+        let (a, b) = ([1.0, 0.0], [1.0, 1.0]);
+        assert!((DistanceMetric::Cosine.score(&a, &b) - 0.5f64.sqrt()).abs() < 1e-9);
+        assert_eq!(DistanceMetric::DotProduct.score(&a, &b), 1.0);
+        assert_eq!(DistanceMetric::Euclidean.score(&a, &b), 0.5);
+        assert_eq!(DistanceMetric::Cosine.score(&a, &[0.0, 0.0]), 0.0);
+    }
+}
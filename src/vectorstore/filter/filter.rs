@@ -0,0 +1,219 @@
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    ops::{BitAnd, BitOr, Not},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A filter on the metadata of the documents, translated by each vector store to its native
+/// query, so that the same filter works with any backend.
+///
+/// A document without the key of a condition does not match it, except for the negations
+/// (`Ne`, `Not`), which it matches. Comparisons (`Gt`, `Gte`, `Lt`, `Lte`) match numbers
+/// against numbers and strings against strings.
+///
+/// # Usage
+/// ```rust,ignore
+/// let filter = MetadataFilter::eq("genre", "Sci-Fi")
+///     & MetadataFilter::between("year", 1990, 2000)
+///     & !MetadataFilter::is_in("director", ["Nolan", "Scott"]);
+/// let options = VecStoreOptions::default().with_metadata_filter(filter);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetadataFilter {
+    Eq(String, Value),
+    Ne(String, Value),
+    In(String, Vec<Value>),
+    Gt(String, Value),
+    Gte(String, Value),
+    Lt(String, Value),
+    Lte(String, Value),
+    Exists(String),
+    And(Vec<MetadataFilter>),
+    Or(Vec<MetadataFilter>),
+    Not(Box<MetadataFilter>),
+}
+
+impl MetadataFilter {
+    pub fn eq<K: Into<String>, V: Into<Value>>(key: K, value: V) -> Self {
+        Self::Eq(key.into(), value.into())
+    }
+
+    pub fn ne<K: Into<String>, V: Into<Value>>(key: K, value: V) -> Self {
+        Self::Ne(key.into(), value.into())
+    }
+
+    pub fn is_in<K, I, V>(key: K, values: I) -> Self
+    where
+        K: Into<String>,
+        I: IntoIterator<Item = V>,
+        V: Into<Value>,
+    {
+        Self::In(key.into(), values.into_iter().map(Into::into).collect())
+    }
+
+    pub fn gt<K: Into<String>, V: Into<Value>>(key: K, value: V) -> Self {
+        Self::Gt(key.into(), value.into())
+    }
+
+    pub fn gte<K: Into<String>, V: Into<Value>>(key: K, value: V) -> Self {
+        Self::Gte(key.into(), value.into())
+    }
+
+    pub fn lt<K: Into<String>, V: Into<Value>>(key: K, value: V) -> Self {
+        Self::Lt(key.into(), value.into())
+    }
+
+    pub fn lte<K: Into<String>, V: Into<Value>>(key: K, value: V) -> Self {
+        Self::Lte(key.into(), value.into())
+    }
+
+    /// Matches values between `min` and `max`, both included.
+    pub fn between<K: Into<String>, V: Into<Value>>(key: K, min: V, max: V) -> Self {
+        let key = key.into();
+        Self::And(vec![Self::gte(key.clone(), min), Self::lte(key, max)])
+    }
+
+    pub fn exists<K: Into<String>>(key: K) -> Self {
+        Self::Exists(key.into())
+    }
+
+    /// Matches the documents matching all the filters.
+    pub fn all<I: IntoIterator<Item = MetadataFilter>>(filters: I) -> Self {
+        Self::And(filters.into_iter().collect())
+    }
+
+    /// Matches the documents matching any of the filters.
+    pub fn any<I: IntoIterator<Item = MetadataFilter>>(filters: I) -> Self {
+        Self::Or(filters.into_iter().collect())
+    }
+
+    pub fn and(self, other: MetadataFilter) -> Self {
+        let mut filters = match self {
+            Self::And(filters) => filters,
+            filter => vec![filter],
+        };
+        match other {
+            Self::And(others) => filters.extend(others),
+            other => filters.push(other),
+        }
+        Self::And(filters)
+    }
+
+    pub fn or(self, other: MetadataFilter) -> Self {
+        let mut filters = match self {
+            Self::Or(filters) => filters,
+            filter => vec![filter],
+        };
+        match other {
+            Self::Or(others) => filters.extend(others),
+            other => filters.push(other),
+        }
+        Self::Or(filters)
+    }
+
+    /// Returns whether the metadata of a document matches the filter.
+    pub fn matches(&self, metadata: &HashMap<String, Value>) -> bool {
+        let compare = |key: &str, value: &Value, expected: &[Ordering]| {
+            metadata
+                .get(key)
+                .and_then(|v| compare_values(v, value))
+                .is_some_and(|ordering| expected.contains(&ordering))
+        };
+        match self {
+            Self::Eq(key, value) => compare(key, value, &[Ordering::Equal]),
+            Self::Ne(key, value) => !compare(key, value, &[Ordering::Equal]),
+            Self::In(key, values) => values
+                .iter()
+                .any(|value| compare(key, value, &[Ordering::Equal])),
+            Self::Gt(key, value) => compare(key, value, &[Ordering::Greater]),
+            Self::Gte(key, value) => compare(key, value, &[Ordering::Greater, Ordering::Equal]),
+            Self::Lt(key, value) => compare(key, value, &[Ordering::Less]),
+            Self::Lte(key, value) => compare(key, value, &[Ordering::Less, Ordering::Equal]),
+            Self::Exists(key) => metadata.contains_key(key),
+            Self::And(filters) => filters.iter().all(|f| f.matches(metadata)),
+            Self::Or(filters) => filters.iter().any(|f| f.matches(metadata)),
+            Self::Not(filter) => !filter.matches(metadata),
+        }
+    }
+}
+
+/// Compares numbers as numbers, strings as strings, and other values by equality only.
+fn compare_values(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        (a, b) if a == b => Some(Ordering::Equal),
+        _ => None,
+    }
+}
+
+impl BitAnd for MetadataFilter {
+    type Output = MetadataFilter;
+
+    fn bitand(self, other: MetadataFilter) -> MetadataFilter {
+        self.and(other)
+    }
+}
+
+impl BitOr for MetadataFilter {
+    type Output = MetadataFilter;
+
+    fn bitor(self, other: MetadataFilter) -> MetadataFilter {
+        self.or(other)
+    }
+}
+
+impl Not for MetadataFilter {
+    type Output = MetadataFilter;
+
+    fn not(self) -> MetadataFilter {
+        match self {
+            Self::Not(filter) => *filter,
+            filter => Self::Not(Box::new(filter)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_metadata_filter_matches() {
+        let metadata = HashMap::from([
+            ("genre".to_string(), json!("Sci-Fi")),
+            ("year".to_string(), json!(1994)),
+            ("rating".to_string(), json!(8.5)),
+        ]);
+
+        let filter = MetadataFilter::eq("genre", "Sci-Fi")
+            & MetadataFilter::between("year", 1990, 2000)
+            & MetadataFilter::gt("rating", 8);
+        assert!(filter.matches(&metadata));
+        assert_eq!(
+            filter,
+            MetadataFilter::And(vec![
+                MetadataFilter::eq("genre", "Sci-Fi"),
+                MetadataFilter::gte("year", 1990),
+                MetadataFilter::lte("year", 2000),
+                MetadataFilter::gt("rating", 8),
+            ])
+        );
+
+        assert!(MetadataFilter::eq("year", 1994.0).matches(&metadata));
+        assert!(!MetadataFilter::gt("genre", 1).matches(&metadata));
+        assert!(MetadataFilter::ne("director", "Nolan").matches(&metadata));
+        assert!(!MetadataFilter::exists("director").matches(&metadata));
+        assert!((!MetadataFilter::is_in("genre", ["Drama", "Comedy"])).matches(&metadata));
+        assert!(
+            (MetadataFilter::eq("genre", "Drama") | MetadataFilter::lt("year", 2000))
+                .matches(&metadata)
+        );
+    }
+}
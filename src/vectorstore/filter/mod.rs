@@ -0,0 +1,16 @@
+#[allow(clippy::module_inception)]
+mod filter;
+mod opensearch_query;
+mod sql;
+mod surrealql;
+
+pub use filter::*;
+
+#[cfg(feature = "opensearch")]
+pub(crate) use opensearch_query::*;
+
+#[cfg(any(feature = "postgres", feature = "sqlite-vec", feature = "sqlite-vss"))]
+pub(crate) use sql::*;
+
+#[cfg(feature = "surrealdb")]
+pub(crate) use surrealql::*;
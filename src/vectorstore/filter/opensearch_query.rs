@@ -0,0 +1,103 @@
+use serde_json::{json, Value};
+
+use super::MetadataFilter;
+
+/// Returns the OpenSearch query of `filter` on the `metadata` object of the documents, to be used
+/// in a `bool` filter or as the filter of a `knn` query.
+///
+/// Strings are compared exactly on the `keyword` subfield of the dynamic mapping, not on the
+/// analyzed text.
+pub(crate) fn metadata_filter_to_opensearch(filter: &MetadataFilter) -> Value {
+    let field = |key: &str| format!("metadata.{key}");
+    let equals = |key: &str, value: &Value| match value {
+        Value::String(_) => json!({ "term": { format!("{}.keyword", field(key)): value } }),
+        _ => json!({ "term": { field(key): value } }),
+    };
+    let range = |key: &str, operator: &str, value: &Value| json!({ "range": { field(key): { operator: value } } });
+    let must_not = |query: Value| json!({ "bool": { "must_not": [query] } });
+
+    match filter {
+        MetadataFilter::Eq(key, value) => equals(key, value),
+        MetadataFilter::Ne(key, value) => must_not(equals(key, value)),
+        MetadataFilter::In(key, values) => json!({
+            "bool": {
+                "should": values.iter().map(|value| equals(key, value)).collect::<Vec<_>>(),
+                "minimum_should_match": 1
+            }
+        }),
+        MetadataFilter::Gt(key, value) => range(key, "gt", value),
+        MetadataFilter::Gte(key, value) => range(key, "gte", value),
+        MetadataFilter::Lt(key, value) => range(key, "lt", value),
+        MetadataFilter::Lte(key, value) => range(key, "lte", value),
+        MetadataFilter::Exists(key) => json!({ "exists": { "field": field(key) } }),
+        MetadataFilter::And(filters) => json!({
+            "bool": {
+                "filter": filters.iter().map(metadata_filter_to_opensearch).collect::<Vec<_>>()
+            }
+        }),
+        MetadataFilter::Or(filters) => json!({
+            "bool": {
+                "should": filters.iter().map(metadata_filter_to_opensearch).collect::<Vec<_>>(),
+                "minimum_should_match": 1
+            }
+        }),
+        MetadataFilter::Not(filter) => must_not(metadata_filter_to_opensearch(filter)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_filter_to_opensearch() {
+        let filter = MetadataFilter::eq("genre", "Sci-Fi")
+            & MetadataFilter::between("year", 1990, 2000)
+            & (MetadataFilter::is_in("rating", [8, 9]) | !MetadataFilter::exists("director"));
+
+        assert_eq!(
+            metadata_filter_to_opensearch(&filter),
+            json!({
+                "bool": {
+                    "filter": [
+                        { "term": { "metadata.genre.keyword": "Sci-Fi" } },
+                        { "range": { "metadata.year": { "gte": 1990 } } },
+                        { "range": { "metadata.year": { "lte": 2000 } } },
+                        {
+                            "bool": {
+                                "should": [
+                                    {
+                                        "bool": {
+                                            "should": [
+                                                { "term": { "metadata.rating": 8 } },
+                                                { "term": { "metadata.rating": 9 } }
+                                            ],
+                                            "minimum_should_match": 1
+                                        }
+                                    },
+                                    {
+                                        "bool": {
+                                            "must_not": [
+                                                { "exists": { "field": "metadata.director" } }
+                                            ]
+                                        }
+                                    }
+                                ],
+                                "minimum_should_match": 1
+                            }
+                        }
+                    ]
+                }
+            })
+        );
+        assert_eq!(
+            metadata_filter_to_opensearch(&MetadataFilter::ne("draft", true)),
+            json!({ "bool": { "must_not": [{ "term": { "metadata.draft": true } }] } })
+        );
+        // No clause can match the `minimum_should_match` of an empty disjunction.
+        assert_eq!(
+            metadata_filter_to_opensearch(&MetadataFilter::Or(Vec::new())),
+            json!({ "bool": { "should": [], "minimum_should_match": 1 } })
+        );
+    }
+}
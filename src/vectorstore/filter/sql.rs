@@ -0,0 +1,206 @@
+use serde_json::Value;
+
+use super::MetadataFilter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SqlDialect {
+    /// The metadata is a `json` or `jsonb` column.
+    Postgres,
+    /// The metadata is JSON text, read with the JSON functions of sqlite.
+    Sqlite,
+}
+
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+fn join(conditions: Vec<String>, operator: &str, empty: &str) -> String {
+    if conditions.is_empty() {
+        empty.to_string()
+    } else {
+        format!("({})", conditions.join(operator))
+    }
+}
+
+/// Returns the SQL condition of `filter` on the JSON metadata `column`. Values are written as
+/// escaped literals.
+pub(crate) fn metadata_filter_to_sql(
+    filter: &MetadataFilter,
+    dialect: SqlDialect,
+    column: &str,
+) -> String {
+    let to_sql = |filter: &MetadataFilter| metadata_filter_to_sql(filter, dialect, column);
+    let compare = |key: &str, value: &Value, operator: &str| match dialect {
+        SqlDialect::Postgres => postgres_compare(column, key, value, operator),
+        SqlDialect::Sqlite => sqlite_compare(column, key, value, operator),
+    };
+    let negate = |condition: String| format!("NOT COALESCE({condition}, FALSE)");
+
+    match filter {
+        MetadataFilter::Eq(key, value) => compare(key, value, "="),
+        MetadataFilter::Ne(key, value) => negate(compare(key, value, "=")),
+        MetadataFilter::In(key, values) => join(
+            values
+                .iter()
+                .map(|value| compare(key, value, "="))
+                .collect(),
+            " OR ",
+            "FALSE",
+        ),
+        MetadataFilter::Gt(key, value) => compare(key, value, ">"),
+        MetadataFilter::Gte(key, value) => compare(key, value, ">="),
+        MetadataFilter::Lt(key, value) => compare(key, value, "<"),
+        MetadataFilter::Lte(key, value) => compare(key, value, "<="),
+        MetadataFilter::Exists(key) => match dialect {
+            SqlDialect::Postgres => format!("({column}::jsonb -> {}) IS NOT NULL", quote(key)),
+            SqlDialect::Sqlite => format!("json_type({column}, {}) IS NOT NULL", sqlite_path(key)),
+        },
+        MetadataFilter::And(filters) => join(filters.iter().map(to_sql).collect(), " AND ", "TRUE"),
+        MetadataFilter::Or(filters) => join(filters.iter().map(to_sql).collect(), " OR ", "FALSE"),
+        MetadataFilter::Not(filter) => negate(to_sql(filter)),
+    }
+}
+
+/// Compares jsonb values, which are ordered by type first, so comparisons check the type.
+fn postgres_compare(column: &str, key: &str, value: &Value, operator: &str) -> String {
+    let field = format!("({column}::jsonb -> {})", quote(key));
+    let literal = format!("{}::jsonb", quote(&value.to_string()));
+    if operator == "=" {
+        return format!("{field} = {literal}");
+    }
+    match value {
+        Value::Number(_) => {
+            format!("(jsonb_typeof({field}) = 'number' AND {field} {operator} {literal})")
+        }
+        Value::String(_) => {
+            format!("(jsonb_typeof({field}) = 'string' AND {field} {operator} {literal})")
+        }
+        _ => "FALSE".to_string(),
+    }
+}
+
+fn sqlite_path(key: &str) -> String {
+    quote(&format!("$.\"{key}\""))
+}
+
+fn sqlite_compare(column: &str, key: &str, value: &Value, operator: &str) -> String {
+    let path = sqlite_path(key);
+    let field = format!("json_extract({column}, {path})");
+    let field_type = format!("json_type({column}, {path})");
+    match value {
+        Value::Number(number) => {
+            format!("({field_type} IN ('integer', 'real') AND {field} {operator} {number})")
+        }
+        Value::String(text) => {
+            format!(
+                "({field_type} = 'text' AND {field} {operator} {})",
+                quote(text)
+            )
+        }
+        Value::Bool(_) | Value::Null if operator == "=" => {
+            format!("{field_type} = {}", quote(&value.to_string()))
+        }
+        Value::Array(_) | Value::Object(_) if operator == "=" => {
+            format!("{field} = json({})", quote(&value.to_string()))
+        }
+        _ => "FALSE".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter() -> MetadataFilter {
+        MetadataFilter::eq("genre", "Sci'Fi")
+            & MetadataFilter::gte("year", 1990)
+            & !MetadataFilter::is_in("rating", [8, 9])
+            & MetadataFilter::exists("director")
+    }
+
+    #[test]
+    fn test_metadata_filter_to_postgres() {
+        assert_eq!(
+            metadata_filter_to_sql(&filter(), SqlDialect::Postgres, "cmetadata"),
+            "((cmetadata::jsonb -> 'genre') = '\"Sci''Fi\"'::jsonb \
+             AND (jsonb_typeof((cmetadata::jsonb -> 'year')) = 'number' \
+             AND (cmetadata::jsonb -> 'year') >= '1990'::jsonb) \
+             AND NOT COALESCE(((cmetadata::jsonb -> 'rating') = '8'::jsonb \
+             OR (cmetadata::jsonb -> 'rating') = '9'::jsonb), FALSE) \
+             AND (cmetadata::jsonb -> 'director') IS NOT NULL)"
+        );
+    }
+
+    #[test]
+    fn test_metadata_filter_to_sqlite() {
+        assert_eq!(
+            metadata_filter_to_sql(&MetadataFilter::ne("genre", true), SqlDialect::Sqlite, "m"),
+            "NOT COALESCE(json_type(m, '$.\"genre\"') = 'true', FALSE)"
+        );
+        assert_eq!(
+            metadata_filter_to_sql(&filter(), SqlDialect::Sqlite, "m"),
+            "((json_type(m, '$.\"genre\"') = 'text' AND json_extract(m, '$.\"genre\"') = 'Sci''Fi') \
+             AND (json_type(m, '$.\"year\"') IN ('integer', 'real') AND json_extract(m, '$.\"year\"') >= 1990) \
+             AND NOT COALESCE(((json_type(m, '$.\"rating\"') IN ('integer', 'real') AND json_extract(m, '$.\"rating\"') = 8) \
+             OR (json_type(m, '$.\"rating\"') IN ('integer', 'real') AND json_extract(m, '$.\"rating\"') = 9)), FALSE) \
+             AND json_type(m, '$.\"director\"') IS NOT NULL)"
+        );
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_metadata_filter_on_sqlite() {
+        use serde_json::json;
+
+        let pool = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::query("CREATE TABLE docs (name TEXT, metadata TEXT)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let rows = [
+            (
+                "a",
+                json!({"genre": "Sci'Fi", "year": 1994, "rating": 7.5, "director": "x"}),
+            ),
+            ("b", json!({"genre": "Sci'Fi", "year": 1994, "rating": 8})),
+            (
+                "c",
+                json!({"genre": "Sci'Fi", "year": "1994", "director": "y"}),
+            ),
+            (
+                "d",
+                json!({"genre": "Drama", "year": 2001, "director": "z"}),
+            ),
+        ];
+        for (name, metadata) in rows {
+            sqlx::query("INSERT INTO docs VALUES (?, ?)")
+                .bind(name)
+                .bind(metadata.to_string())
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let names: Vec<(String,)> = sqlx::query_as(&format!(
+            "SELECT name FROM docs WHERE {} ORDER BY name",
+            metadata_filter_to_sql(&filter(), SqlDialect::Sqlite, "metadata")
+        ))
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(names, vec![("a".to_string(),)]);
+
+        let names: Vec<(String,)> = sqlx::query_as(&format!(
+            "SELECT name FROM docs WHERE {} ORDER BY name",
+            metadata_filter_to_sql(
+                &MetadataFilter::ne("rating", 8),
+                SqlDialect::Sqlite,
+                "metadata"
+            )
+        ))
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(names.len(), 3);
+    }
+}
@@ -0,0 +1,114 @@
+use serde_json::Value;
+
+use super::MetadataFilter;
+
+/// Returns the SurrealQL condition of `filter` on the `metadata` field of the records, with the
+/// keys and values to bind to its parameters.
+pub(crate) fn metadata_filter_to_surrealql(
+    filter: &MetadataFilter,
+) -> (String, Vec<(String, Value)>) {
+    let mut params = Vec::new();
+    let condition = to_surrealql(filter, &mut params);
+    (condition, params)
+}
+
+/// Binds the key and, when given, the value of a condition, returning the field and the value
+/// to use in the condition.
+fn bind(params: &mut Vec<(String, Value)>, key: &str, value: Option<&Value>) -> (String, String) {
+    let i = params.len();
+    params.push((format!("fk{i}"), Value::String(key.to_string())));
+    if let Some(value) = value {
+        params.push((format!("fv{i}"), value.clone()));
+    }
+    (format!("metadata[$fk{i}]"), format!("$fv{i}"))
+}
+
+fn compare(params: &mut Vec<(String, Value)>, key: &str, value: &Value, operator: &str) -> String {
+    let guard = match value {
+        Value::Number(_) => "type::is::number",
+        Value::String(_) => "type::is::string",
+        _ => return "false".to_string(),
+    };
+    let (field, value) = bind(params, key, Some(value));
+    format!("({guard}({field}) AND {field} {operator} {value})")
+}
+
+fn join(conditions: Vec<String>, operator: &str, empty: &str) -> String {
+    if conditions.is_empty() {
+        empty.to_string()
+    } else {
+        format!("({})", conditions.join(operator))
+    }
+}
+
+fn to_surrealql(filter: &MetadataFilter, params: &mut Vec<(String, Value)>) -> String {
+    match filter {
+        MetadataFilter::Eq(key, value) => {
+            let (field, value) = bind(params, key, Some(value));
+            format!("{field} = {value}")
+        }
+        MetadataFilter::Ne(key, value) => {
+            let (field, value) = bind(params, key, Some(value));
+            format!("{field} != {value}")
+        }
+        MetadataFilter::In(key, values) => {
+            let (field, values) = bind(params, key, Some(&Value::Array(values.clone())));
+            format!("{field} IN {values}")
+        }
+        MetadataFilter::Gt(key, value) => compare(params, key, value, ">"),
+        MetadataFilter::Gte(key, value) => compare(params, key, value, ">="),
+        MetadataFilter::Lt(key, value) => compare(params, key, value, "<"),
+        MetadataFilter::Lte(key, value) => compare(params, key, value, "<="),
+        MetadataFilter::Exists(key) => {
+            let (field, _) = bind(params, key, None);
+            format!("{field} != NONE")
+        }
+        MetadataFilter::And(filters) => join(
+            filters.iter().map(|f| to_surrealql(f, params)).collect(),
+            " AND ",
+            "true",
+        ),
+        MetadataFilter::Or(filters) => join(
+            filters.iter().map(|f| to_surrealql(f, params)).collect(),
+            " OR ",
+            "false",
+        ),
+        MetadataFilter::Not(filter) => format!("!({})", to_surrealql(filter, params)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_metadata_filter_to_surrealql() {
+        let filter = MetadataFilter::eq("genre", "Sci-Fi")
+            & MetadataFilter::gte("year", 1990)
+            & !MetadataFilter::is_in("rating", [8, 9]);
+
+        let (condition, params) = metadata_filter_to_surrealql(&filter);
+        assert_eq!(
+            condition,
+            "(metadata[$fk0] = $fv0 \
+             AND (type::is::number(metadata[$fk2]) AND metadata[$fk2] >= $fv2) \
+             AND !(metadata[$fk4] IN $fv4))"
+        );
+        assert_eq!(
+            params,
+            vec![
+                ("fk0".to_string(), json!("genre")),
+                ("fv0".to_string(), json!("Sci-Fi")),
+                ("fk2".to_string(), json!("year")),
+                ("fv2".to_string(), json!(1990)),
+                ("fk4".to_string(), json!("rating")),
+                ("fv4".to_string(), json!([8, 9])),
+            ]
+        );
+
+        let (condition, _) = metadata_filter_to_surrealql(&MetadataFilter::gt("draft", true));
+        assert_eq!(condition, "false");
+    }
+}
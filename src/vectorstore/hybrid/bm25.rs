@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+/// The parameters of the BM25 ranking function.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bm25Params {
+    /// How fast the score saturates with the frequency of a term, 1.2 by default.
+    pub k1: f64,
+    /// How much the length of a document lowers its score, between 0 and 1, 0.75 by default.
+    pub b: f64,
+}
+
+impl Default for Bm25Params {
+    fn default() -> Self {
+        Self { k1: 1.2, b: 0.75 }
+    }
+}
+
+/// Splits a text into lowercase terms of letters, digits and underscores, so that identifiers
+/// like `ERR_CONN_RESET` or `E1042` are kept as terms.
+pub(crate) fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|term| !term.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// An in-memory BM25 index of texts, identified by their position.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Bm25Index {
+    params: Bm25Params,
+    term_freqs: Vec<HashMap<String, usize>>,
+    lengths: Vec<usize>,
+    doc_freqs: HashMap<String, usize>,
+}
+
+impl Bm25Index {
+    pub(crate) fn new<'a, I: IntoIterator<Item = &'a str>>(texts: I, params: Bm25Params) -> Self {
+        let mut index = Self {
+            params,
+            ..Default::default()
+        };
+        for text in texts {
+            index.push(text);
+        }
+        index
+    }
+
+    pub(crate) fn push(&mut self, text: &str) {
+        let (term_freqs, length) = self.count_terms(text);
+        self.lengths.push(length);
+        self.term_freqs.push(term_freqs);
+    }
+
+    /// Replaces the text at `position`.
+    pub(crate) fn replace(&mut self, position: usize, text: &str) {
+        self.forget_terms(position);
+        let (term_freqs, length) = self.count_terms(text);
+        self.lengths[position] = length;
+        self.term_freqs[position] = term_freqs;
+    }
+
+    /// Removes the text at `position`, shifting the positions of the following texts.
+    pub(crate) fn remove(&mut self, position: usize) {
+        self.forget_terms(position);
+        self.lengths.remove(position);
+        self.term_freqs.remove(position);
+    }
+
+    /// Returns the frequencies of the terms of `text` and its length, counting its terms in the
+    /// document frequencies.
+    fn count_terms(&mut self, text: &str) -> (HashMap<String, usize>, usize) {
+        let terms = tokenize(text);
+        let mut term_freqs = HashMap::new();
+        for term in &terms {
+            *term_freqs.entry(term.clone()).or_insert(0) += 1;
+        }
+        for term in term_freqs.keys() {
+            *self.doc_freqs.entry(term.clone()).or_insert(0) += 1;
+        }
+        (term_freqs, terms.len())
+    }
+
+    /// Removes the terms of the text at `position` from the document frequencies.
+    fn forget_terms(&mut self, position: usize) {
+        for term in self.term_freqs[position].keys() {
+            if let Some(doc_freq) = self.doc_freqs.get_mut(term) {
+                *doc_freq -= 1;
+                if *doc_freq == 0 {
+                    self.doc_freqs.remove(term);
+                }
+            }
+        }
+    }
+
+    /// Returns the position and the score of the `limit` texts matching the query best, best
+    /// first. Texts without any term of the query are not returned.
+    pub(crate) fn search(&self, query: &str, limit: usize) -> Vec<(usize, f64)> {
+        let mut terms = tokenize(query);
+        terms.sort();
+        terms.dedup();
+
+        let count = self.lengths.len() as f64;
+        let average_length = self.lengths.iter().sum::<usize>() as f64 / count.max(1.0);
+        let Bm25Params { k1, b } = self.params;
+
+        let mut scores: Vec<(usize, f64)> = self
+            .term_freqs
+            .iter()
+            .zip(&self.lengths)
+            .enumerate()
+            .filter_map(|(i, (term_freqs, &length))| {
+                let norm = k1 * (1.0 - b + b * length as f64 / average_length.max(1.0));
+                let score: f64 = terms
+                    .iter()
+                    .filter_map(|term| {
+                        let tf = *term_freqs.get(term)? as f64;
+                        let df = self.doc_freqs[term] as f64;
+                        let idf = ((count - df + 0.5) / (df + 0.5) + 1.0).ln();
+                        Some(idf * tf * (k1 + 1.0) / (tf + norm))
+                    })
+                    .sum();
+                (score > 0.0).then_some((i, score))
+            })
+            .collect();
+        scores.sort_by(|(i, a), (j, b)| b.total_cmp(a).then(i.cmp(j)));
+        scores.truncate(limit);
+        scores
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bm25_index_updates() {
+        let mut index = Bm25Index::new(
+            ["The cat sleeps", "The dog barks", "The fish swims"],
+            Bm25Params::default(),
+        );
+        assert_eq!(index.search("dog", 3)[0].0, 1);
+
+        index.replace(1, "The bird sings");
+        assert!(index.search("dog", 3).is_empty());
+        assert_eq!(index.search("bird", 3)[0].0, 1);
+
+        index.remove(0);
+        assert_eq!(index.search("fish", 3)[0].0, 1);
+        assert!(index.search("cat", 3).is_empty());
+        assert_eq!(
+            index.search("the fish", 3),
+            Bm25Index::new(["The bird sings", "The fish swims"], Bm25Params::default())
+                .search("the fish", 3)
+        );
+    }
+}
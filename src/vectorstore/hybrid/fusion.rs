@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+
+use crate::schemas::Document;
+
+/// How the result lists of several searches are merged into one ranking.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fusion {
+    /// Reciprocal rank fusion: a document scores the sum of `weight / (k + rank)` over the lists
+    /// it is in, its rank starting at 1. Only the ranks matter, so lists scored on different
+    /// scales, like BM25 and cosine similarity, can be merged. `k` is usually 60.
+    ReciprocalRank { k: f64 },
+    /// A document scores the sum of `weight * score` over the lists it is in, the scores of each
+    /// list being min-max normalized between 0 and 1.
+    Weighted,
+}
+
+impl Default for Fusion {
+    fn default() -> Self {
+        Self::ReciprocalRank { k: 60.0 }
+    }
+}
+
+/// Identifies the same document in different lists, by id or else by content.
+pub(crate) fn document_key(doc: &Document) -> &str {
+    doc.id.as_deref().unwrap_or(&doc.page_content)
+}
+
+impl Fusion {
+    /// Merges the result lists, each ranked best first, and returns the `limit` best documents
+    /// with their fused score. `weights` are the weights of the lists, 1 if missing.
+    pub fn fuse(
+        &self,
+        results: Vec<Vec<Document>>,
+        weights: &[f64],
+        limit: usize,
+    ) -> Vec<Document> {
+        let mut fused: Vec<Document> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+
+        for (i, docs) in results.into_iter().enumerate() {
+            let weight = weights.get(i).copied().unwrap_or(1.0);
+            let (min, max) = docs.iter().fold((f64::MAX, f64::MIN), |(min, max), doc| {
+                (min.min(doc.score), max.max(doc.score))
+            });
+
+            for (rank, doc) in docs.into_iter().enumerate() {
+                let score = match self {
+                    Self::ReciprocalRank { k } => weight / (k + rank as f64 + 1.0),
+                    Self::Weighted if max > min => weight * (doc.score - min) / (max - min),
+                    Self::Weighted => weight,
+                };
+                match positions.get(document_key(&doc)) {
+                    Some(&position) => fused[position].score += score,
+                    None => {
+                        positions.insert(document_key(&doc).to_string(), fused.len());
+                        fused.push(doc.with_score(score));
+                    }
+                }
+            }
+        }
+
+        fused.sort_by(|a, b| b.score.total_cmp(&a.score));
+        fused.truncate(limit);
+        fused
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn docs(texts: &[(&str, f64)]) -> Vec<Document> {
+        texts
+            .iter()
+            .map(|(text, score)| Document::new(*text).with_score(*score))
+            .collect()
+    }
+
+    #[test]
+    fn test_fusion() {
+        let keyword = docs(&[("a", 12.0), ("b", 3.0)]);
+        let vector = docs(&[("c", 0.9), ("b", 0.8), ("a", 0.1)]);
+
+        let fused = Fusion::default().fuse(vec![keyword.clone(), vector.clone()], &[], 2);
+        let texts: Vec<&str> = fused.iter().map(|d| d.page_content.as_str()).collect();
+        assert_eq!(texts, ["a", "b"]);
+        assert!((fused[0].score - (1.0 / 61.0 + 1.0 / 63.0)).abs() < 1e-12);
+
+        let fused = Fusion::Weighted.fuse(vec![keyword, vector], &[0.2, 0.8], 3);
+        let texts: Vec<&str> = fused.iter().map(|d| d.page_content.as_str()).collect();
+        assert_eq!(texts, ["c", "b", "a"]);
+        assert!((fused[0].score - 0.8).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,5 @@
+mod bm25;
+pub use bm25::*;
+
+mod fusion;
+pub use fusion::*;
@@ -0,0 +1,45 @@
+use std::{error::Error, sync::Arc};
+
+use super::InMemoryStore;
+use crate::{
+    embedding::embedder_trait::Embedder, schemas::BuilderError, vectorstore::DistanceMetric,
+};
+
+pub struct StoreBuilder {
+    embedder: Option<Arc<dyn Embedder>>,
+    metric: DistanceMetric,
+}
+
+impl StoreBuilder {
+    pub fn new() -> Self {
+        StoreBuilder {
+            embedder: None,
+            metric: DistanceMetric::Cosine,
+        }
+    }
+
+    pub fn embedder<E: Embedder + 'static>(mut self, embedder: E) -> Self {
+        self.embedder = Some(Arc::new(embedder));
+        self
+    }
+
+    /// Sets the metric comparing the vectors, [`DistanceMetric::Cosine`] by default.
+    pub fn metric(mut self, metric: DistanceMetric) -> Self {
+        self.metric = metric;
+        self
+    }
+
+    pub fn build(self) -> Result<InMemoryStore, Box<dyn Error>> {
+        let embedder = self
+            .embedder
+            .ok_or(BuilderError::MissingField("embedder"))?;
+
+        Ok(InMemoryStore::new(embedder, self.metric))
+    }
+}
+
+impl Default for StoreBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
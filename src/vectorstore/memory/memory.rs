@@ -0,0 +1,681 @@
+use std::{collections::HashMap, error::Error, path::Path, sync::Arc};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::{
+    embedding::embedder_trait::Embedder,
+    schemas::Document,
+    telemetry,
+    vectorstore::{Bm25Index, Bm25Params, DistanceMetric, VecStoreOptions, VectorStore},
+};
+
+pub type InMemoryOptions = VecStoreOptions<Value>;
+
+/// The name space of the documents added without one.
+pub const DEFAULT_NAME_SPACE: &str = "default";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct Entry {
+    pub(crate) id: String,
+    pub(crate) page_content: String,
+    pub(crate) metadata: HashMap<String, Value>,
+    pub(crate) vector: Vec<f64>,
+}
+
+impl Entry {
+    fn to_document(&self, score: f64) -> Document {
+        Document {
+            page_content: self.page_content.clone(),
+            metadata: self.metadata.clone(),
+            score,
+            id: Some(self.id.clone()),
+        }
+    }
+}
+
+/// The documents of a name space, with the BM25 index of their content for the keyword search,
+/// kept in the same order.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct NameSpace {
+    pub(crate) entries: Vec<Entry>,
+    index: Bm25Index,
+}
+
+impl NameSpace {
+    fn new(entries: Vec<Entry>) -> Self {
+        let index = Bm25Index::new(
+            entries.iter().map(|entry| entry.page_content.as_str()),
+            Bm25Params::default(),
+        );
+        Self { entries, index }
+    }
+
+    /// Adds the entry, replacing the entry with the same id, if any.
+    fn upsert(&mut self, entry: Entry) {
+        match self.entries.iter().position(|e| e.id == entry.id) {
+            Some(position) => {
+                self.index.replace(position, &entry.page_content);
+                self.entries[position] = entry;
+            }
+            None => {
+                self.index.push(&entry.page_content);
+                self.entries.push(entry);
+            }
+        }
+    }
+
+    fn delete(&mut self, ids: &[String]) {
+        for position in (0..self.entries.len()).rev() {
+            if ids.contains(&self.entries[position].id) {
+                self.entries.remove(position);
+                self.index.remove(position);
+            }
+        }
+    }
+}
+
+/// The content of a saved store.
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    metric: DistanceMetric,
+    name_spaces: HashMap<String, Vec<Entry>>,
+}
+
+/// A vector store keeping the documents and their embeddings in memory, searched exactly.
+///
+/// Documents are grouped by the `name_space` of the options. The `filters` are a JSON object
+/// of metadata values the documents must have, and the `metadata_filter` is evaluated on the
+/// metadata of each document. The store can be saved to a JSON file and
+/// loaded back, so that tests and prototypes do not need a database.
+pub struct InMemoryStore {
+    pub(crate) embedder: Arc<dyn Embedder>,
+    pub(crate) metric: DistanceMetric,
+    pub(crate) name_spaces: RwLock<HashMap<String, NameSpace>>,
+}
+
+impl InMemoryStore {
+    pub fn new(embedder: Arc<dyn Embedder>, metric: DistanceMetric) -> Self {
+        Self {
+            embedder,
+            metric,
+            name_spaces: RwLock::default(),
+        }
+    }
+
+    /// Returns the number of documents of `name_space`, the default one if `None`.
+    pub async fn len(&self, name_space: Option<&str>) -> usize {
+        self.name_spaces
+            .read()
+            .await
+            .get(name_space.unwrap_or(DEFAULT_NAME_SPACE))
+            .map_or(0, |name_space| name_space.entries.len())
+    }
+
+    pub async fn is_empty(&self, name_space: Option<&str>) -> bool {
+        self.len(name_space).await == 0
+    }
+
+    /// Saves the documents and their embeddings to the JSON file at `path`.
+    pub async fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let snapshot = Snapshot {
+            metric: self.metric,
+            name_spaces: self
+                .name_spaces
+                .read()
+                .await
+                .iter()
+                .map(|(name, name_space)| (name.clone(), name_space.entries.clone()))
+                .collect(),
+        };
+        let path = path.as_ref();
+        let tmp_path = path.with_extension("tmp");
+        tokio::fs::write(&tmp_path, serde_json::to_vec(&snapshot)?).await?;
+        tokio::fs::rename(&tmp_path, path).await?;
+        Ok(())
+    }
+
+    /// Replaces the documents with the ones saved at `path` by [`InMemoryStore::save`].
+    pub async fn load<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let snapshot: Snapshot = serde_json::from_slice(&tokio::fs::read(path).await?)?;
+        if snapshot.metric != self.metric {
+            return Err(format!(
+                "The store was saved with the {:?} metric, not {:?}",
+                snapshot.metric, self.metric
+            )
+            .into());
+        }
+        *self.name_spaces.write().await = snapshot
+            .name_spaces
+            .into_iter()
+            .map(|(name, entries)| (name, NameSpace::new(entries)))
+            .collect();
+        Ok(())
+    }
+
+    /// Returns the `limit` documents most similar to `query_vector` with their vector.
+    async fn search(
+        &self,
+        query_vector: &[f64],
+        limit: usize,
+        opt: &InMemoryOptions,
+    ) -> Result<Vec<(Document, Vec<f64>)>, Box<dyn Error>> {
+        let filters = self.get_filters(opt)?;
+
+        let name_spaces = self.name_spaces.read().await;
+        let Some(name_space) = name_spaces.get(&self.get_name_space(opt)) else {
+            return Ok(Vec::new());
+        };
+
+        let mut docs = Vec::new();
+        for entry in &name_space.entries {
+            if entry.vector.len() != query_vector.len() {
+                return Err(format!(
+                    "Expected a query vector of {} dimensions, got {}",
+                    entry.vector.len(),
+                    query_vector.len()
+                )
+                .into());
+            }
+            if !Self::matches(entry, filters, opt) {
+                continue;
+            }
+
+            let score = self.metric.normalized_score(query_vector, &entry.vector);
+            if opt
+                .score_threshold
+                .is_some_and(|threshold| score < threshold as f64)
+            {
+                continue;
+            }
+            // The normalized score is clamped, the documents are ranked on the raw one.
+            let rank = self.metric.score(query_vector, &entry.vector);
+            docs.push((rank, entry.to_document(score), entry.vector.clone()));
+        }
+
+        docs.sort_by(|(a, _, _), (b, _, _)| b.total_cmp(a));
+        Ok(docs
+            .into_iter()
+            .take(limit)
+            .map(|(_, doc, vector)| (doc, vector))
+            .collect())
+    }
+
+    /// Returns whether the entry matches the `filters` and the `metadata_filter` of the options.
+    fn matches(
+        entry: &Entry,
+        filters: Option<&serde_json::Map<String, Value>>,
+        opt: &InMemoryOptions,
+    ) -> bool {
+        filters.is_none_or(|filters| {
+            filters
+                .iter()
+                .all(|(key, value)| entry.metadata.get(key) == Some(value))
+        }) && opt
+            .metadata_filter
+            .as_ref()
+            .is_none_or(|filter| filter.matches(&entry.metadata))
+    }
+
+    fn get_name_space(&self, opt: &InMemoryOptions) -> String {
+        opt.name_space
+            .clone()
+            .unwrap_or_else(|| DEFAULT_NAME_SPACE.to_string())
+    }
+
+    fn get_filters<'a>(
+        &self,
+        opt: &'a InMemoryOptions,
+    ) -> Result<Option<&'a serde_json::Map<String, Value>>, Box<dyn Error>> {
+        match &opt.filters {
+            Some(Value::Object(map)) => Ok(Some(map)),
+            None => Ok(None),
+            _ => Err("Invalid filters format".into()),
+        }
+    }
+}
+
+#[async_trait]
+impl VectorStore for InMemoryStore {
+    type Options = InMemoryOptions;
+
+    fn embedder(&self) -> Option<&dyn Embedder> {
+        Some(self.embedder.as_ref())
+    }
+
+    async fn add_documents(
+        &self,
+        docs: &[Document],
+        opt: &Self::Options,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let texts: Vec<String> = docs.iter().map(|d| d.page_content.clone()).collect();
+
+        let embedder = opt.embedder.as_ref().unwrap_or(&self.embedder);
+
+        let vectors = embedder.embed_documents(&texts).await?;
+        if vectors.len() != docs.len() {
+            let err = std::io::Error::other("Number of vectors and documents do not match");
+            return Err(err.into());
+        }
+
+        let mut name_spaces = self.name_spaces.write().await;
+        let name_space = name_spaces.entry(self.get_name_space(opt)).or_default();
+        let dimensions = name_space.entries.first().map(|e| e.vector.len());
+        if let Some(dimensions) = dimensions {
+            if let Some(vector) = vectors.iter().find(|v| v.len() != dimensions) {
+                return Err(format!(
+                    "Expected vectors of {dimensions} dimensions, got {}",
+                    vector.len()
+                )
+                .into());
+            }
+        }
+
+        let mut ids = Vec::with_capacity(docs.len());
+        for (doc, vector) in docs.iter().zip(vectors) {
+            let id = doc.id.clone().unwrap_or_else(|| Uuid::new_v4().to_string());
+            name_space.upsert(Entry {
+                id: id.clone(),
+                page_content: doc.page_content.clone(),
+                metadata: doc.metadata.clone(),
+                vector,
+            });
+            ids.push(id);
+        }
+
+        Ok(ids)
+    }
+
+    async fn similarity_search(
+        &self,
+        query: &str,
+        limit: usize,
+        opt: &Self::Options,
+    ) -> Result<Vec<Document>, Box<dyn Error>> {
+        let span = telemetry::similarity_search_span("in_memory", limit);
+        telemetry::in_span(span, async move {
+            let embedder = opt.embedder.as_ref().unwrap_or(&self.embedder);
+            let query_vector = embedder.embed_query(query).await?;
+            self.similarity_search_by_vector(&query_vector, limit, opt)
+                .await
+        })
+        .await
+    }
+
+    /// The score of the documents is the [`DistanceMetric::normalized_score`] of the store.
+    async fn similarity_search_by_vector(
+        &self,
+        vector: &[f64],
+        limit: usize,
+        opt: &Self::Options,
+    ) -> Result<Vec<Document>, Box<dyn Error>> {
+        let docs = self.search(vector, limit, opt).await?;
+        Ok(docs.into_iter().map(|(doc, _)| doc).collect())
+    }
+
+    async fn similarity_search_with_vectors(
+        &self,
+        vector: &[f64],
+        limit: usize,
+        opt: &Self::Options,
+    ) -> Result<Vec<(Document, Vec<f64>)>, Box<dyn Error>> {
+        self.search(vector, limit, opt).await
+    }
+
+    /// The score of the documents is their BM25 score among all the documents of the name space,
+    /// whose index is kept up to date as documents are added and deleted.
+    async fn keyword_search(
+        &self,
+        query: &str,
+        limit: usize,
+        opt: &Self::Options,
+    ) -> Result<Vec<Document>, Box<dyn Error>> {
+        let filters = self.get_filters(opt)?;
+
+        let name_spaces = self.name_spaces.read().await;
+        let Some(name_space) = name_spaces.get(&self.get_name_space(opt)) else {
+            return Ok(Vec::new());
+        };
+
+        Ok(name_space
+            .index
+            .search(query, name_space.entries.len())
+            .into_iter()
+            .map(|(i, score)| (&name_space.entries[i], score))
+            .filter(|(entry, _)| Self::matches(entry, filters, opt))
+            .take(limit)
+            .map(|(entry, score)| entry.to_document(score))
+            .collect())
+    }
+
+    async fn delete(&self, ids: &[String], opt: &Self::Options) -> Result<(), Box<dyn Error>> {
+        let mut name_spaces = self.name_spaces.write().await;
+        if let Some(name_space) = name_spaces.get_mut(&self.get_name_space(opt)) {
+            name_space.delete(ids);
+        }
+        Ok(())
+    }
+
+    async fn get_by_ids(
+        &self,
+        ids: &[String],
+        opt: &Self::Options,
+    ) -> Result<Vec<Document>, Box<dyn Error>> {
+        let name_spaces = self.name_spaces.read().await;
+        let Some(name_space) = name_spaces.get(&self.get_name_space(opt)) else {
+            return Ok(Vec::new());
+        };
+        Ok(ids
+            .iter()
+            .filter_map(|id| name_space.entries.iter().find(|entry| &entry.id == id))
+            .map(|entry| entry.to_document(0.0))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use serde_json::json;
+
+    use crate::{
+        embedding::EmbedderError,
+        schemas::Retriever as _,
+        vectorstore::{memory::StoreBuilder, MetadataFilter, Retriever},
+    };
+
+    use super::*;
+
+    /// Embeds a text as the number of occurrences of each word of a small vocabulary.
+    pub(crate) struct WordCountEmbedder;
+
+    impl WordCountEmbedder {
+        pub(crate) const VOCABULARY: [&'static str; 4] = ["cat", "dog", "fish", "bird"];
+
+        fn embed(text: &str) -> Vec<f64> {
+            let text = text.to_lowercase();
+            Self::VOCABULARY
+                .iter()
+                .map(|word| text.matches(word).count() as f64)
+                .collect()
+        }
+    }
+
+    #[async_trait]
+    impl Embedder for WordCountEmbedder {
+        async fn embed_documents(
+            &self,
+            documents: &[String],
+        ) -> Result<Vec<Vec<f64>>, EmbedderError> {
+            Ok(documents.iter().map(|d| Self::embed(d)).collect())
+        }
+
+        async fn embed_query(&self, text: &str) -> Result<Vec<f64>, EmbedderError> {
+            Ok(Self::embed(text))
+        }
+    }
+
+    pub(crate) fn pet_documents() -> Vec<Document> {
+        vec![
+            Document::new("The cat sleeps")
+                .with_metadata(HashMap::from([("kind".to_string(), json!("mammal"))])),
+            Document::new("The dog barks at the cat")
+                .with_metadata(HashMap::from([("kind".to_string(), json!("mammal"))])),
+            Document::new("The fish swims")
+                .with_metadata(HashMap::from([("kind".to_string(), json!("fish"))])),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_store() {
+        let store = StoreBuilder::new()
+            .embedder(WordCountEmbedder)
+            .build()
+            .unwrap();
+        let ids = store
+            .add_documents(&pet_documents(), &InMemoryOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(ids.len(), 3);
+
+        let docs = store
+            .similarity_search("cat", 2, &InMemoryOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(docs[0].page_content, "The cat sleeps");
+        assert_eq!(docs[1].page_content, "The dog barks at the cat");
+        assert!(docs[0].score > docs[1].score);
+
+        let options = InMemoryOptions::default().with_filters(json!({ "kind": "fish" }));
+        let docs = store.similarity_search("cat", 2, &options).await.unwrap();
+        assert_eq!(docs.len(), 1);
+        assert_eq!(docs[0].page_content, "The fish swims");
+
+        let options = InMemoryOptions::default().with_score_threshold(0.9);
+        let docs = store.similarity_search("cat", 3, &options).await.unwrap();
+        assert_eq!(docs.len(), 1);
+
+        let options = InMemoryOptions::default()
+            .with_filters(json!({ "kind": "mammal" }))
+            .with_metadata_filter(!MetadataFilter::eq("kind", "mammal"));
+        let docs = store.similarity_search("cat", 3, &options).await.unwrap();
+        assert!(docs.is_empty());
+
+        let options = InMemoryOptions::default()
+            .with_metadata_filter(MetadataFilter::is_in("kind", ["fish", "bird"]));
+        let docs = store.similarity_search("cat", 3, &options).await.unwrap();
+        assert_eq!(docs.len(), 1);
+        assert_eq!(docs[0].page_content, "The fish swims");
+
+        let options = InMemoryOptions::default().with_name_space("birds");
+        assert!(store
+            .similarity_search("cat", 3, &options)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_store_upsert_and_delete() {
+        let options = InMemoryOptions::default();
+        let store = StoreBuilder::new()
+            .embedder(WordCountEmbedder)
+            .build()
+            .unwrap();
+        let ids = store
+            .add_documents(&pet_documents(), &options)
+            .await
+            .unwrap();
+
+        let updated = Document::new("The bird sings").with_id(ids[0].clone());
+        let upserted = store
+            .add_documents(
+                &[updated, Document::new("A fish").with_id("fish")],
+                &options,
+            )
+            .await
+            .unwrap();
+        assert_eq!(upserted, vec![ids[0].clone(), "fish".to_string()]);
+        assert_eq!(store.len(None).await, 4);
+
+        let docs = store
+            .get_by_ids(&["fish".into(), "unknown".into(), ids[0].clone()], &options)
+            .await
+            .unwrap();
+        assert_eq!(docs.len(), 2);
+        assert_eq!(docs[0].page_content, "A fish");
+        assert_eq!(docs[1].page_content, "The bird sings");
+        assert_eq!(docs[1].id.as_ref(), Some(&ids[0]));
+
+        let docs = store.similarity_search("bird", 1, &options).await.unwrap();
+        assert_eq!(docs[0].id.as_ref(), Some(&ids[0]));
+
+        store
+            .delete(&[ids[0].clone(), "fish".into()], &options)
+            .await
+            .unwrap();
+        assert_eq!(store.len(None).await, 2);
+        assert!(store
+            .get_by_ids(&[ids[0].clone()], &options)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_store_save_and_load() {
+        let options = InMemoryOptions::default().with_name_space("pets");
+        let store = StoreBuilder::new()
+            .embedder(WordCountEmbedder)
+            .build()
+            .unwrap();
+        store
+            .add_documents(&pet_documents(), &options)
+            .await
+            .unwrap();
+
+        let path = std::env::temp_dir().join(format!("in_memory_store_{}.json", Uuid::new_v4()));
+        store.save(&path).await.unwrap();
+
+        let loaded = StoreBuilder::new()
+            .embedder(WordCountEmbedder)
+            .build()
+            .unwrap();
+        loaded.load(&path).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.len(Some("pets")).await, 3);
+        let docs = loaded.similarity_search("fish", 1, &options).await.unwrap();
+        assert_eq!(docs[0].page_content, "The fish swims");
+
+        let euclidean = StoreBuilder::new()
+            .embedder(WordCountEmbedder)
+            .metric(DistanceMetric::Euclidean)
+            .build()
+            .unwrap();
+        let path = std::env::temp_dir().join(format!("in_memory_store_{}.json", Uuid::new_v4()));
+        store.save(&path).await.unwrap();
+        assert!(euclidean.load(&path).await.is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_store_search_by_vector() {
+        let store = StoreBuilder::new()
+            .embedder(WordCountEmbedder)
+            .metric(DistanceMetric::DotProduct)
+            .build()
+            .unwrap();
+        store
+            .add_documents(&pet_documents(), &InMemoryOptions::default())
+            .await
+            .unwrap();
+
+        let docs = store
+            .similarity_search_by_vector(&[2.0, 1.0, 0.0, 0.0], 3, &InMemoryOptions::default())
+            .await
+            .unwrap();
+        let contents: Vec<&str> = docs.iter().map(|d| d.page_content.as_str()).collect();
+        assert_eq!(
+            contents,
+            [
+                "The dog barks at the cat",
+                "The cat sleeps",
+                "The fish swims"
+            ]
+        );
+        assert!(docs.iter().all(|doc| (0.0..=1.0).contains(&doc.score)));
+
+        let docs = store
+            .similarity_search_with_score("fish", 3, &InMemoryOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(docs[0].0.page_content, "The fish swims");
+        assert!(docs.iter().all(|(_, score)| (0.0..=1.0).contains(score)));
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_store_mmr() {
+        let store = StoreBuilder::new()
+            .embedder(WordCountEmbedder)
+            .build()
+            .unwrap();
+        store
+            .add_documents(
+                &[
+                    Document::new("The cat sleeps"),
+                    Document::new("The cat sleeps, the cat dreams"),
+                    Document::new("The cat and the dog"),
+                ],
+                &InMemoryOptions::default(),
+            )
+            .await
+            .unwrap();
+
+        let retriever = Retriever::new(store, 2).with_mmr(3, 0.3);
+        let docs = retriever.get_relevant_documents("cat").await.unwrap();
+        assert_eq!(docs.len(), 2);
+        assert_eq!(docs[0].page_content, "The cat sleeps");
+        assert_eq!(docs[1].page_content, "The cat and the dog");
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_store_keyword_search() {
+        let store = StoreBuilder::new()
+            .embedder(WordCountEmbedder)
+            .build()
+            .unwrap();
+        let mut docs = pet_documents();
+        docs.push(
+            Document::new("Error E1042 in the fish tank")
+                .with_metadata(HashMap::from([("kind".to_string(), json!("fish"))])),
+        );
+        store
+            .add_documents(&docs, &InMemoryOptions::default())
+            .await
+            .unwrap();
+
+        let options =
+            InMemoryOptions::default().with_metadata_filter(MetadataFilter::eq("kind", "mammal"));
+        let found = store.keyword_search("cat", 5, &options).await.unwrap();
+        assert_eq!(found.len(), 2);
+        assert!(found[0].score > 0.0);
+
+        let sleeping = found
+            .iter()
+            .find(|doc| doc.page_content == "The cat sleeps")
+            .unwrap();
+        let bird = Document::new("The bird sleeps").with_id(sleeping.id.clone().unwrap());
+        store
+            .add_documents(&[bird], &InMemoryOptions::default())
+            .await
+            .unwrap();
+        let default = InMemoryOptions::default();
+        assert_eq!(
+            store
+                .keyword_search("cat", 5, &default)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        let found = store.keyword_search("bird", 5, &default).await.unwrap();
+        assert_eq!(found[0].page_content, "The bird sleeps");
+
+        store
+            .delete(&[found[0].id.clone().unwrap()], &default)
+            .await
+            .unwrap();
+        assert!(store
+            .keyword_search("bird", 5, &default)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let retriever = Retriever::new(store, 1).with_hybrid(3, 0.7);
+        let found = retriever.get_relevant_documents("e1042").await.unwrap();
+        assert_eq!(found[0].page_content, "Error E1042 in the fish tank");
+    }
+}
@@ -0,0 +1,6 @@
+mod builder;
+#[allow(clippy::module_inception)]
+mod memory;
+
+pub use builder::*;
+pub use memory::*;
@@ -0,0 +1,66 @@
+use super::DistanceMetric;
+
+/// Returns the indices of the `k` vectors selected by maximal marginal relevance, in the order
+/// they were selected.
+///
+/// Each step selects the vector maximizing
+/// `lambda * sim(query, v) - (1 - lambda) * max(sim(v, selected))`, with the cosine similarity.
+/// A `lambda` of 1 ranks by relevance only, a `lambda` of 0 by diversity only.
+pub fn maximal_marginal_relevance(
+    query_vector: &[f64],
+    vectors: &[Vec<f64>],
+    k: usize,
+    lambda: f64,
+) -> Vec<usize> {
+    let similarity = |a: &[f64], b: &[f64]| DistanceMetric::Cosine.score(a, b);
+    let relevance: Vec<f64> = vectors
+        .iter()
+        .map(|vector| similarity(query_vector, vector))
+        .collect();
+
+    let mut selected: Vec<usize> = Vec::with_capacity(k.min(vectors.len()));
+    while selected.len() < k.min(vectors.len()) {
+        let best = (0..vectors.len())
+            .filter(|i| !selected.contains(i))
+            .map(|i| {
+                let redundancy = selected
+                    .iter()
+                    .map(|&j| similarity(&vectors[i], &vectors[j]))
+                    .fold(None, |max: Option<f64>, s| {
+                        Some(max.map_or(s, |m| m.max(s)))
+                    })
+                    .unwrap_or(0.0);
+                (i, lambda * relevance[i] - (1.0 - lambda) * redundancy)
+            })
+            .max_by(|(i, a), (j, b)| a.total_cmp(b).then(j.cmp(i)));
+        match best {
+            Some((i, _)) => selected.push(i),
+            None => break,
+        }
+    }
+    selected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_maximal_marginal_relevance() {
+        let query = vec![1.0, 0.0];
+        let vectors = vec![
+            vec![1.0, 0.1],
+            vec![1.0, 0.11],
+            vec![0.5, 0.5],
+            vec![0.0, 1.0],
+        ];
+
+        assert_eq!(maximal_marginal_relevance(&query, &vectors, 2, 1.0), [0, 1]);
+        assert_eq!(maximal_marginal_relevance(&query, &vectors, 2, 0.3), [0, 3]);
+        assert_eq!(
+            maximal_marginal_relevance(&query, &vectors, 10, 0.5).len(),
+            4
+        );
+        assert!(maximal_marginal_relevance(&query, &[], 2, 0.5).is_empty());
+    }
+}
@@ -1,8 +1,14 @@
 #![allow(dead_code)]
 // I have no idea how to remove dead codes here.
 
+mod distance;
+mod filter;
+mod hybrid;
+mod mmr;
 mod options;
 
+pub mod memory;
+
 #[cfg(feature = "postgres")]
 pub mod pgvector;
 
@@ -12,6 +18,9 @@ pub mod sqlite_vss;
 #[cfg(feature = "sqlite-vec")]
 pub mod sqlite_vec;
 
+#[cfg(any(feature = "sqlite", feature = "sqlite-vec", feature = "sqlite-vss"))]
+pub(crate) mod sqlite_rows;
+
 #[cfg(feature = "surrealdb")]
 pub mod surrealdb;
 
@@ -24,5 +33,9 @@ pub mod qdrant;
 #[allow(clippy::module_inception)]
 mod vectorstore;
 
+pub use distance::*;
+pub use filter::*;
+pub use hybrid::*;
+pub use mmr::*;
 pub use options::*;
 pub use vectorstore::*;
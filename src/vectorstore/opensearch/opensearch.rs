@@ -2,7 +2,7 @@ use async_trait::async_trait;
 use opensearch::http::request::JsonBody;
 use opensearch::http::response::Response;
 use opensearch::indices::{IndicesCreateParts, IndicesDeleteParts};
-use opensearch::{BulkParts, SearchParts};
+use opensearch::{BulkParts, MgetParts, SearchParts};
 use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::error::Error;
@@ -16,7 +16,8 @@ pub use opensearch::OpenSearch;
 use crate::{
     embedding::embedder_trait::Embedder,
     schemas::Document,
-    vectorstore::{VecStoreOptions, VectorStore},
+    telemetry,
+    vectorstore::{metadata_filter_to_opensearch, sort_by_ids, VecStoreOptions, VectorStore},
 };
 
 pub struct Store {
@@ -95,12 +96,27 @@ impl Store {
 
         Ok(result)
     }
+
+    fn hit_to_document(&self, hit: &Value) -> Document {
+        let page_content =
+            serde_json::from_value::<String>(hit["_source"][&self.content_field].clone()).unwrap();
+        let metadata =
+            serde_json::from_value::<HashMap<String, Value>>(hit["_source"]["metadata"].clone())
+                .unwrap();
+        let mut document = Document::new(page_content).with_metadata(metadata);
+        document.id = hit["_id"].as_str().map(str::to_string);
+        document
+    }
 }
 
 #[async_trait]
 impl VectorStore for Store {
     type Options = VecStoreOptions<Value>;
 
+    fn embedder(&self) -> Option<&dyn Embedder> {
+        Some(self.embedder.as_ref())
+    }
+
     async fn add_documents(
         &self,
         docs: &[Document],
@@ -118,7 +134,10 @@ impl VectorStore for Store {
         let mut body: Vec<JsonBody<_>> = Vec::with_capacity(docs.len() * 2);
 
         for (doc, vector) in docs.iter().zip(vectors.iter()) {
-            let operation = json!({"index": {}});
+            let operation = match &doc.id {
+                Some(id) => json!({"index": {"_id": id}}),
+                None => json!({"index": {}}),
+            };
             body.push(operation.into());
 
             let document = json!({
@@ -156,56 +175,181 @@ impl VectorStore for Store {
         limit: usize,
         opt: &Self::Options,
     ) -> Result<Vec<Document>, Box<dyn Error>> {
-        let query_vector = self.embedder.embed_query(query).await?;
+        let span = telemetry::similarity_search_span("opensearch", limit);
+        telemetry::in_span(span, async move {
+            let query_vector = self.embedder.embed_query(query).await?;
+            self.similarity_search_by_vector(&query_vector, limit, opt)
+                .await
+        })
+        .await
+    }
+
+    /// The score of the documents is the one of the `l2` space of the index, `1 / (1 + d²)` for
+    /// the euclidean distance `d`, between 0 and 1.
+    async fn similarity_search_by_vector(
+        &self,
+        vector: &[f64],
+        limit: usize,
+        opt: &Self::Options,
+    ) -> Result<Vec<Document>, Box<dyn Error>> {
+        let docs = self
+            .similarity_search_with_vectors(vector, limit, opt)
+            .await?;
+        Ok(docs.into_iter().map(|(doc, _)| doc).collect())
+    }
+
+    async fn similarity_search_with_vectors(
+        &self,
+        vector: &[f64],
+        limit: usize,
+        opt: &Self::Options,
+    ) -> Result<Vec<(Document, Vec<f64>)>, Box<dyn Error>> {
+        let filter = get_filter(opt);
         let query = build_similarity_search_query(
-            query_vector,
+            vector.to_vec(),
             &self.vector_field,
             limit,
             self.k,
-            opt.filters.clone(),
+            filter,
         );
 
         let response = self
             .client
             .search(SearchParts::Index(&[&self.index]))
             .from(0)
-            .size(3)
+            .size(limit as i64)
             .body(query)
             .send()
             .await?;
 
         let response_body = response.json::<Value>().await?;
 
-        let aoss_documents = response_body["hits"]["hits"]
+        let mut documents = response_body["hits"]["hits"]
             .as_array()
             .unwrap()
             .iter()
-            .map(|raw_value| {
-                serde_json::from_value::<HashMap<String, Value>>(raw_value.clone()).unwrap()
+            .map(|hit| {
+                let score = serde_json::from_value::<f64>(hit["_score"].clone()).unwrap();
+                let vector =
+                    serde_json::from_value::<Vec<f64>>(hit["_source"][&self.vector_field].clone())?;
+                Ok((self.hit_to_document(hit).with_score(score), vector))
             })
-            .collect::<Vec<_>>();
+            .collect::<Result<Vec<(Document, Vec<f64>)>, serde_json::Error>>()?;
+        if let Some(score_threshold) = opt.score_threshold {
+            documents.retain(|(doc, _)| doc.score >= score_threshold as f64);
+        }
 
-        let documents = aoss_documents
-            .into_iter()
-            .map(|item| {
-                let page_content =
-                    serde_json::from_value::<String>(item["_source"][&self.content_field].clone())
-                        .unwrap();
-                let metadata = serde_json::from_value::<HashMap<String, Value>>(
-                    item["_source"]["metadata"].clone(),
-                )
-                .unwrap();
-                let score = serde_json::from_value::<f64>(item["_score"].clone()).unwrap();
-                Document {
-                    page_content,
-                    metadata,
-                    score,
+        Ok(documents)
+    }
+
+    /// Searches the content field with a `match` query. The score of the documents is their
+    /// BM25 `_score`.
+    async fn keyword_search(
+        &self,
+        query: &str,
+        limit: usize,
+        opt: &Self::Options,
+    ) -> Result<Vec<Document>, Box<dyn Error>> {
+        let body = json!({
+            "size": limit,
+            "query": {
+                "bool": {
+                    "must": [{ "match": { &self.content_field: query } }],
+                    "filter": get_filter(opt).into_iter().collect::<Vec<_>>(),
                 }
+            }
+        });
+
+        let response = self
+            .client
+            .search(SearchParts::Index(&[&self.index]))
+            .body(body)
+            .send()
+            .await?
+            .error_for_status_code()
+            .map_err(Box::new)?;
+
+        let response_body = response.json::<Value>().await?;
+
+        let documents = response_body["hits"]["hits"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hit| {
+                let score = hit["_score"].as_f64().unwrap_or_default();
+                self.hit_to_document(hit).with_score(score)
             })
             .collect();
 
         Ok(documents)
     }
+
+    async fn delete(&self, ids: &[String], _opt: &Self::Options) -> Result<(), Box<dyn Error>> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let body: Vec<JsonBody<_>> = ids
+            .iter()
+            .map(|id| json!({"delete": {"_id": id}}).into())
+            .collect();
+
+        self.client
+            .bulk(BulkParts::Index(&self.index))
+            .body(body)
+            .send()
+            .await?
+            .error_for_status_code()
+            .map_err(Box::new)?;
+
+        Ok(())
+    }
+
+    async fn get_by_ids(
+        &self,
+        ids: &[String],
+        _opt: &Self::Options,
+    ) -> Result<Vec<Document>, Box<dyn Error>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let response = self
+            .client
+            .mget(MgetParts::Index(&self.index))
+            .body(json!({ "ids": ids }))
+            .send()
+            .await?
+            .error_for_status_code()
+            .map_err(Box::new)?;
+
+        let response_body = response.json::<Value>().await?;
+
+        let mut documents = response_body["docs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|doc| doc["found"].as_bool().unwrap_or(false))
+            .map(|doc| self.hit_to_document(doc))
+            .collect::<Vec<_>>();
+        sort_by_ids(&mut documents, ids);
+
+        Ok(documents)
+    }
+}
+
+/// Returns the `filters` and the `metadata_filter` of the options as one OpenSearch query.
+fn get_filter(opt: &VecStoreOptions<Value>) -> Option<Value> {
+    let metadata_filter = opt
+        .metadata_filter
+        .as_ref()
+        .map(metadata_filter_to_opensearch);
+    match (opt.filters.clone(), metadata_filter) {
+        (Some(filters), Some(metadata_filter)) => {
+            Some(json!({ "bool": { "filter": [filters, metadata_filter] } }))
+        }
+        (filters, metadata_filter) => filters.or(metadata_filter),
+    }
 }
 
 fn build_similarity_search_query(
@@ -4,9 +4,11 @@ use serde_json::Value;
 
 use crate::embedding::embedder_trait::Embedder;
 
+use super::MetadataFilter;
+
 /// The `VecStoreOptions` struct is responsible for determining options when
 /// interacting with a Vector Store. The options include `name_space`, `score_threshold`,
-/// `filters`, and `embedder`.
+/// `filters`, `metadata_filter`, and `embedder`.
 ///
 /// # Usage
 /// ```rust,ignore
@@ -14,15 +16,30 @@ use crate::embedding::embedder_trait::Embedder;
 ///     .with_name_space("my_custom_namespace")
 ///     .with_score_threshold(0.5)
 ///     .with_filters(json!({"genre": "Sci-Fi"}))
+///     .with_metadata_filter(MetadataFilter::gte("year", 1990))
 ///     .with_embedder(my_embedder);
 /// ```
 pub struct VecStoreOptions<F> {
     pub name_space: Option<String>,
     pub score_threshold: Option<f32>,
     pub filters: Option<F>,
+    /// A filter translated to the native query of the store, applied with `filters`.
+    pub metadata_filter: Option<MetadataFilter>,
     pub embedder: Option<Arc<dyn Embedder>>,
 }
 
+/// The options of a vector store call that can override the embedder of the store.
+pub trait EmbedderOption {
+    /// Returns the embedder of the call, if any, used instead of the one of the store.
+    fn embedder(&self) -> Option<&dyn Embedder>;
+}
+
+impl<F> EmbedderOption for VecStoreOptions<F> {
+    fn embedder(&self) -> Option<&dyn Embedder> {
+        self.embedder.as_deref()
+    }
+}
+
 impl Default for VecStoreOptions<Value> {
     fn default() -> Self {
         Self::new()
@@ -35,6 +52,7 @@ impl<F> VecStoreOptions<F> {
             name_space: None,
             score_threshold: None,
             filters: None,
+            metadata_filter: None,
             embedder: None,
         }
     }
@@ -54,6 +72,11 @@ impl<F> VecStoreOptions<F> {
         self
     }
 
+    pub fn with_metadata_filter(mut self, metadata_filter: MetadataFilter) -> Self {
+        self.metadata_filter = Some(metadata_filter);
+        self
+    }
+
     pub fn with_embedder<E: Embedder + 'static>(mut self, embedder: E) -> Self {
         self.embedder = Some(Arc::new(embedder));
         self
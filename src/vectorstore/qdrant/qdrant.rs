@@ -1,7 +1,12 @@
 use async_trait::async_trait;
 use qdrant_client::client::Payload;
-use qdrant_client::qdrant::{Filter, PointStruct, SearchPointsBuilder, UpsertPointsBuilder};
+use qdrant_client::qdrant::{
+    point_id::PointIdOptions, vector_output::Vector, vectors_output::VectorsOptions, Condition,
+    DeletePointsBuilder, Filter, GetPointsBuilder, PointId, PointStruct, PointsIdsList, Range,
+    ScoredPoint, SearchPointsBuilder, UpsertPointsBuilder, VectorOutput, VectorsOutput,
+};
 use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::error::Error;
 use std::sync::Arc;
 
@@ -14,7 +19,8 @@ pub use qdrant_client::Qdrant as QdrantClient;
 use crate::{
     embedding::embedder_trait::Embedder,
     schemas::Document,
-    vectorstore::{VecStoreOptions, VectorStore},
+    telemetry,
+    vectorstore::{sort_by_ids, MetadataFilter, VecStoreOptions, VectorStore},
 };
 use uuid::Uuid;
 
@@ -29,12 +35,185 @@ pub struct Store {
 
 type QdrantOptions = VecStoreOptions<Value>;
 
+impl Store {
+    fn point_to_document(
+        &self,
+        id: Option<PointId>,
+        payload: HashMap<String, qdrant_client::qdrant::Value>,
+    ) -> Document {
+        let page_content = payload[&self.content_field].to_string();
+        let metadata =
+            serde_json::from_value(payload[&self.metadata_field].clone().into_json()).unwrap();
+        let mut document = Document::new(page_content).with_metadata(metadata);
+        document.id = id.and_then(|id| id.point_id_options).map(|id| match id {
+            PointIdOptions::Num(num) => num.to_string(),
+            PointIdOptions::Uuid(uuid) => uuid,
+        });
+        document
+    }
+
+    fn scored_point_to_document(&self, point: ScoredPoint) -> Document {
+        self.point_to_document(point.id, point.payload)
+            .with_score((point.score as f64).clamp(0.0, 1.0))
+    }
+
+    async fn search_points(
+        &self,
+        vector: &[f64],
+        limit: usize,
+        opt: &QdrantOptions,
+        with_vectors: bool,
+    ) -> Result<Vec<ScoredPoint>, Box<dyn Error>> {
+        if opt.name_space.is_some() {
+            return Err("Qdrant doesn't support namespaces".into());
+        }
+
+        if opt.filters.is_some() {
+            return Err(
+                "'qdrant_client' doesn't support 'serde_json::Value' filters. 
+            Use `search_filter` when constructing VectorStore instead"
+                    .into(),
+            );
+        }
+
+        let query_vector: Vec<f32> = vector.iter().map(|f| *f as f32).collect();
+
+        let mut operation =
+            SearchPointsBuilder::new(&self.collection_name, query_vector, limit as u64)
+                .with_payload(true)
+                .with_vectors(with_vectors);
+        if let Some(score_threshold) = opt.score_threshold {
+            operation = operation.score_threshold(score_threshold);
+        }
+        let mut conditions = Vec::new();
+        if let Some(filter) = &self.search_filter {
+            conditions.push(Condition::from(filter.clone()));
+        }
+        if let Some(metadata_filter) = &opt.metadata_filter {
+            conditions.push(self.metadata_condition(metadata_filter)?);
+        }
+        if !conditions.is_empty() {
+            operation = operation.filter(Filter::must(conditions));
+        }
+        Ok(self.client.search_points(operation).await?.result)
+    }
+
+    /// Translates a [`MetadataFilter`] to a condition on the metadata field of the payload.
+    fn metadata_condition(&self, filter: &MetadataFilter) -> Result<Condition, Box<dyn Error>> {
+        metadata_condition(&self.metadata_field, filter)
+    }
+}
+
+/// Translates a [`MetadataFilter`] to a condition on the `metadata_field` of the payload.
+fn metadata_condition(
+    metadata_field: &str,
+    filter: &MetadataFilter,
+) -> Result<Condition, Box<dyn Error>> {
+    let field = |key: &str| format!("{metadata_field}.{key}");
+    let equals = |key: &str, value: &Value| -> Result<Condition, Box<dyn Error>> {
+        match value {
+            Value::String(text) => Ok(Condition::matches(field(key), text.clone())),
+            Value::Bool(boolean) => Ok(Condition::matches(field(key), *boolean)),
+            Value::Number(number) => match number.as_i64() {
+                Some(integer) => Ok(Condition::matches(field(key), integer)),
+                None => Ok(Condition::range(
+                    field(key),
+                    Range {
+                        gte: number.as_f64(),
+                        lte: number.as_f64(),
+                        ..Default::default()
+                    },
+                )),
+            },
+            _ => Err(format!("Qdrant can't match the value {value} of '{key}'").into()),
+        }
+    };
+    let range = |key: &str, value: &Value, range: fn(f64) -> Range| match value.as_f64() {
+        Some(number) => Ok(Condition::range(field(key), range(number))),
+        None => Err(format!(
+            "Qdrant only compares numbers, not the value {value} of '{key}'"
+        )),
+    };
+    let conditions = |filters: &[MetadataFilter]| {
+        filters
+            .iter()
+            .map(|filter| metadata_condition(metadata_field, filter))
+            .collect::<Result<Vec<_>, _>>()
+    };
+
+    Ok(match filter {
+        MetadataFilter::Eq(key, value) => equals(key, value)?,
+        MetadataFilter::Ne(key, value) => Filter::must_not([equals(key, value)?]).into(),
+        MetadataFilter::In(key, values) => any(values
+            .iter()
+            .map(|value| equals(key, value))
+            .collect::<Result<Vec<_>, _>>()?),
+        MetadataFilter::Gt(key, value) => range(key, value, |gt| Range {
+            gt: Some(gt),
+            ..Default::default()
+        })?,
+        MetadataFilter::Gte(key, value) => range(key, value, |gte| Range {
+            gte: Some(gte),
+            ..Default::default()
+        })?,
+        MetadataFilter::Lt(key, value) => range(key, value, |lt| Range {
+            lt: Some(lt),
+            ..Default::default()
+        })?,
+        MetadataFilter::Lte(key, value) => range(key, value, |lte| Range {
+            lte: Some(lte),
+            ..Default::default()
+        })?,
+        MetadataFilter::Exists(key) => Filter::must_not([Condition::is_empty(field(key))]).into(),
+        MetadataFilter::And(filters) => Filter::must(conditions(filters)?).into(),
+        MetadataFilter::Or(filters) => any(conditions(filters)?),
+        MetadataFilter::Not(filter) => {
+            Filter::must_not([metadata_condition(metadata_field, filter)?]).into()
+        }
+    })
+}
+
+/// Returns the condition matching any of `conditions`. Qdrant matches every point with an empty
+/// `should`, so no condition gives the negation of the empty filter, matching no point, as an
+/// empty `OR` in SQL.
+fn any(conditions: Vec<Condition>) -> Condition {
+    if conditions.is_empty() {
+        Filter::must_not([Condition::from(Filter::default())]).into()
+    } else {
+        Filter::should(conditions).into()
+    }
+}
+
+/// Returns the unnamed dense vector of a point.
+fn dense_vector(vectors: Option<VectorsOutput>) -> Option<Vec<f64>> {
+    match vectors?.vectors_options? {
+        VectorsOptions::Vector(VectorOutput {
+            vector: Some(Vector::Dense(dense)),
+            ..
+        }) => Some(dense.data.into_iter().map(f64::from).collect()),
+        _ => None,
+    }
+}
+
+/// Qdrant ids are either unsigned integers or UUIDs.
+fn point_id(id: &str) -> PointId {
+    match id.parse::<u64>() {
+        Ok(num) => num.into(),
+        Err(_) => id.to_string().into(),
+    }
+}
+
 #[async_trait]
 impl VectorStore for Store {
     type Options = QdrantOptions;
 
+    fn embedder(&self) -> Option<&dyn Embedder> {
+        Some(self.embedder.as_ref())
+    }
+
     /// Add documents to the store.
-    /// Returns a list of document IDs added to the Qdrant collection.
+    /// Returns a list of document IDs added to the Qdrant collection. The ids of the documents
+    /// must be UUIDs or unsigned integers.
     async fn add_documents(
         &self,
         docs: &[Document],
@@ -43,7 +222,9 @@ impl VectorStore for Store {
         let embedder = opt.embedder.as_ref().unwrap_or(&self.embedder);
         let texts: Vec<String> = docs.iter().map(|d| d.page_content.clone()).collect();
 
-        let ids = docs.iter().map(|_| Uuid::new_v4().to_string());
+        let ids = docs
+            .iter()
+            .map(|d| d.id.clone().unwrap_or_else(|| Uuid::new_v4().to_string()));
         let vectors = embedder.embed_documents(&texts).await?.into_iter();
         let payloads = docs.iter().map(|d| {
             json!({
@@ -56,7 +237,8 @@ impl VectorStore for Store {
 
         for (id, (vector, payload)) in ids.clone().zip(vectors.zip(payloads)) {
             let vector: Vec<f32> = vector.into_iter().map(|f| f as f32).collect();
-            let point = PointStruct::new(id, vector, Payload::try_from(payload).unwrap());
+            let point =
+                PointStruct::new(point_id(&id), vector, Payload::try_from(payload).unwrap());
             points.push(point);
         }
 
@@ -75,56 +257,182 @@ impl VectorStore for Store {
         limit: usize,
         opt: &QdrantOptions,
     ) -> Result<Vec<Document>, Box<dyn Error>> {
+        let span = telemetry::similarity_search_span("qdrant", limit);
+        telemetry::in_span(span, async move {
+            let embedder = opt.embedder.as_ref().unwrap_or(&self.embedder);
+            let query_vector = embedder.embed_query(query).await?;
+            self.similarity_search_by_vector(&query_vector, limit, opt)
+                .await
+        })
+        .await
+    }
+
+    /// The collection is expected to use the cosine distance, as created by the builder: the
+    /// score of the documents is their cosine similarity, clamped between 0 and 1.
+    async fn similarity_search_by_vector(
+        &self,
+        vector: &[f64],
+        limit: usize,
+        opt: &QdrantOptions,
+    ) -> Result<Vec<Document>, Box<dyn Error>> {
+        let points = self.search_points(vector, limit, opt, false).await?;
+        Ok(points
+            .into_iter()
+            .map(|point| self.scored_point_to_document(point))
+            .collect())
+    }
+
+    /// Returns the vectors stored in the collection, which must have a single unnamed dense
+    /// vector per point, as created by the builder.
+    async fn similarity_search_with_vectors(
+        &self,
+        vector: &[f64],
+        limit: usize,
+        opt: &QdrantOptions,
+    ) -> Result<Vec<(Document, Vec<f64>)>, Box<dyn Error>> {
+        let points = self.search_points(vector, limit, opt, true).await?;
+        points
+            .into_iter()
+            .map(|mut point| {
+                let vector = dense_vector(point.vectors.take())
+                    .ok_or("Qdrant returned a point without a dense vector")?;
+                Ok((self.scored_point_to_document(point), vector))
+            })
+            .collect()
+    }
+
+    async fn delete(&self, ids: &[String], opt: &QdrantOptions) -> Result<(), Box<dyn Error>> {
         if opt.name_space.is_some() {
             return Err("Qdrant doesn't support namespaces".into());
         }
-
-        if opt.filters.is_some() {
-            return Err(
-                "'qdrant_client' doesn't support 'serde_json::Value' filters. 
-            Use `search_filter` when constructing VectorStore instead"
-                    .into(),
-            );
+        if ids.is_empty() {
+            return Ok(());
         }
 
-        let embedder = opt.embedder.as_ref().unwrap_or(&self.embedder);
-        let query_vector: Vec<f32> = embedder
-            .embed_query(query)
-            .await?
-            .into_iter()
-            .map(|f| f as f32)
-            .collect();
+        let points = PointsIdsList {
+            ids: ids.iter().map(|id| point_id(id)).collect(),
+        };
+        self.client
+            .delete_points(
+                DeletePointsBuilder::new(&self.collection_name)
+                    .points(points)
+                    .wait(true),
+            )
+            .await?;
+        Ok(())
+    }
 
-        let mut operation =
-            SearchPointsBuilder::new(&self.collection_name, query_vector, limit as u64)
-                .with_payload(true);
-        if let Some(score_threshold) = opt.score_threshold {
-            operation = operation.score_threshold(score_threshold);
+    async fn get_by_ids(
+        &self,
+        ids: &[String],
+        opt: &QdrantOptions,
+    ) -> Result<Vec<Document>, Box<dyn Error>> {
+        if opt.name_space.is_some() {
+            return Err("Qdrant doesn't support namespaces".into());
         }
-        if let Some(filter) = &self.search_filter {
-            operation = operation.filter(filter.clone());
+        if ids.is_empty() {
+            return Ok(Vec::new());
         }
-        let results = self.client.search_points(operation).await?;
 
-        let documents = results
+        let points = ids.iter().map(|id| point_id(id)).collect::<Vec<_>>();
+        let results = self
+            .client
+            .get_points(GetPointsBuilder::new(&self.collection_name, points).with_payload(true))
+            .await?;
+
+        let mut documents = results
             .result
             .into_iter()
-            .map(|scored_point| {
-                let payload = scored_point.payload;
-
-                let page_content = payload[&self.content_field].to_string();
-                let metadata =
-                    serde_json::from_value(payload[&self.metadata_field].clone().into_json())
-                        .unwrap();
-                let score = scored_point.score as f64;
-                Document {
-                    page_content,
-                    metadata,
-                    score,
+            .map(|point| self.point_to_document(point.id, point.payload))
+            .collect::<Vec<_>>();
+        sort_by_ids(&mut documents, ids);
+        Ok(documents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dense_vector() {
+        let vectors = |vector| VectorsOutput {
+            vectors_options: Some(VectorsOptions::Vector(VectorOutput {
+                vector: Some(vector),
+                ..Default::default()
+            })),
+        };
+
+        assert_eq!(
+            dense_vector(Some(vectors(Vector::Dense(vec![0.5, 1.0].into())))),
+            Some(vec![0.5, 1.0])
+        );
+        assert_eq!(
+            dense_vector(Some(vectors(Vector::Sparse(Default::default())))),
+            None
+        );
+        assert_eq!(dense_vector(None), None);
+    }
+
+    #[test]
+    fn test_metadata_condition() {
+        let condition = |filter: &MetadataFilter| metadata_condition("metadata", filter).unwrap();
+        let match_none = Condition::from(Filter::must_not([Condition::from(Filter::default())]));
+
+        assert_eq!(
+            condition(&MetadataFilter::eq("genre", "Sci-Fi")),
+            Condition::matches("metadata.genre", "Sci-Fi".to_string())
+        );
+        assert_eq!(
+            condition(&MetadataFilter::ne("draft", true)),
+            Condition::from(Filter::must_not([Condition::matches(
+                "metadata.draft",
+                true
+            )]))
+        );
+        assert_eq!(
+            condition(&MetadataFilter::eq("rating", 8.5)),
+            Condition::range(
+                "metadata.rating",
+                Range {
+                    gte: Some(8.5),
+                    lte: Some(8.5),
+                    ..Default::default()
                 }
-            })
-            .collect();
+            )
+        );
+        assert_eq!(
+            condition(&(MetadataFilter::gt("year", 1990) | MetadataFilter::exists("director"))),
+            Condition::from(Filter::should([
+                Condition::range(
+                    "metadata.year",
+                    Range {
+                        gt: Some(1990.0),
+                        ..Default::default()
+                    }
+                ),
+                Filter::must_not([Condition::is_empty("metadata.director")]).into(),
+            ]))
+        );
+        assert_eq!(
+            condition(&MetadataFilter::is_in("rating", [8, 9])),
+            Condition::from(Filter::should([
+                Condition::matches("metadata.rating", 8i64),
+                Condition::matches("metadata.rating", 9i64),
+            ]))
+        );
 
-        Ok(documents)
+        // An empty disjunction matches nothing, as in SQL, and an empty conjunction everything.
+        assert_eq!(condition(&MetadataFilter::Or(Vec::new())), match_none);
+        assert_eq!(
+            condition(&MetadataFilter::is_in("rating", Vec::<i64>::new())),
+            match_none
+        );
+        assert_eq!(
+            condition(&MetadataFilter::And(Vec::new())),
+            Condition::from(Filter::must(Vec::<Condition>::new()))
+        );
+
+        assert!(metadata_condition("metadata", &MetadataFilter::gt("genre", "Sci-Fi")).is_err());
     }
 }
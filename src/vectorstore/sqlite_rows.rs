@@ -0,0 +1,164 @@
+//! The rows of the documents of the sqlite vector stores, shared by `sqlite-vec` and
+//! `sqlite-vss`: a table of the documents, whose integer rowids are the ids of the documents,
+//! and a virtual table of their embeddings with the same rowids. The other sqlite stores of the
+//! `sqlite` feature only share [`placeholders`].
+#![cfg_attr(
+    not(any(feature = "sqlite-vec", feature = "sqlite-vss")),
+    allow(dead_code, unused_imports)
+)]
+
+use std::{collections::HashMap, error::Error};
+
+use indoc::formatdoc;
+use serde_json::{json, Value};
+use sqlx::{sqlite::SqliteRow, Pool, Row, Sqlite, Transaction};
+
+use crate::{schemas::Document, vectorstore::sort_by_ids};
+
+/// Parses the ids of the documents, which are the rowids of the table.
+pub(crate) fn parse_ids(ids: &[String]) -> Result<Vec<i64>, Box<dyn Error>> {
+    ids.iter()
+        .map(|id| {
+            id.parse::<i64>()
+                .map_err(|_| format!("Invalid id {id}, the ids are integer rowids").into())
+        })
+        .collect()
+}
+
+/// Returns the `?` placeholders of `len` values, separated by commas.
+pub(crate) fn placeholders(len: usize) -> String {
+    vec!["?"; len].join(",")
+}
+
+pub(crate) fn row_to_document(row: &SqliteRow) -> Result<Document, sqlx::Error> {
+    let id: i64 = row.try_get("id")?;
+    let page_content: String = row.try_get("text")?;
+    let metadata_json: Value = row.try_get("metadata")?;
+
+    let metadata = if let Value::Object(obj) = metadata_json {
+        obj.into_iter().collect()
+    } else {
+        HashMap::new() // Or handle this case as needed
+    };
+
+    Ok(Document::new(page_content)
+        .with_metadata(metadata)
+        .with_id(id.to_string()))
+}
+
+/// Returns the embedding of the row, stored as a JSON array in the `text_embedding` column.
+pub(crate) fn row_to_vector(row: &SqliteRow) -> Result<Vec<f64>, Box<dyn Error>> {
+    let text_embedding: String = row.try_get("text_embedding")?;
+    Ok(serde_json::from_str(&text_embedding)?)
+}
+
+/// Deletes the rows of `table` and their embeddings in `index_table`.
+async fn delete_rows(
+    tx: &mut Transaction<'_, Sqlite>,
+    table: &str,
+    index_table: &str,
+    rowids: &[i64],
+) -> Result<(), Box<dyn Error>> {
+    let placeholders = placeholders(rowids.len());
+    for table in [table, index_table] {
+        let sql = format!("DELETE FROM {table} WHERE rowid IN ({placeholders})");
+        let mut query = sqlx::query(&sql);
+        for rowid in rowids {
+            query = query.bind(rowid);
+        }
+        query.execute(&mut **tx).await?;
+    }
+    Ok(())
+}
+
+/// Inserts the documents with their vectors, replacing the documents with the same ids. The
+/// embeddings are inserted in `index_table` by the trigger of `table`. Returns the ids of the
+/// documents.
+pub(crate) async fn upsert_documents(
+    pool: &Pool<Sqlite>,
+    table: &str,
+    index_table: &str,
+    docs: &[Document],
+    vectors: &[Vec<f64>],
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut tx = pool.begin().await?;
+    let mut ids = Vec::with_capacity(docs.len());
+
+    for (doc, vector) in docs.iter().zip(vectors.iter()) {
+        let rowid = match &doc.id {
+            Some(id) => {
+                let rowid = parse_ids(std::slice::from_ref(id))?[0];
+                delete_rows(&mut tx, table, index_table, &[rowid]).await?;
+                Some(rowid)
+            }
+            None => None,
+        };
+
+        let text_embedding = json!(&vector);
+        let id = sqlx::query(&formatdoc! {"
+            INSERT INTO {table}
+                (rowid, text, metadata, text_embedding)
+            VALUES
+                (?,?,?,?)"
+        })
+        .bind(rowid)
+        .bind(&doc.page_content)
+        .bind(json!(&doc.metadata))
+        .bind(text_embedding.to_string())
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+
+        ids.push(id.to_string());
+    }
+
+    tx.commit().await?;
+    Ok(ids)
+}
+
+/// Deletes the documents of the given ids and their embeddings.
+pub(crate) async fn delete_documents(
+    pool: &Pool<Sqlite>,
+    table: &str,
+    index_table: &str,
+    ids: &[String],
+) -> Result<(), Box<dyn Error>> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+    let rowids = parse_ids(ids)?;
+    let mut tx = pool.begin().await?;
+    delete_rows(&mut tx, table, index_table, &rowids).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Returns the documents of the given ids, in the order of `ids`.
+pub(crate) async fn get_documents(
+    pool: &Pool<Sqlite>,
+    table: &str,
+    ids: &[String],
+) -> Result<Vec<Document>, Box<dyn Error>> {
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let rowids = parse_ids(ids)?;
+
+    let sql = format!(
+        "SELECT rowid AS id, text, metadata FROM {table} WHERE rowid IN ({})",
+        placeholders(rowids.len())
+    );
+    let mut query = sqlx::query(&sql);
+    for rowid in &rowids {
+        query = query.bind(rowid);
+    }
+    let mut docs = query
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(row_to_document)
+        .collect::<Result<Vec<Document>, sqlx::Error>>()?;
+
+    sort_by_ids(&mut docs, ids);
+    Ok(docs)
+}
@@ -8,7 +8,14 @@ use sqlx::{Pool, Row, Sqlite};
 use crate::{
     embedding::embedder_trait::Embedder,
     schemas::Document,
-    vectorstore::{VecStoreOptions, VectorStore},
+    telemetry,
+    vectorstore::{
+        euclidean_distance_to_score, metadata_filter_to_sql,
+        sqlite_rows::{
+            delete_documents, get_documents, row_to_document, row_to_vector, upsert_documents,
+        },
+        SqlDialect, VecStoreOptions, VectorStore,
+    },
 };
 
 pub struct Store {
@@ -82,6 +89,10 @@ impl Store {
 impl VectorStore for Store {
     type Options = SqliteOptions;
 
+    fn embedder(&self) -> Option<&dyn Embedder> {
+        Some(self.embedder.as_ref())
+    }
+
     async fn add_documents(
         &self,
         docs: &[Document],
@@ -98,32 +109,7 @@ impl VectorStore for Store {
         }
 
         let table = &self.table;
-
-        let mut tx = self.pool.begin().await?;
-
-        let mut ids = Vec::with_capacity(docs.len());
-
-        for (doc, vector) in docs.iter().zip(vectors.iter()) {
-            let text_embedding = json!(&vector);
-            let id = sqlx::query(&formatdoc! {"
-                INSERT INTO {table}
-                    (text, metadata, text_embedding)
-                VALUES
-                    (?,?,?)"
-            })
-            .bind(&doc.page_content)
-            .bind(json!(&doc.metadata))
-            .bind(text_embedding.to_string())
-            .execute(&mut *tx)
-            .await?
-            .last_insert_rowid();
-
-            ids.push(id.to_string());
-        }
-
-        tx.commit().await?;
-
-        Ok(ids)
+        upsert_documents(&self.pool, table, &format!("vec_{table}"), docs, &vectors).await
     }
 
     async fn similarity_search(
@@ -132,15 +118,46 @@ impl VectorStore for Store {
         limit: usize,
         opt: &Self::Options,
     ) -> Result<Vec<Document>, Box<dyn Error>> {
+        let span = telemetry::similarity_search_span("sqlite", limit);
+        telemetry::in_span(span, async move {
+            let query_vector = self.embedder.embed_query(query).await?;
+            self.similarity_search_by_vector(&query_vector, limit, opt)
+                .await
+        })
+        .await
+    }
+
+    /// The score of the documents is `1 / (1 + d)` for the euclidean distance `d` of `vec0`.
+    async fn similarity_search_by_vector(
+        &self,
+        vector: &[f64],
+        limit: usize,
+        opt: &Self::Options,
+    ) -> Result<Vec<Document>, Box<dyn Error>> {
+        let docs = self
+            .similarity_search_with_vectors(vector, limit, opt)
+            .await?;
+        Ok(docs.into_iter().map(|(doc, _)| doc).collect())
+    }
+
+    async fn similarity_search_with_vectors(
+        &self,
+        vector: &[f64],
+        limit: usize,
+        opt: &Self::Options,
+    ) -> Result<Vec<(Document, Vec<f64>)>, Box<dyn Error>> {
         let table = &self.table;
 
-        let query_vector = json!(self.embedder.embed_query(query).await?);
+        let query_vector = json!(vector);
 
         let filter = self.get_filters(opt)?;
 
         let mut metadata_query = filter
             .iter()
             .map(|(k, v)| format!("json_extract(e.metadata, '$.{k}') = '{v}'"))
+            .chain(opt.metadata_filter.iter().map(|metadata_filter| {
+                metadata_filter_to_sql(metadata_filter, SqlDialect::Sqlite, "e.metadata")
+            }))
             .collect::<Vec<String>>()
             .join(" AND ");
 
@@ -150,8 +167,10 @@ impl VectorStore for Store {
 
         let rows = sqlx::query(&formatdoc! {"
             SELECT
+                e.rowid AS id,
                 text,
                 metadata,
+                e.text_embedding,
                 distance
             FROM {table} e
             INNER JOIN vec_{table} v on v.rowid = e.rowid
@@ -164,27 +183,31 @@ impl VectorStore for Store {
         .fetch_all(&self.pool)
         .await?;
 
-        let docs = rows
+        let mut docs = rows
             .into_iter()
             .map(|row| {
-                let page_content: String = row.try_get("text")?;
-                let metadata_json: Value = row.try_get("metadata")?;
-                let score: f64 = row.try_get("distance")?;
-
-                let metadata = if let Value::Object(obj) = metadata_json {
-                    obj.into_iter().collect()
-                } else {
-                    HashMap::new() // Or handle this case as needed
-                };
-
-                Ok(Document {
-                    page_content,
-                    metadata,
-                    score,
-                })
+                let distance: f64 = row.try_get("distance")?;
+                let doc = row_to_document(&row)?.with_score(euclidean_distance_to_score(distance));
+                Ok((doc, row_to_vector(&row)?))
             })
-            .collect::<Result<Vec<Document>, sqlx::Error>>()?;
+            .collect::<Result<Vec<(Document, Vec<f64>)>, Box<dyn Error>>>()?;
+        if let Some(score_threshold) = opt.score_threshold {
+            docs.retain(|(doc, _)| doc.score >= score_threshold as f64);
+        }
 
         Ok(docs)
     }
+
+    async fn delete(&self, ids: &[String], _opt: &Self::Options) -> Result<(), Box<dyn Error>> {
+        let table = &self.table;
+        delete_documents(&self.pool, table, &format!("vec_{table}"), ids).await
+    }
+
+    async fn get_by_ids(
+        &self,
+        ids: &[String],
+        _opt: &Self::Options,
+    ) -> Result<Vec<Document>, Box<dyn Error>> {
+        get_documents(&self.pool, &self.table, ids).await
+    }
 }
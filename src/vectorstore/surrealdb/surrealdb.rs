@@ -9,7 +9,8 @@ use surrealdb::{Connection, Surreal};
 use crate::{
     embedding::embedder_trait::Embedder,
     schemas::Document,
-    vectorstore::{VecStoreOptions, VectorStore},
+    telemetry,
+    vectorstore::{metadata_filter_to_surrealql, sort_by_ids, VecStoreOptions, VectorStore},
 };
 
 // INSERT INTO documents {
@@ -43,6 +44,13 @@ impl<C: Connection> Store<C> {
             .unwrap_or_else(|| "collection".to_string())
     }
 
+    fn collection_predicate(&self) -> &'static str {
+        match &self.collection_table_name {
+            Some(_) => " AND metadata[$collection_metadata_key] = $collection_name ",
+            None => "",
+        }
+    }
+
     pub async fn initialize(&self) -> Result<(), Box<dyn Error>> {
         self.create_collection_table_if_not_exists().await?;
         Ok(())
@@ -92,6 +100,10 @@ impl<C: Connection> Store<C> {
 impl<C: Connection> VectorStore for Store<C> {
     type Options = VecStoreOptions<Value>;
 
+    fn embedder(&self) -> Option<&dyn Embedder> {
+        Some(self.embedder.as_ref())
+    }
+
     async fn add_documents(
         &self,
         docs: &[Document],
@@ -109,56 +121,42 @@ impl<C: Connection> VectorStore for Store<C> {
 
         let mut ids = Vec::with_capacity(docs.len());
 
+        let collection_table_name = self.get_collection_table_name();
+
         for (doc, vector) in docs.iter().zip(vectors.iter()) {
-            match &self.collection_table_name {
-                Some(collection_table_name) => {
-                    let mut metadata: HashMap<String, Value> = doc.metadata.clone();
-                    metadata.insert(
-                        self.get_collection_metdata_key(),
-                        Value::String(self.collection_name.to_owned()),
-                    );
-
-                    let mut result = self
-                        .db
-                        .query(formatdoc! {"
-                            CREATE {collection_table_name} CONTENT {{
-                                text: $text,
-                                embedding: $embedding,
-                                metadata: $metadata,
-                            }}
-                            RETURN record::id(id) as id"
-                        })
-                        .bind(("text", doc.page_content.to_owned()))
-                        .bind(("embedding", vector.to_owned()))
-                        .bind(("metadata", metadata.to_owned()))
-                        .await?
-                        .check()?;
-
-                    let id: Option<String> = result.take("id")?;
-                    ids.push(id.unwrap());
-                }
-                None => {
-                    let collection_table_name = &self.collection_name;
-                    let mut result = self
-                        .db
-                        .query(formatdoc! {"
-                            CREATE {collection_table_name} CONTENT {{
-                                text: $text,
-                                embedding: $embedding,
-                                metadata: $metadata,
-                            }}
-                            RETURN record::id(id) as id"
-                        })
-                        .bind(("text", doc.page_content.to_owned()))
-                        .bind(("embedding", vector.to_owned()))
-                        .bind(("metadata", doc.metadata.to_owned()))
-                        .await?
-                        .check()?;
-
-                    let id: Option<String> = result.take("id")?;
-                    ids.push(id.unwrap());
-                }
+            let mut metadata: HashMap<String, Value> = doc.metadata.clone();
+            if self.collection_table_name.is_some() {
+                metadata.insert(
+                    self.get_collection_metdata_key(),
+                    Value::String(self.collection_name.to_owned()),
+                );
             }
+
+            // A document with an id replaces the stored one.
+            let statement = match &doc.id {
+                Some(_) => format!("UPSERT type::thing('{collection_table_name}', $id)"),
+                None => format!("CREATE {collection_table_name}"),
+            };
+
+            let mut result = self
+                .db
+                .query(formatdoc! {"
+                    {statement} CONTENT {{
+                        text: $text,
+                        embedding: $embedding,
+                        metadata: $metadata,
+                    }}
+                    RETURN record::id(id) as id"
+                })
+                .bind(("id", doc.id.to_owned()))
+                .bind(("text", doc.page_content.to_owned()))
+                .bind(("embedding", vector.to_owned()))
+                .bind(("metadata", metadata))
+                .await?
+                .check()?;
+
+            let id: Option<String> = result.take("id")?;
+            ids.push(id.unwrap());
         }
 
         Ok(ids)
@@ -170,49 +168,131 @@ impl<C: Connection> VectorStore for Store<C> {
         limit: usize,
         opt: &Self::Options,
     ) -> Result<Vec<Document>, Box<dyn Error>> {
+        let span = telemetry::similarity_search_span("surrealdb", limit);
+        telemetry::in_span(span, async move {
+            let query_vector = self.embedder.embed_query(query).await?;
+            self.similarity_search_by_vector(&query_vector, limit, opt)
+                .await
+        })
+        .await
+    }
+
+    /// The score of the documents is their cosine similarity, clamped between 0 and 1.
+    async fn similarity_search_by_vector(
+        &self,
+        vector: &[f64],
+        limit: usize,
+        opt: &Self::Options,
+    ) -> Result<Vec<Document>, Box<dyn Error>> {
+        let docs = self
+            .similarity_search_with_vectors(vector, limit, opt)
+            .await?;
+        Ok(docs.into_iter().map(|(doc, _)| doc).collect())
+    }
+
+    async fn similarity_search_with_vectors(
+        &self,
+        vector: &[f64],
+        limit: usize,
+        opt: &Self::Options,
+    ) -> Result<Vec<(Document, Vec<f64>)>, Box<dyn Error>> {
         let collection_name = &self.collection_name;
         let collection_table_name = self.get_collection_table_name();
 
-        let query_vector = self.embedder.embed_query(query).await?;
-
-        let collection_predicate = match &self.collection_table_name {
-            Some(_) => " AND metadata[$collection_metadata_key] = $collection_name ",
-            None => "",
+        let collection_predicate = self.collection_predicate();
+        let (metadata_predicate, metadata_params) = match &opt.metadata_filter {
+            Some(metadata_filter) => {
+                let (condition, params) = metadata_filter_to_surrealql(metadata_filter);
+                (format!(" AND {condition} "), params)
+            }
+            None => (String::new(), Vec::new()),
         };
 
-        let mut result = self
+        let mut query = self
             .db
             .query(formatdoc! {"
                 SELECT 
                     record::id(id) as id,
                     text,
                     metadata,
+                    embedding,
                     vector::similarity::cosine(embedding, $embedding) as similarity
                 FROM {collection_table_name}
-                WHERE vector::similarity::cosine(embedding, $embedding) >= $score_threshold {collection_predicate}
+                WHERE vector::similarity::cosine(embedding, $embedding) >= $score_threshold {collection_predicate} {metadata_predicate}
                 ORDER BY similarity DESC LIMIT $k"
             })
             .bind(("collection_name", collection_name.to_owned()))
             .bind(("collection_metadata_key", self.get_collection_metdata_key().to_owned()))
             .bind(("score_threshold", opt.score_threshold.unwrap_or(0.0)))
             .bind(("k", limit))
-            .bind(("embedding", query_vector.to_owned()))
-            .await?
-            .check()?;
+            .bind(("embedding", vector.to_vec()));
+        for param in metadata_params {
+            query = query.bind(param);
+        }
+        let mut result = query.await?.check()?;
 
         let query_result: Vec<Row> = result.take(0)?;
 
         let documents = query_result
             .into_iter()
-            .map(|row| Document {
-                page_content: row.text,
-                metadata: row.metadata,
-                score: row.similarity,
+            .map(|mut row| {
+                let score = row.similarity.clamp(0.0, 1.0);
+                let embedding = std::mem::take(&mut row.embedding);
+                (row.into_document().with_score(score), embedding)
             })
             .collect();
 
         Ok(documents)
     }
+
+    async fn delete(&self, ids: &[String], _opt: &Self::Options) -> Result<(), Box<dyn Error>> {
+        let collection_table_name = self.get_collection_table_name();
+        let collection_predicate = self.collection_predicate();
+
+        self.db
+            .query(formatdoc! {"
+                DELETE {collection_table_name}
+                WHERE record::id(id) IN $ids {collection_predicate}"
+            })
+            .bind(("ids", ids.to_vec()))
+            .bind(("collection_name", self.collection_name.to_owned()))
+            .bind(("collection_metadata_key", self.get_collection_metdata_key()))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    async fn get_by_ids(
+        &self,
+        ids: &[String],
+        _opt: &Self::Options,
+    ) -> Result<Vec<Document>, Box<dyn Error>> {
+        let collection_table_name = self.get_collection_table_name();
+        let collection_predicate = self.collection_predicate();
+
+        let mut result = self
+            .db
+            .query(formatdoc! {"
+                SELECT
+                    record::id(id) as id,
+                    text,
+                    metadata
+                FROM {collection_table_name}
+                WHERE record::id(id) IN $ids {collection_predicate}"
+            })
+            .bind(("ids", ids.to_vec()))
+            .bind(("collection_name", self.collection_name.to_owned()))
+            .bind(("collection_metadata_key", self.get_collection_metdata_key()))
+            .await?
+            .check()?;
+
+        let rows: Vec<Row> = result.take(0)?;
+        let mut documents: Vec<Document> = rows.into_iter().map(Row::into_document).collect();
+        sort_by_ids(&mut documents, ids);
+
+        Ok(documents)
+    }
 }
 
 #[derive(Deserialize, Debug)]
@@ -220,5 +300,17 @@ struct Row {
     id: String,
     text: String,
     metadata: HashMap<String, Value>,
+    #[serde(default)]
+    embedding: Vec<f64>,
+    #[serde(default)]
     similarity: f64,
 }
+
+impl Row {
+    fn into_document(self) -> Document {
+        Document::new(self.text)
+            .with_metadata(self.metadata)
+            .with_score(self.similarity)
+            .with_id(self.id)
+    }
+}
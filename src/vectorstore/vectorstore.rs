@@ -2,28 +2,195 @@ use std::error::Error;
 
 use async_trait::async_trait;
 
-use crate::schemas::{self, Document};
+use crate::{
+    callbacks::trace_retrieval,
+    embedding::embedder_trait::Embedder,
+    retrievers::try_join_retrievals,
+    schemas::{self, Document},
+};
 
-use super::VecStoreOptions;
+use super::{maximal_marginal_relevance, EmbedderOption, Fusion, VecStoreOptions};
 
 // VectorStore is the trait for saving and querying documents in the
 // form of vector embeddings.
 #[async_trait]
 pub trait VectorStore: Send + Sync {
-    type Options;
+    type Options: Sync + EmbedderOption;
 
+    /// Adds the documents and returns their ids. A document with an `id` replaces the stored
+    /// document with the same id, if any; the others get a new id.
     async fn add_documents(
         &self,
         docs: &[Document],
         opt: &Self::Options,
     ) -> Result<Vec<String>, Box<dyn Error>>;
 
+    /// Returns the `limit` documents most similar to the query, most similar first, with their
+    /// score set as by [`similarity_search_by_vector`](VectorStore::similarity_search_by_vector).
     async fn similarity_search(
         &self,
         query: &str,
         limit: usize,
         opt: &Self::Options,
     ) -> Result<Vec<Document>, Box<dyn Error>>;
+
+    /// Returns the `limit` documents most similar to `vector`, the embedding of a query, most
+    /// similar first.
+    ///
+    /// The score of the documents is normalized between 0 and 1, 1 being the most similar, from
+    /// the distance metric documented by each backend. The `score_threshold` of the options
+    /// applies to this score.
+    async fn similarity_search_by_vector(
+        &self,
+        _vector: &[f64],
+        _limit: usize,
+        _opt: &Self::Options,
+    ) -> Result<Vec<Document>, Box<dyn Error>> {
+        Err("similarity_search_by_vector is not supported by this vector store".into())
+    }
+
+    /// Returns the `limit` documents most similar to the query with their normalized score, see
+    /// [`similarity_search_by_vector`](VectorStore::similarity_search_by_vector).
+    async fn similarity_search_with_score(
+        &self,
+        query: &str,
+        limit: usize,
+        opt: &Self::Options,
+    ) -> Result<Vec<(Document, f64)>, Box<dyn Error>> {
+        let Some(embedder) = opt.embedder().or(self.embedder()) else {
+            return Err(
+                "similarity_search_with_score is not supported by this vector store".into(),
+            );
+        };
+        let vector = embedder.embed_query(query).await?;
+        let docs = self
+            .similarity_search_by_vector(&vector, limit, opt)
+            .await?;
+        Ok(docs
+            .into_iter()
+            .map(|doc| {
+                let score = doc.score;
+                (doc, score)
+            })
+            .collect())
+    }
+
+    /// Returns the embedder of the store, used by the default implementations when the options
+    /// do not have one.
+    fn embedder(&self) -> Option<&dyn Embedder> {
+        None
+    }
+
+    /// Returns the `limit` documents most similar to `vector` with their embedding, as
+    /// [`similarity_search_by_vector`](VectorStore::similarity_search_by_vector).
+    ///
+    /// The default implementation embeds the found documents again with the embedder of the
+    /// options or of the store. Backends returning their stored vectors override it.
+    async fn similarity_search_with_vectors(
+        &self,
+        vector: &[f64],
+        limit: usize,
+        opt: &Self::Options,
+    ) -> Result<Vec<(Document, Vec<f64>)>, Box<dyn Error>> {
+        let Some(embedder) = opt.embedder().or(self.embedder()) else {
+            return Err(
+                "similarity_search_with_vectors is not supported by this vector store".into(),
+            );
+        };
+        let docs = self.similarity_search_by_vector(vector, limit, opt).await?;
+        if docs.is_empty() {
+            return Ok(Vec::new());
+        }
+        let texts: Vec<String> = docs.iter().map(|doc| doc.page_content.clone()).collect();
+        let vectors = embedder.embed_documents(&texts).await?;
+        Ok(docs.into_iter().zip(vectors).collect())
+    }
+
+    /// Returns `k` documents among the `fetch_k` most similar to the query, selected by
+    /// maximal marginal relevance to be both relevant and diverse. `lambda` is between 0
+    /// (maximum diversity) and 1 (maximum relevance).
+    ///
+    /// The default implementation compares the embeddings returned by
+    /// [`similarity_search_with_vectors`](VectorStore::similarity_search_with_vectors).
+    async fn max_marginal_relevance_search(
+        &self,
+        query: &str,
+        k: usize,
+        fetch_k: usize,
+        lambda: f64,
+        opt: &Self::Options,
+    ) -> Result<Vec<Document>, Box<dyn Error>> {
+        let Some(embedder) = opt.embedder().or(self.embedder()) else {
+            return Err(
+                "max_marginal_relevance_search is not supported by this vector store".into(),
+            );
+        };
+        let query_vector = embedder.embed_query(query).await?;
+        let (docs, vectors): (Vec<Document>, Vec<Vec<f64>>) = self
+            .similarity_search_with_vectors(&query_vector, fetch_k, opt)
+            .await?
+            .into_iter()
+            .unzip();
+
+        Ok(select_by_indices(
+            docs,
+            &maximal_marginal_relevance(&query_vector, &vectors, k, lambda),
+        ))
+    }
+
+    /// Returns the `limit` documents matching the terms of the query best, by the full-text
+    /// search of the backend, best first.
+    ///
+    /// The score of the documents is the relevance computed by the backend, documented by each
+    /// backend, and is not normalized: the `score_threshold` of the options does not apply.
+    async fn keyword_search(
+        &self,
+        _query: &str,
+        _limit: usize,
+        _opt: &Self::Options,
+    ) -> Result<Vec<Document>, Box<dyn Error>> {
+        Err("keyword_search is not supported by this vector store".into())
+    }
+
+    /// Deletes the documents with the given ids. Unknown ids are ignored.
+    async fn delete(&self, _ids: &[String], _opt: &Self::Options) -> Result<(), Box<dyn Error>> {
+        Err("delete is not supported by this vector store".into())
+    }
+
+    /// Returns the documents with the given ids, in the order of `ids`. Unknown ids are skipped.
+    async fn get_by_ids(
+        &self,
+        _ids: &[String],
+        _opt: &Self::Options,
+    ) -> Result<Vec<Document>, Box<dyn Error>> {
+        Err("get_by_ids is not supported by this vector store".into())
+    }
+}
+
+/// Converts a cosine distance, between 0 and 2, to a score between 0 and 1. Opposite vectors
+/// get the same score as orthogonal ones.
+pub(crate) fn cosine_distance_to_score(distance: f64) -> f64 {
+    (1.0 - distance).clamp(0.0, 1.0)
+}
+
+/// Converts a euclidean distance to a score between 0 and 1, `1 / (1 + distance)`.
+pub(crate) fn euclidean_distance_to_score(distance: f64) -> f64 {
+    1.0 / (1.0 + distance.max(0.0))
+}
+
+/// Returns the documents at `indices`, in the order of `indices`.
+pub(crate) fn select_by_indices(docs: Vec<Document>, indices: &[usize]) -> Vec<Document> {
+    let mut docs: Vec<Option<Document>> = docs.into_iter().map(Some).collect();
+    indices.iter().filter_map(|&i| docs[i].take()).collect()
+}
+
+/// Sorts `docs` in the order of their id in `ids`.
+pub(crate) fn sort_by_ids(docs: &mut [Document], ids: &[String]) {
+    docs.sort_by_key(|doc| {
+        doc.id
+            .as_ref()
+            .and_then(|id| ids.iter().position(|i| i == id))
+    });
 }
 
 impl<VS, F> From<VS> for Box<dyn VectorStore<Options = F>>
@@ -59,11 +226,32 @@ macro_rules! similarity_search {
     };
 }
 
+/// How a [`Retriever`] searches the vector store.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchType {
+    Similarity,
+    /// Maximal marginal relevance among the `fetch_k` most similar documents, see
+    /// [`VectorStore::max_marginal_relevance_search`].
+    Mmr {
+        fetch_k: usize,
+        lambda: f64,
+    },
+    /// The `fetch_k` best documents of [`VectorStore::keyword_search`] and of the similarity
+    /// search, merged by `fusion`. `keyword_weight` is the weight of the keyword results,
+    /// between 0 and 1, the similarity results weighing the rest.
+    Hybrid {
+        fetch_k: usize,
+        keyword_weight: f64,
+        fusion: Fusion,
+    },
+}
+
 // Retriever is a retriever for vector stores.
 pub struct Retriever<F> {
     vstore: Box<dyn VectorStore<Options = VecStoreOptions<F>>>,
     num_docs: usize,
     options: VecStoreOptions<F>,
+    search_type: SearchType,
 }
 impl<F> Retriever<F> {
     pub fn new<V: Into<Box<dyn VectorStore<Options = VecStoreOptions<F>>>>>(
@@ -74,6 +262,7 @@ impl<F> Retriever<F> {
             vstore: vstore.into(),
             num_docs,
             options: VecStoreOptions::<F>::new(),
+            search_type: SearchType::Similarity,
         }
     }
 
@@ -81,13 +270,145 @@ impl<F> Retriever<F> {
         self.options = options;
         self
     }
+
+    pub fn with_search_type(mut self, search_type: SearchType) -> Self {
+        self.search_type = search_type;
+        self
+    }
+
+    /// Retrieves the documents by maximal marginal relevance among the `fetch_k` most similar.
+    pub fn with_mmr(self, fetch_k: usize, lambda: f64) -> Self {
+        self.with_search_type(SearchType::Mmr { fetch_k, lambda })
+    }
+
+    /// Retrieves the documents by reciprocal rank fusion of the `fetch_k` best documents of the
+    /// keyword and the similarity searches of the store.
+    pub fn with_hybrid(self, fetch_k: usize, keyword_weight: f64) -> Self {
+        self.with_search_type(SearchType::Hybrid {
+            fetch_k,
+            keyword_weight: keyword_weight.clamp(0.0, 1.0),
+            fusion: Fusion::default(),
+        })
+    }
 }
 
 #[async_trait]
 impl<O: Sync + Send> schemas::Retriever for Retriever<O> {
     async fn get_relevant_documents(&self, query: &str) -> Result<Vec<Document>, Box<dyn Error>> {
-        self.vstore
-            .similarity_search(query, self.num_docs, &self.options)
+        trace_retrieval(query, async {
+            match self.search_type {
+                SearchType::Similarity => {
+                    self.vstore
+                        .similarity_search(query, self.num_docs, &self.options)
+                        .await
+                }
+                SearchType::Mmr { fetch_k, lambda } => {
+                    self.vstore
+                        .max_marginal_relevance_search(
+                            query,
+                            self.num_docs,
+                            fetch_k,
+                            lambda,
+                            &self.options,
+                        )
+                        .await
+                }
+                SearchType::Hybrid {
+                    fetch_k,
+                    keyword_weight,
+                    fusion,
+                } => {
+                    let results = try_join_retrievals([
+                        self.vstore.keyword_search(query, fetch_k, &self.options),
+                        self.vstore.similarity_search(query, fetch_k, &self.options),
+                    ])
+                    .await?;
+                    Ok(fusion.fuse(
+                        results,
+                        &[keyword_weight, 1.0 - keyword_weight],
+                        self.num_docs,
+                    ))
+                }
+            }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::Value;
+
+    use crate::vectorstore::{
+        memory::{tests::WordCountEmbedder, InMemoryStore},
+        DistanceMetric,
+    };
+
+    use super::*;
+
+    /// A store relying on the default implementations for everything but the similarity search.
+    struct SimilarityOnly(InMemoryStore);
+
+    #[async_trait]
+    impl VectorStore for SimilarityOnly {
+        type Options = VecStoreOptions<Value>;
+
+        fn embedder(&self) -> Option<&dyn Embedder> {
+            self.0.embedder()
+        }
+
+        async fn add_documents(
+            &self,
+            docs: &[Document],
+            opt: &Self::Options,
+        ) -> Result<Vec<String>, Box<dyn Error>> {
+            self.0.add_documents(docs, opt).await
+        }
+
+        async fn similarity_search(
+            &self,
+            query: &str,
+            limit: usize,
+            opt: &Self::Options,
+        ) -> Result<Vec<Document>, Box<dyn Error>> {
+            self.0.similarity_search(query, limit, opt).await
+        }
+
+        async fn similarity_search_by_vector(
+            &self,
+            vector: &[f64],
+            limit: usize,
+            opt: &Self::Options,
+        ) -> Result<Vec<Document>, Box<dyn Error>> {
+            self.0.similarity_search_by_vector(vector, limit, opt).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_mmr_embeds_the_found_documents() {
+        let store = SimilarityOnly(InMemoryStore::new(
+            Arc::new(WordCountEmbedder),
+            DistanceMetric::Cosine,
+        ));
+        store
+            .add_documents(
+                &[
+                    Document::new("The cat sleeps"),
+                    Document::new("The cat sleeps, the cat dreams"),
+                    Document::new("The cat and the dog"),
+                ],
+                &VecStoreOptions::default(),
+            )
+            .await
+            .unwrap();
+
+        let retriever = Retriever::new(store, 2).with_mmr(3, 0.3);
+        let docs = schemas::Retriever::get_relevant_documents(&retriever, "cat")
             .await
+            .unwrap();
+        let texts: Vec<&str> = docs.iter().map(|d| d.page_content.as_str()).collect();
+        assert_eq!(texts, ["The cat sleeps", "The cat and the dog"]);
     }
 }